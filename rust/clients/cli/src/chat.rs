@@ -57,7 +57,11 @@ impl Chat {
                     // get message string
                     if let Some(message) = command_string.strip_prefix(group_id_str) {
                         // send message
-                        Self::send_chat_message(group_id, message.to_string().trim().to_string());
+                        Self::send_chat_message(
+                            group_id,
+                            message.to_string().trim().to_string(),
+                            Vec::new(),
+                        );
                         println!("chat message sent [{}] {}", group_id_str, message);
                         return;
                     } else {
@@ -117,6 +121,232 @@ impl Chat {
                     }
                 }
             }
+            // delete chat messages by id
+            cmd if cmd.starts_with("delete ") => {
+                let command_string = cmd.strip_prefix("delete ").unwrap().to_string();
+                let mut iter = command_string.split_whitespace();
+
+                if let Some(group_id) = iter.next().and_then(Self::group_id_from_str) {
+                    let mut message_ids: Vec<Vec<u8>> = Vec::new();
+                    for message_id_str in iter {
+                        match bs58::decode(message_id_str).into_vec() {
+                            Ok(message_id) => message_ids.push(message_id),
+                            Err(e) => {
+                                log::error!("invalid message id: {}", e);
+                                return;
+                            }
+                        }
+                    }
+                    if message_ids.len() == 0 {
+                        log::error!("no message id given");
+                        return;
+                    }
+                    Self::delete_messages(group_id, message_ids, 0, 0);
+                } else {
+                    log::error!("chat delete command incorrectly formatted");
+                }
+            }
+            // delete chat messages by index range
+            cmd if cmd.starts_with("purge ") => {
+                let command_string = cmd.strip_prefix("purge ").unwrap().to_string();
+                let mut iter = command_string.split_whitespace();
+
+                if let Some(group_id) = iter.next().and_then(Self::group_id_from_str) {
+                    let first_index = iter.next().and_then(|s| s.parse::<u64>().ok());
+                    let last_index = iter.next().and_then(|s| s.parse::<u64>().ok());
+                    match (first_index, last_index) {
+                        (Some(first_index), Some(last_index)) => {
+                            Self::delete_messages(group_id, Vec::new(), first_index, last_index);
+                        }
+                        _ => log::error!("chat purge index is not a valid number"),
+                    }
+                } else {
+                    log::error!("chat purge command incorrectly formatted");
+                }
+            }
+            // search chat messages
+            cmd if cmd.starts_with("search ") => {
+                let command_string = cmd.strip_prefix("search ").unwrap().to_string();
+                let mut request = proto::ChatSearchRequest {
+                    query: "".to_string(),
+                    group_id: Vec::new(),
+                    sender_id: Vec::new(),
+                    from: 0,
+                    to: 0,
+                    limit: 0,
+                };
+                let mut words: Vec<&str> = Vec::new();
+
+                // separate filters from the query
+                for word in command_string.split_whitespace() {
+                    if let Some(group_id_str) = word.strip_prefix("group=") {
+                        match Self::group_id_from_str(group_id_str) {
+                            Some(group_id) => request.group_id = group_id,
+                            None => return,
+                        }
+                    } else if let Some(sender_id_str) = word.strip_prefix("sender=") {
+                        match bs58::decode(sender_id_str).into_vec() {
+                            Ok(sender_id) => request.sender_id = sender_id,
+                            Err(e) => {
+                                log::error!("invalid sender id: {}", e);
+                                return;
+                            }
+                        }
+                    } else if let Some(from_str) = word.strip_prefix("from=") {
+                        match from_str.parse::<u64>() {
+                            Ok(from) => request.from = from,
+                            Err(_) => {
+                                log::error!("from is not a valid time stamp");
+                                return;
+                            }
+                        }
+                    } else if let Some(to_str) = word.strip_prefix("to=") {
+                        match to_str.parse::<u64>() {
+                            Ok(to) => request.to = to,
+                            Err(_) => {
+                                log::error!("to is not a valid time stamp");
+                                return;
+                            }
+                        }
+                    } else {
+                        words.push(word);
+                    }
+                }
+
+                if words.len() == 0 {
+                    log::error!("chat search query is missing");
+                    return;
+                }
+                request.query = words.join(" ");
+                Self::search_messages(request);
+            }
+            // reply to a chat message
+            cmd if cmd.starts_with("reply ") => {
+                match Self::group_message_text_from_command(cmd, "reply ") {
+                    Some((group_id, message_id, text)) => {
+                        Self::send_chat_message(group_id, text, message_id);
+                    }
+                    None => log::error!("chat reply command incorrectly formatted"),
+                }
+            }
+            // react to a chat message
+            cmd if cmd.starts_with("react ") => {
+                match Self::group_message_text_from_command(cmd, "react ") {
+                    Some((group_id, message_id, reaction)) => {
+                        Self::send_chat_reaction(group_id, message_id, reaction, false);
+                    }
+                    None => log::error!("chat react command incorrectly formatted"),
+                }
+            }
+            // remove a reaction from a chat message
+            cmd if cmd.starts_with("unreact ") => {
+                match Self::group_message_text_from_command(cmd, "unreact ") {
+                    Some((group_id, message_id, reaction)) => {
+                        Self::send_chat_reaction(group_id, message_id, reaction, true);
+                    }
+                    None => log::error!("chat unreact command incorrectly formatted"),
+                }
+            }
+            // edit an own chat message
+            cmd if cmd.starts_with("edit ") => {
+                match Self::group_message_text_from_command(cmd, "edit ") {
+                    Some((group_id, message_id, text)) => {
+                        Self::send_chat_edit(group_id, message_id, text, false);
+                    }
+                    None => log::error!("chat edit command incorrectly formatted"),
+                }
+            }
+            // redact an own chat message
+            cmd if cmd.starts_with("redact ") => {
+                let command_string = cmd.strip_prefix("redact ").unwrap().to_string();
+                let mut iter = command_string.split_whitespace();
+
+                let group_id = iter.next().and_then(Self::group_id_from_str);
+                let message_id = iter.next().and_then(|s| bs58::decode(s).into_vec().ok());
+                match (group_id, message_id) {
+                    (Some(group_id), Some(message_id)) => {
+                        Self::send_chat_edit(group_id, message_id, "".to_string(), true);
+                    }
+                    _ => log::error!("chat redact command incorrectly formatted"),
+                }
+            }
+            // enable or disable read receipts
+            cmd if cmd.starts_with("read-receipts ") => {
+                match cmd.strip_prefix("read-receipts ").unwrap().trim() {
+                    "on" => Self::send_read_receipts_set(true),
+                    "off" => Self::send_read_receipts_set(false),
+                    _ => log::error!("chat read-receipts command incorrectly formatted"),
+                }
+            }
+            // request read receipts of a group
+            cmd if cmd.starts_with("receipts ") => {
+                let command_string = cmd.strip_prefix("receipts ").unwrap().to_string();
+                match command_string
+                    .split_whitespace()
+                    .next()
+                    .and_then(Self::group_id_from_str)
+                {
+                    Some(group_id) => Self::request_read_receipts(group_id),
+                    None => log::error!("chat receipts command incorrectly formatted"),
+                }
+            }
+            // send typing notification
+            cmd if cmd.starts_with("typing ") => {
+                let command_string = cmd.strip_prefix("typing ").unwrap().to_string();
+                match command_string
+                    .split_whitespace()
+                    .next()
+                    .and_then(Self::group_id_from_str)
+                {
+                    Some(group_id) => Self::send_typing(group_id, true),
+                    None => log::error!("chat typing command incorrectly formatted"),
+                }
+            }
+            // send stopped typing notification
+            cmd if cmd.starts_with("typing-stop ") => {
+                let command_string = cmd.strip_prefix("typing-stop ").unwrap().to_string();
+                match command_string
+                    .split_whitespace()
+                    .next()
+                    .and_then(Self::group_id_from_str)
+                {
+                    Some(group_id) => Self::send_typing(group_id, false),
+                    None => log::error!("chat typing-stop command incorrectly formatted"),
+                }
+            }
+            // request typing users of a group
+            cmd if cmd.starts_with("typing-users ") => {
+                let command_string = cmd.strip_prefix("typing-users ").unwrap().to_string();
+                match command_string
+                    .split_whitespace()
+                    .next()
+                    .and_then(Self::group_id_from_str)
+                {
+                    Some(group_id) => Self::request_typing(group_id),
+                    None => log::error!("chat typing-users command incorrectly formatted"),
+                }
+            }
+            // request delivery status of a message
+            cmd if cmd.starts_with("status ") => {
+                match Self::message_id_from_command(cmd, "status ") {
+                    Some(message_id) => Self::request_message_status(message_id),
+                    None => log::error!("chat status command incorrectly formatted"),
+                }
+            }
+            // resend a message
+            cmd if cmd.starts_with("resend ") => {
+                match Self::message_id_from_command(cmd, "resend ") {
+                    Some(message_id) => Self::resend_message(message_id),
+                    None => log::error!("chat resend command incorrectly formatted"),
+                }
+            }
+            // cancel the sending of a message
+            cmd if cmd.starts_with("cancel ") => {
+                match Self::message_id_from_command(cmd, "cancel ") {
+                    Some(message_id) => Self::cancel_message(message_id),
+                    None => log::error!("chat cancel command incorrectly formatted"),
+                }
+            }
             // unknown command
             _ => log::error!("unknown chat command"),
         }
@@ -139,6 +369,61 @@ impl Chat {
         }
     }
 
+    /// Get the binary group id from a string
+    fn group_id_from_str(group_id_str: &str) -> Option<Vec<u8>> {
+        match Self::id_string_to_bin(group_id_str.to_string()) {
+            Ok(group_id) => Some(group_id),
+            Err(_) => match Self::uuid_string_to_bin(group_id_str.to_string()) {
+                Ok(group_id) => Some(group_id),
+                Err(e) => {
+                    log::error!("{}", e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Get the binary message id from a command
+    fn message_id_from_command(cmd: &str, prefix: &str) -> Option<Vec<u8>> {
+        let command_string = cmd.strip_prefix(prefix)?.to_string();
+        let message_id_str = command_string.split_whitespace().next()?;
+
+        match bs58::decode(message_id_str).into_vec() {
+            Ok(message_id) => Some(message_id),
+            Err(e) => {
+                log::error!("invalid message id: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Get group id, message id and text from a command
+    ///
+    /// The command is formatted as follows:
+    /// {Group ID} {Message ID} {Text}
+    fn group_message_text_from_command(
+        cmd: &str,
+        prefix: &str,
+    ) -> Option<(Vec<u8>, Vec<u8>, String)> {
+        let command_string = cmd.strip_prefix(prefix)?.trim_start();
+        let (group_id_str, rest) = command_string.split_once(' ')?;
+        let rest = rest.trim_start();
+        let (message_id_str, text) = rest.split_once(' ')?;
+        let text = text.trim().to_string();
+        if text.len() == 0 {
+            return None;
+        }
+
+        let group_id = Self::group_id_from_str(group_id_str)?;
+        match bs58::decode(message_id_str).into_vec() {
+            Ok(message_id) => Some((group_id, message_id, text)),
+            Err(e) => {
+                log::error!("invalid message id: {}", e);
+                None
+            }
+        }
+    }
+
     /// Convert Group ID from String to Binary
     fn uuid_string_to_bin(id_str: String) -> Result<Vec<u8>, String> {
         match uuid::Uuid::parse_str(id_str.as_str()) {
@@ -148,12 +433,16 @@ impl Chat {
     }
 
     /// Create and send feed message via rpc
-    fn send_chat_message(group_id: Vec<u8>, message_text: String) {
+    ///
+    /// If `reply_to` contains a message id, the message
+    /// is sent as a reply to this message.
+    fn send_chat_message(group_id: Vec<u8>, message_text: String, reply_to: Vec<u8>) {
         // create feed send message
         let proto_message = proto::Chat {
             message: Some(proto::chat::Message::Send(proto::ChatMessageSend {
                 group_id,
                 content: message_text,
+                reply_to,
             })),
         };
 
@@ -167,6 +456,117 @@ impl Chat {
         Rpc::send_message(buf, super::rpc::proto::Modules::Chat.into(), "".to_string());
     }
 
+    /// Send a reaction to a chat message via rpc
+    fn send_chat_reaction(group_id: Vec<u8>, message_id: Vec<u8>, reaction: String, remove: bool) {
+        // create reaction message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::ReactionSend(
+                proto::ChatReactionSend {
+                    group_id,
+                    message_id,
+                    reaction,
+                    remove,
+                },
+            )),
+        };
+
+        // encode message
+        let mut buf = Vec::with_capacity(proto_message.encoded_len());
+        proto_message
+            .encode(&mut buf)
+            .expect("Vec<u8> provides capacity as needed");
+
+        // send message
+        Rpc::send_message(buf, super::rpc::proto::Modules::Chat.into(), "".to_string());
+    }
+
+    /// Send an edit or redaction of a chat message via rpc
+    fn send_chat_edit(group_id: Vec<u8>, message_id: Vec<u8>, content: String, redact: bool) {
+        // create edit message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::EditSend(proto::ChatEditSend {
+                group_id,
+                message_id,
+                content,
+                redact,
+            })),
+        };
+
+        // encode message
+        let mut buf = Vec::with_capacity(proto_message.encoded_len());
+        proto_message
+            .encode(&mut buf)
+            .expect("Vec<u8> provides capacity as needed");
+
+        // send message
+        Rpc::send_message(buf, super::rpc::proto::Modules::Chat.into(), "".to_string());
+    }
+
+    /// Enable or disable read receipts via rpc
+    fn send_read_receipts_set(enabled: bool) {
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::ReadReceiptsSet(
+                proto::ChatReadReceiptsSet { enabled },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Request the read receipts of a group via rpc
+    fn request_read_receipts(group_id: Vec<u8>) {
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::ReadReceiptsRequest(
+                proto::ChatReadReceiptsRequest { group_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Send a typing notification via rpc
+    fn send_typing(group_id: Vec<u8>, typing: bool) {
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::TypingSend(proto::ChatTypingSend {
+                group_id,
+                typing,
+            })),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Request the typing users of a group via rpc
+    fn request_typing(group_id: Vec<u8>) {
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::TypingRequest(
+                proto::ChatTypingRequest { group_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
     /// Request chat conversation via rpc
     ///
     /// This provides all chat messages of a specific conversation.
@@ -192,12 +592,145 @@ impl Chat {
         Rpc::send_message(buf, super::rpc::proto::Modules::Chat.into(), "".to_string());
     }
 
+    /// Request the delivery status of a message via rpc
+    fn request_message_status(message_id: Vec<u8>) {
+        // create status request message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::StatusRequest(
+                proto::ChatMessageStatusRequest { message_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Delete chat messages via rpc
+    fn delete_messages(
+        group_id: Vec<u8>,
+        message_ids: Vec<Vec<u8>>,
+        first_index: u64,
+        last_index: u64,
+    ) {
+        // create delete request message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::DeleteRequest(
+                proto::ChatDeleteRequest {
+                    group_id,
+                    message_ids,
+                    first_index,
+                    last_index,
+                },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Search chat messages via rpc
+    fn search_messages(request: proto::ChatSearchRequest) {
+        // create search request message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::SearchRequest(request)),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Resend a message via rpc
+    fn resend_message(message_id: Vec<u8>) {
+        // create resend request message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::ResendRequest(
+                proto::ChatMessageResendRequest { message_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Cancel the sending of a message via rpc
+    fn cancel_message(message_id: Vec<u8>) {
+        // create cancel request message
+        let proto_message = proto::Chat {
+            message: Some(proto::chat::Message::CancelRequest(
+                proto::ChatMessageCancelRequest { message_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+        );
+    }
+
+    /// Print message status
+    fn print_message_status(status: i32) {
+        match proto::MessageStatus::from_i32(status) {
+            Some(proto::MessageStatus::Sending) => print!(".. | "),
+            Some(proto::MessageStatus::Sent) => print!("✓. | "),
+            Some(proto::MessageStatus::Confirmed) => print!("✓✓ | "),
+            Some(proto::MessageStatus::ConfirmedByAll) => print!("✓✓✓| "),
+            Some(proto::MessageStatus::Receiving) => print!("🚚 | "),
+            Some(proto::MessageStatus::Received) => print!("📨 | "),
+            Some(proto::MessageStatus::Failed) => print!("✗  | "),
+            None => print!("?? | "),
+        }
+    }
+
+    /// Print edits and reactions of a message
+    fn print_message_changes(
+        redacted: bool,
+        edited_at: u64,
+        reactions: &Vec<proto::MessageReaction>,
+    ) {
+        if redacted {
+            println!("\t[redacted at {}]", edited_at);
+        } else if edited_at > 0 {
+            println!("\t[edited at {}]", edited_at);
+        }
+        for reaction in reactions {
+            println!(
+                "\t{} {}",
+                reaction.reaction,
+                bs58::encode(&reaction.user_id).into_string()
+            );
+        }
+    }
+
     fn analyze_content(content: &Vec<u8>) -> Result<Vec<String>, String> {
         let mut res: Vec<String> = vec![];
 
         if let Ok(content_message) = proto::ChatContentMessage::decode(&content[..]) {
             match content_message.message {
                 Some(proto::chat_content_message::Message::ChatContent(chat_content)) => {
+                    if chat_content.reply_to.len() > 0 {
+                        res.push(
+                            "reply to: ".to_string()
+                                + bs58::encode(chat_content.reply_to).into_string().as_str(),
+                        );
+                    }
                     res.push(chat_content.text);
                     return Ok(res);
                 }
@@ -210,7 +743,9 @@ impl Chat {
                         " name: ".to_string()
                             + file_content.file_name.as_str()
                             + " size: "
-                            + file_content.file_size.to_string().as_str(),
+                            + file_content.file_size.to_string().as_str()
+                            + " type: "
+                            + file_content.mime_type.as_str(),
                     );
                     return Ok(res);
                 }
@@ -277,14 +812,7 @@ impl Chat {
                         for message in proto_conversation.message_list {
                             if let Ok(ss) = Self::analyze_content(&message.content) {
                                 print! {"{} | ", message.index};
-                                match proto::MessageStatus::from_i32(message.status).unwrap() {
-                                    proto::MessageStatus::Sending => print!(".. | "),
-                                    proto::MessageStatus::Sent => print!("✓. | "),
-                                    proto::MessageStatus::Confirmed => print!("✓✓ | "),
-                                    proto::MessageStatus::ConfirmedByAll => print!("✓✓✓| "),
-                                    proto::MessageStatus::Receiving => print!("🚚 | "),
-                                    proto::MessageStatus::Received => print!("📨 | "),
-                                }
+                                Self::print_message_status(message.status);
 
                                 print!("{} | ", message.sent_at);
                                 println!("{}", bs58::encode(message.sender_id).into_string());
@@ -294,6 +822,45 @@ impl Chat {
                                     message.received_at
                                 );
 
+                                for s in ss {
+                                    println!("\t{}", s);
+                                }
+                                Self::print_message_changes(
+                                    message.redacted,
+                                    message.edited_at,
+                                    &message.reactions,
+                                );
+                                println!("");
+                            }
+                        }
+                    }
+                    Some(proto::chat::Message::SearchResponse(search_response)) => {
+                        println!("");
+                        println!(
+                            "Search [ {} ] {} results",
+                            search_response.query,
+                            search_response.message_list.len()
+                        );
+                        println!("");
+                        println!("Group ID | No. | Sent At | Sender ID");
+                        println!("  [Message ID]");
+                        println!("  Message Content");
+                        println!("");
+
+                        for message in search_response.message_list {
+                            if let Ok(ss) = Self::analyze_content(&message.content) {
+                                let group_id = match message.group_id.clone().try_into() {
+                                    Ok(id) => uuid::Uuid::from_bytes(id).to_string(),
+                                    Err(_) => bs58::encode(message.group_id).into_string(),
+                                };
+                                println!(
+                                    "{} | {} | {} | {}",
+                                    group_id,
+                                    message.index,
+                                    message.sent_at,
+                                    bs58::encode(message.sender_id).into_string()
+                                );
+                                println!(" [{}]", bs58::encode(message.message_id).into_string());
                                 for s in ss {
                                     println!("\t{}", s);
                                 }
@@ -301,6 +868,93 @@ impl Chat {
                             }
                         }
                     }
+                    Some(proto::chat::Message::ReadReceiptsResponse(receipts_response)) => {
+                        println!("");
+                        match receipts_response.enabled {
+                            true => println!("Read Receipts (sending enabled)"),
+                            false => println!("Read Receipts (sending disabled)"),
+                        }
+                        println!("User ID | Last Read Message ID | Read At");
+                        for receipt in receipts_response.receipts {
+                            println!(
+                                "{} | {} | {}",
+                                bs58::encode(receipt.user_id).into_string(),
+                                bs58::encode(receipt.last_message_id).into_string(),
+                                receipt.read_at
+                            );
+                        }
+                        println!("");
+                    }
+                    Some(proto::chat::Message::TypingResponse(typing_response)) => {
+                        println!("");
+                        if typing_response.user_ids.len() == 0 {
+                            println!("nobody is typing");
+                        }
+                        for user_id in typing_response.user_ids {
+                            println!("{} is typing", bs58::encode(user_id).into_string());
+                        }
+                        println!("");
+                    }
+                    Some(proto::chat::Message::DeleteResponse(delete_response)) => {
+                        println!("");
+                        println!("{} chat messages deleted", delete_response.deleted);
+                        println!("");
+                    }
+                    Some(proto::chat::Message::StatusResponse(status_response)) => {
+                        println!("");
+                        println!(
+                            "Message Status [ {} ]",
+                            bs58::encode(status_response.message_id).into_string()
+                        );
+                        if !status_response.found {
+                            println!("  message not found");
+                            println!("");
+                            return;
+                        }
+                        if status_response.changed > 0 {
+                            println!("  {} deliveries changed", status_response.changed);
+                        }
+
+                        // print chat message
+                        if let Some(message) = status_response.message {
+                            print!("  ");
+                            Self::print_message_status(message.status);
+                            println!("{} | {}", message.sent_at, message.received_at);
+                            for confirmation in message.message_reception_confirmed {
+                                println!(
+                                    "  confirmed by {} at {}",
+                                    bs58::encode(confirmation.user_id).into_string(),
+                                    confirmation.confirmed_at
+                                );
+                            }
+                        }
+
+                        // print open deliveries
+                        println!("");
+                        println!("Receiver ID | State | Attempts | Created At | Last Try");
+                        for delivery in status_response.deliveries {
+                            let state = match proto::DeliveryState::from_i32(delivery.state) {
+                                Some(proto::DeliveryState::DeliveryPending) => "pending",
+                                Some(proto::DeliveryState::DeliverySent) => "sent",
+                                Some(proto::DeliveryState::DeliveryDtn) => "DTN",
+                                Some(proto::DeliveryState::DeliveryCustody) => "custody",
+                                Some(proto::DeliveryState::DeliveryFailed) => "failed",
+                                None => "unknown",
+                            };
+                            println!(
+                                "{} | {} | {} | {} | {}",
+                                bs58::encode(delivery.receiver_id).into_string(),
+                                state,
+                                delivery.try_count,
+                                delivery.created_at,
+                                delivery.last_try
+                            );
+                            if delivery.failure.len() > 0 {
+                                println!("  failure: {}", delivery.failure);
+                            }
+                        }
+                        println!("");
+                    }
 
                     _ => {
                         log::error!("unprocessable RPC chat message");
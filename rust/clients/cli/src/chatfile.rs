@@ -70,20 +70,84 @@ impl ChatFile {
             cmd if cmd.starts_with("history") => {
                 let mut offset: i32 = 0;
                 let mut limit: i32 = 10;
+                let mut group_id: Vec<u8> = Vec::new();
+                let mut sender_id: Vec<u8> = Vec::new();
 
                 if cmd.starts_with("history ") {
                     let command_string = cmd.strip_prefix("history ").unwrap().to_string();
-                    let mut iter = command_string.split_whitespace();
+                    let mut iter = command_string.split_whitespace().peekable();
 
-                    if let Some(offset_str) = iter.next() {
+                    // pagination
+                    if let Some(offset_str) = iter.next_if(|s| s.parse::<i32>().is_ok()) {
                         offset = offset_str.to_string().parse().unwrap();
-                        if let Some(limit_str) = iter.next() {
+                        if let Some(limit_str) = iter.next_if(|s| s.parse::<i32>().is_ok()) {
                             limit = limit_str.to_string().parse().unwrap();
                         }
                     }
+
+                    // filters
+                    while let Some(filter) = iter.next() {
+                        match (filter, iter.next()) {
+                            ("group", Some(id_str)) => {
+                                match Self::uuid_string_to_bin(id_str.to_string()) {
+                                    Ok(id) => group_id = id,
+                                    Err(_) => match Self::id_string_to_bin(id_str.to_string()) {
+                                        Ok(id) => group_id = id,
+                                        Err(_) => {
+                                            log::error!("Invalid group id");
+                                            return;
+                                        }
+                                    },
+                                }
+                            }
+                            ("sender", Some(id_str)) => match bs58::decode(id_str).into_vec() {
+                                Ok(id) => sender_id = id,
+                                Err(_) => {
+                                    log::error!("Invalid sender id");
+                                    return;
+                                }
+                            },
+                            _ => {
+                                log::error!("file history command incorrectly formatted");
+                                return;
+                            }
+                        }
+                    }
                 }
 
-                Self::send_file_history_command(offset as u32, limit as u32);
+                Self::send_file_history_command(offset as u32, limit as u32, group_id, sender_id);
+            }
+            // delete a file
+            cmd if cmd.starts_with("delete ") => {
+                match cmd.strip_prefix("delete ").unwrap().trim().parse::<u64>() {
+                    Ok(file_id) => Self::send_delete_command(file_id),
+                    Err(_) => log::error!("file delete command incorrectly formatted"),
+                }
+            }
+            // file storage usage
+            cmd if cmd.starts_with("storage") => {
+                Self::send_storage_command();
+            }
+            // pause the reception of a file
+            cmd if cmd.starts_with("pause ") => {
+                match cmd.strip_prefix("pause ").unwrap().trim().parse::<u64>() {
+                    Ok(file_id) => Self::send_pause_command(file_id),
+                    Err(_) => log::error!("file pause command incorrectly formatted"),
+                }
+            }
+            // resume the reception of a file
+            cmd if cmd.starts_with("resume ") => {
+                match cmd.strip_prefix("resume ").unwrap().trim().parse::<u64>() {
+                    Ok(file_id) => Self::send_resume_command(file_id),
+                    Err(_) => log::error!("file resume command incorrectly formatted"),
+                }
+            }
+            // cancel the transfer of a file
+            cmd if cmd.starts_with("cancel ") => {
+                match cmd.strip_prefix("cancel ").unwrap().trim().parse::<u64>() {
+                    Ok(file_id) => Self::send_cancel_command(file_id),
+                    Err(_) => log::error!("file cancel command incorrectly formatted"),
+                }
             }
             // unknown command
             _ => log::error!("unknown file command"),
@@ -124,6 +188,8 @@ impl ChatFile {
                     path_name: file_name.clone(),
                     group_id: group_id.clone(),
                     description: description.clone(),
+                    mime_type: "".to_string(),
+                    preview: Vec::new(),
                 },
             )),
         };
@@ -143,11 +209,16 @@ impl ChatFile {
     }
 
     /// send file history list command via rpc
-    fn send_file_history_command(offset: u32, limit: u32) {
+    fn send_file_history_command(offset: u32, limit: u32, group_id: Vec<u8>, sender_id: Vec<u8>) {
         // create file history message
         let proto_message = proto::ChatFile {
             message: Some(proto::chat_file::Message::FileHistory(
-                proto::FileHistoryRequest { offset, limit },
+                proto::FileHistoryRequest {
+                    offset,
+                    limit,
+                    group_id,
+                    sender_id,
+                },
             )),
         };
 
@@ -165,6 +236,103 @@ impl ChatFile {
         );
     }
 
+    /// send file pause command via rpc
+    fn send_pause_command(file_id: u64) {
+        let proto_message = proto::ChatFile {
+            message: Some(proto::chat_file::Message::PauseRequest(
+                proto::FilePauseRequest { file_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+        );
+    }
+
+    /// send file resume command via rpc
+    fn send_resume_command(file_id: u64) {
+        let proto_message = proto::ChatFile {
+            message: Some(proto::chat_file::Message::ResumeRequest(
+                proto::FileResumeRequest { file_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+        );
+    }
+
+    /// send file cancel command via rpc
+    fn send_cancel_command(file_id: u64) {
+        let proto_message = proto::ChatFile {
+            message: Some(proto::chat_file::Message::CancelRequest(
+                proto::FileCancelRequest { file_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+        );
+    }
+
+    /// send file delete command via rpc
+    fn send_delete_command(file_id: u64) {
+        let proto_message = proto::ChatFile {
+            message: Some(proto::chat_file::Message::DeleteRequest(
+                proto::FileDeleteRequest { file_id },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+        );
+    }
+
+    /// send file storage usage request via rpc
+    fn send_storage_command() {
+        let proto_message = proto::ChatFile {
+            message: Some(proto::chat_file::Message::StorageRequest(
+                proto::FileStorageRequest {},
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+        );
+    }
+
+    /// get the display name of a file state
+    fn state_name(state: i32) -> &'static str {
+        match proto::FileHistoryState::from_i32(state) {
+            Some(proto::FileHistoryState::FileSending) => "sending",
+            Some(proto::FileHistoryState::FileSent) => "sent",
+            Some(proto::FileHistoryState::FileConfirmed) => "confirmed",
+            Some(proto::FileHistoryState::FileConfirmedByAll) => "confirmed by all",
+            Some(proto::FileHistoryState::FileReceiving) => "receiving",
+            Some(proto::FileHistoryState::FileReceived) => "received",
+            Some(proto::FileHistoryState::FileFailed) => "failed",
+            Some(proto::FileHistoryState::FileVerificationFailed) => "verification failed",
+            Some(proto::FileHistoryState::FilePaused) => "paused",
+            Some(proto::FileHistoryState::FileCancelled) => "cancelled",
+            None => "unknown",
+        }
+    }
+
     /// Process received RPC message
     ///
     /// Decodes received protobuf encoded binary RPC message
@@ -194,9 +362,54 @@ impl ChatFile {
                                 "\t FileSize: {}, Description: {}",
                                 entry.file_size, entry.file_description
                             );
+                            println!(
+                                "\t MIME type: {}, Preview: {} bytes",
+                                entry.mime_type,
+                                entry.preview.len()
+                            );
+                            println!(
+                                "\t State: {}, SHA-256: {}",
+                                Self::state_name(entry.state),
+                                bs58::encode(entry.file_hash).into_string()
+                            );
                             println!("");
                         }
                     }
+                    Some(proto::chat_file::Message::DeleteResponse(response)) => {
+                        if response.success {
+                            println!("File {} deleted", response.file_id);
+                        } else {
+                            println!(
+                                "File {} could not be deleted: {}",
+                                response.file_id, response.error
+                            );
+                        }
+                    }
+                    Some(proto::chat_file::Message::StorageResponse(response)) => {
+                        println!("====================================");
+                        println!("File Storage Usage");
+                        println!("------------------------------------");
+                        for group in response.groups {
+                            let group_id = match uuid::Uuid::from_slice(&group.group_id) {
+                                Ok(id) => id.to_string(),
+                                Err(_) => bs58::encode(group.group_id).into_string(),
+                            };
+                            println!(
+                                "{} - {} files, {} bytes",
+                                group_id, group.file_count, group.size
+                            );
+                        }
+                    }
+                    Some(proto::chat_file::Message::Progress(progress)) => {
+                        println!(
+                            "File {} {}: {}/{} chunks, {} confirmed",
+                            progress.file_id,
+                            Self::state_name(progress.state),
+                            progress.chunks_transferred,
+                            progress.chunk_count,
+                            progress.chunks_confirmed
+                        );
+                    }
                     _ => {
                         log::error!("unprocessable RPC file message");
                     }
@@ -68,6 +68,39 @@ impl Dtn {
                     log::error!("invalid command parameter");
                 }
             },
+            // usage per user
+            cmd if cmd.starts_with("usage") => match cmd.strip_prefix("usage ") {
+                Some(user_id_str) => {
+                    if let Ok(id) = Self::id_string_to_bin(user_id_str.to_string()) {
+                        Self::dtn_usage(id);
+                    } else {
+                        log::error!("invalid user id");
+                    }
+                }
+                None => {
+                    Self::dtn_usage(Vec::new());
+                }
+            },
+            // set per user quota
+            cmd if cmd.starts_with("quota ") => match cmd.strip_prefix("quota ") {
+                Some(quota_str) => {
+                    let mut iter = quota_str.split_whitespace();
+                    match (
+                        iter.next().map(|v| v.parse::<u32>()),
+                        iter.next().map(|v| v.parse::<u32>()),
+                    ) {
+                        (Some(Ok(user_size_max)), Some(Ok(user_message_max))) => {
+                            Self::dtn_user_quota(user_size_max, user_message_max);
+                        }
+                        _ => {
+                            log::error!("invalid user quota");
+                        }
+                    }
+                }
+                None => {
+                    log::error!("invalid command parameter");
+                }
+            },
             // unknown command
             _ => log::error!("unknown dtn command"),
         }
@@ -170,6 +203,41 @@ impl Dtn {
         );
     }
 
+    /// dtn usage per user
+    fn dtn_usage(user_id: Vec<u8>) {
+        // create dtn usage message
+        let proto_message = proto::Dtn {
+            message: Some(proto::dtn::Message::DtnUsageRequest(
+                proto::DtnUsageRequest { user_id },
+            )),
+        };
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Dtn.into(),
+            "".to_string(),
+        );
+    }
+
+    /// dtn user quota
+    fn dtn_user_quota(user_size_max: u32, user_message_max: u32) {
+        // create dtn user quota message
+        let proto_message = proto::Dtn {
+            message: Some(proto::dtn::Message::DtnSetUserQuotaRequest(
+                proto::DtnSetUserQuotaRequest {
+                    user_size_max,
+                    user_message_max,
+                },
+            )),
+        };
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Dtn.into(),
+            "".to_string(),
+        );
+    }
+
     /// Process received RPC message
     ///
     /// Decodes received protobuf encoded binary RPC message
@@ -183,11 +251,21 @@ impl Dtn {
                     println!("\tUsed Storage Size: {} MB", dtn_state.used_size);
                     println!("\tDTN Messages: {}", dtn_state.dtn_message_count);
                     println!("\tUnconfirmed Messages: {}", dtn_state.unconfirmed_count);
+                    println!("\tExpired Messages: {}", dtn_state.expired_count);
+                    println!("\tLast Expiry Sweep: {}", dtn_state.last_sweep_at);
                 }
                 Some(proto::dtn::Message::DtnConfigResponse(dtn_config)) => {
                     println!("====================================");
                     println!("DTN Options");
                     println!("\tMaximum Storage Size: {} MB", dtn_config.total_size);
+                    println!(
+                        "\tMaximum Storage Size per User: {} MB",
+                        dtn_config.user_size_max
+                    );
+                    println!(
+                        "\tMaximum Messages per User: {}",
+                        dtn_config.user_message_max
+                    );
                     println!("\tUsers");
                     for user in dtn_config.users {
                         println!("\t\t{}", bs58::encode(user).into_string());
@@ -223,6 +301,25 @@ impl Dtn {
                         println!("\t{}", resp.message);
                     }
                 }
+                Some(proto::dtn::Message::DtnUsageResponse(resp)) => {
+                    println!("====================================");
+                    println!("DTN Usage per User");
+                    for user in resp.users {
+                        println!("\t{}", bs58::encode(user.user_id).into_string());
+                        println!("\t\tUsed Storage Size: {} bytes", user.used_size);
+                        println!("\t\tDTN Messages: {}", user.message_count);
+                    }
+                }
+                Some(proto::dtn::Message::DtnSetUserQuotaResponse(resp)) => {
+                    println!("====================================");
+                    println!("DTN Set User Quota");
+                    if resp.status {
+                        println!("\tSuccess");
+                    } else {
+                        println!("\tFailed");
+                        println!("\t{}", resp.message);
+                    }
+                }
                 _ => {
                     log::error!("unprocessable RPC group chat message");
                 }
@@ -40,6 +40,38 @@ impl Feed {
                     }
                 }
             },
+            // request a page of feed messages
+            cmd if cmd.starts_with("page ") => {
+                match Self::parse_page_request(cmd.strip_prefix("page ").unwrap()) {
+                    Ok(request) => Self::request_feed_page(request),
+                    Err(e) => log::error!("{}", e),
+                }
+            },
+            // look up a single feed message
+            cmd if cmd.starts_with("get ") => {
+                match bs58::decode(cmd.strip_prefix("get ").unwrap().trim()).into_vec() {
+                    Ok(message_id) => Self::request_feed_message(message_id),
+                    Err(_) => log::error!("feed message id is not valid"),
+                }
+            },
+            // mute a sender
+            cmd if cmd.starts_with("mute ") => {
+                match bs58::decode(cmd.strip_prefix("mute ").unwrap().trim()).into_vec() {
+                    Ok(sender_id) => Self::request_mute(sender_id, true),
+                    Err(_) => log::error!("user id is not valid"),
+                }
+            },
+            // unmute a sender
+            cmd if cmd.starts_with("unmute ") => {
+                match bs58::decode(cmd.strip_prefix("unmute ").unwrap().trim()).into_vec() {
+                    Ok(sender_id) => Self::request_mute(sender_id, false),
+                    Err(_) => log::error!("user id is not valid"),
+                }
+            },
+            // list muted senders
+            cmd if cmd.starts_with("muted") => {
+                Self::request_mute_list();
+            },
             // unknown command
             _ => log::error!("unknown feed command"),
         }
@@ -84,6 +116,127 @@ impl Feed {
         Rpc::send_message(buf, super::rpc::proto::Modules::Feed.into(), "".to_string());
     }
 
+    /// parse the arguments of the feed page command
+    ///
+    /// `{older|newer} {Start Index} {Limit} [sender {User ID}] [from {Time}] [to {Time}]`
+    fn parse_page_request(arguments: &str) -> Result<proto::FeedPageRequest, String> {
+        let mut iter = arguments.split_whitespace();
+
+        let direction = match iter.next() {
+            Some("older") => proto::FeedPageDirection::FeedOlder,
+            Some("newer") => proto::FeedPageDirection::FeedNewer,
+            _ => return Err("feed page direction must be older or newer".to_string()),
+        };
+        let start_index = match iter.next().map(|s| s.parse::<u64>()) {
+            Some(Ok(index)) => index,
+            _ => return Err("feed page start index is not a valid number".to_string()),
+        };
+        let limit = match iter.next().map(|s| s.parse::<u32>()) {
+            Some(Ok(limit)) => limit,
+            _ => return Err("feed page limit is not a valid number".to_string()),
+        };
+
+        let mut request = proto::FeedPageRequest {
+            start_index,
+            direction: direction as i32,
+            limit,
+            sender_id: Vec::new(),
+            time_from: 0,
+            time_to: 0,
+        };
+
+        // filters
+        while let Some(filter) = iter.next() {
+            match (filter, iter.next()) {
+                ("sender", Some(id_str)) => {
+                    match bs58::decode(id_str).into_vec() {
+                        Ok(id) => request.sender_id = id,
+                        Err(_) => return Err("sender id is not valid".to_string()),
+                    }
+                },
+                ("from", Some(time_str)) => {
+                    match time_str.parse::<u64>() {
+                        Ok(time) => request.time_from = time,
+                        Err(_) => return Err("from time is not a valid number".to_string()),
+                    }
+                },
+                ("to", Some(time_str)) => {
+                    match time_str.parse::<u64>() {
+                        Ok(time) => request.time_to = time,
+                        Err(_) => return Err("to time is not a valid number".to_string()),
+                    }
+                },
+                _ => return Err("feed page command incorrectly formatted".to_string()),
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// request a page of feed messages via rpc
+    fn request_feed_page(request: proto::FeedPageRequest) {
+        let proto_message = proto::Feed {
+            message: Some(proto::feed::Message::PageRequest(request)),
+        };
+
+        // send message
+        Rpc::send_message(proto_message.encode_to_vec(), super::rpc::proto::Modules::Feed.into(), "".to_string());
+    }
+
+    /// request a single feed message via rpc
+    fn request_feed_message(message_id: Vec<u8>) {
+        let proto_message = proto::Feed {
+            message: Some(proto::feed::Message::LookupRequest(
+                proto::FeedLookupRequest{
+                    message_id,
+                }
+            )),
+        };
+
+        // send message
+        Rpc::send_message(proto_message.encode_to_vec(), super::rpc::proto::Modules::Feed.into(), "".to_string());
+    }
+
+    /// mute or unmute a sender via rpc
+    fn request_mute(sender_id: Vec<u8>, mute: bool) {
+        let proto_message = proto::Feed {
+            message: Some(proto::feed::Message::MuteRequest(
+                proto::FeedMuteRequest{
+                    sender_id,
+                    mute,
+                }
+            )),
+        };
+
+        // send message
+        Rpc::send_message(proto_message.encode_to_vec(), super::rpc::proto::Modules::Feed.into(), "".to_string());
+    }
+
+    /// request the list of muted senders via rpc
+    fn request_mute_list() {
+        let proto_message = proto::Feed {
+            message: Some(proto::feed::Message::MuteListRequest(
+                proto::FeedMuteListRequest{}
+            )),
+        };
+
+        // send message
+        Rpc::send_message(proto_message.encode_to_vec(), super::rpc::proto::Modules::Feed.into(), "".to_string());
+    }
+
+    /// print a feed message
+    fn print_message(message: proto::FeedMessage) {
+        print!{"[{}] ", message.index};
+        println!("Time Sent - {}", message.time_sent);
+        println!("Timestamp Sent - {}", message.timestamp_sent);
+        println!("Time Received - {}", message.time_received);
+        println!("Timestamp Received - {}", message.timestamp_received);
+        println!("Message ID {}", message.message_id_base58);
+        println!("From {}", message.sender_id_base58);
+        println!("\t{}", message.content);
+        println!("");
+    }
+
     /// Process received RPC message
     /// 
     /// Decodes received protobuf encoded binary RPC message
@@ -100,15 +253,35 @@ impl Feed {
 
                         // print all messages in the feed list
                         for message in proto_feedlist.feed_message {
-                            print!{"[{}] ", message.index};
-                            println!("Time Sent - {}", message.time_sent);
-                            println!("Timestamp Sent - {}", message.timestamp_sent);
-                            println!("Time Received - {}", message.time_received);
-                            println!("Timestamp Received - {}", message.timestamp_received);
-                            println!("Message ID {}", message.message_id_base58);
-                            println!("From {}", message.sender_id_base58);
-                            println!("\t{}", message.content);
-                            println!("");
+                            Self::print_message(message);
+                        }
+                    }
+                    Some(proto::feed::Message::Page(proto_page)) => {
+                        // List header
+                        println!("====================================");
+                        println!("Feed Messages Page");
+                        println!("------------------------------------");
+
+                        // print all messages in the page
+                        for message in proto_page.feed_message {
+                            Self::print_message(message);
+                        }
+                        if proto_page.has_more {
+                            println!("more messages available");
+                        }
+                    }
+                    Some(proto::feed::Message::MuteList(mute_list)) => {
+                        println!("====================================");
+                        println!("Muted Feed Senders");
+                        println!("------------------------------------");
+                        for sender_id in mute_list.sender_ids {
+                            println!("{}", bs58::encode(sender_id).into_string());
+                        }
+                    }
+                    Some(proto::feed::Message::LookupResponse(lookup_response)) => {
+                        match lookup_response.feed_message {
+                            Some(message) if lookup_response.found => Self::print_message(message),
+                            _ => println!("Feed message {} not found", bs58::encode(lookup_response.message_id).into_string()),
                         }
                     }
                     _ => {
@@ -67,6 +67,39 @@ impl Group {
                     log::error!("group create command incorrectly formatted");
                 }
             }
+            // set message retention
+            cmd if cmd.starts_with("retention ") => {
+                let command_string = cmd.strip_prefix("retention ").unwrap().to_string();
+                let mut iter = command_string.split_whitespace();
+
+                if let Some(group_id_str) = iter.next() {
+                    match Self::uuid_string_to_bin(group_id_str.to_string()) {
+                        Ok(group_id) => {
+                            let mut values: Vec<u64> = Vec::new();
+                            for value_str in iter {
+                                match value_str.parse::<u64>() {
+                                    Ok(value) => values.push(value),
+                                    Err(_) => {
+                                        log::error!("retention value is not a valid number");
+                                        return;
+                                    }
+                                }
+                            }
+                            if values.len() != 3 {
+                                log::error!("group retention command incorrectly formatted");
+                                return;
+                            }
+                            Self::set_retention(group_id, values[0], values[1] as u32, values[2]);
+                        }
+                        Err(e) => {
+                            log::error!("{}", e);
+                            return;
+                        }
+                    }
+                } else {
+                    log::error!("group retention command incorrectly formatted");
+                }
+            }
             // group info
             cmd if cmd.starts_with("info ") => {
                 let command_string = cmd.strip_prefix("info ").unwrap().to_string();
@@ -276,6 +309,40 @@ impl Group {
         );
     }
 
+    /// set message retention of a group
+    fn set_retention(group_id: Vec<u8>, max_age: u64, max_count: u32, disappear_after: u64) {
+        // group retention send message
+        let proto_message = proto::Group {
+            message: Some(proto::group::Message::GroupRetentionRequest(
+                proto::GroupRetentionRequest {
+                    group_id,
+                    retention: Some(proto::GroupRetention {
+                        max_age,
+                        max_count,
+                        disappear_after,
+                    }),
+                },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            super::rpc::proto::Modules::Group.into(),
+            "".to_string(),
+        );
+    }
+
+    /// print message retention of a group
+    fn print_retention(retention: Option<proto::GroupRetention>) {
+        if let Some(retention) = retention {
+            println!(
+                "	retention: max age {}s, max count {}, disappear after {}s",
+                retention.max_age, retention.max_count, retention.disappear_after
+            );
+        }
+    }
+
     /// group info
     fn group_info(group_id: Vec<u8>) {
         // group info send message
@@ -494,6 +561,17 @@ impl Group {
                             println!("\terror: {}", result.message);
                         }
                     }
+                    Some(proto::group::Message::GroupRetentionResponse(retention_response)) => {
+                        let result = retention_response.result.unwrap();
+                        println!("====================================");
+                        println!("Group Retention status: {}", result.status);
+                        let group_id =
+                            uuid::Uuid::from_bytes(retention_response.group_id.try_into().unwrap());
+                        println!("\tid: {}", group_id.to_string());
+                        if !result.status {
+                            println!("\terror: {}", result.message);
+                        }
+                    }
                     Some(proto::group::Message::GroupInviteMemberResponse(
                         invite_group_response,
                     )) => {
@@ -545,6 +623,7 @@ impl Group {
                         println!("\tname: {}", group_info_response.group_name.clone());
                         println!("\tcreated_at: {}", group_info_response.created_at);
                         println!("\tmembers: {}", group_info_response.members.len());
+                        Self::print_retention(group_info_response.retention);
                     }
                     Some(proto::group::Message::GroupListResponse(group_list_response)) => {
                         // List groups
@@ -602,6 +681,7 @@ impl Group {
                                 }
                             }
                             println!("\trevision: {}", group.revision);
+                            Self::print_retention(group.retention);
                             println!("\tunread messages: {}", group.unread_messages);
                             println!("\tlast message:");
                             println!(
@@ -32,6 +32,10 @@ impl Router {
             cmd if cmd.starts_with("connections list") => {
                 Self::request_connections_list();
             },
+            // request loop detection statistics
+            cmd if cmd.starts_with("loops") => {
+                Self::request_loop_statistics();
+            },
             // unknown command
             _ => log::error!("unknown router command"),
         }
@@ -76,6 +80,19 @@ impl Router {
         Self::send_message(proto_message);
     }
 
+    /// create rpc request for loop detection statistics
+    fn request_loop_statistics() {
+        // create request message
+        let proto_message = proto::Router {
+            message: Some(proto::router::Message::LoopStatisticsRequest (
+                proto::LoopStatisticsRequest {}
+            )),
+        };
+
+        // send message
+        Self::send_message(proto_message);
+    }
+
     /// Encode and send protobuf message
     fn send_message(message: proto::Router) {
         // encode message
@@ -152,6 +169,19 @@ impl Router {
                         println!("Internet Connections");
                         Self::rpc_display_connections_list(proto_message.internet);
                     },
+                    Some(proto::router::Message::LoopStatistics(proto_message)) => {
+                        println!("");
+                        println!("Loop Detection Statistics");
+                        println!("forwarded messages: {}", proto_message.forwarded);
+                        println!("detected loops: {}", proto_message.loops_detected);
+                        if proto_message.loops_detected > 0 {
+                            println!("last loop at: {}", proto_message.last_loop_at);
+                            println!("last looping message: {}", bs58::encode(proto_message.last_loop_signature).into_string());
+                        }
+                        println!("poisoned routes received: {}", proto_message.routes_poisoned);
+                        println!("routes exceeding the maximal hop count: {}", proto_message.routes_hop_count_exceeded);
+                        println!("");
+                    },
                     _ => {
                         log::error!("unprocessable RPC router message");
                     },
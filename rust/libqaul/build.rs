@@ -26,6 +26,10 @@ fn main() {
         "MessageReceptionConfirmed",
         "#[derive(serde::Serialize, serde::Deserialize)]",
     );
+    prost_build.type_attribute(
+        "MessageReaction",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
 
     // make network messaging serializable
     // in order to save them in the data base
@@ -52,6 +56,10 @@ fn main() {
         "GroupMember",
         "#[derive(serde::Serialize, serde::Deserialize)]",
     );
+    prost_build.type_attribute(
+        "GroupRetention",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
 
     // make crypto message serializable
     // in order to save them in the data base
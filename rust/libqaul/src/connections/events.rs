@@ -74,12 +74,14 @@ pub fn ping_event(event: Event, module: ConnectionModule) {
             result: Result::Err(Failure::Timeout),
         } => {
             log::debug!("PingFailure::Timeout to {}", peer);
+            Neighbours::update_statistics(module, peer, false);
         }
         Event {
             peer,
             result: Result::Err(Failure::Other { error }),
         } => {
             log::debug!("PingFailure::Other {} error: {}", peer, error);
+            Neighbours::update_statistics(module, peer, false);
         }
         Event {
             peer,
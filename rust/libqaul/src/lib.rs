@@ -25,6 +25,9 @@ mod services;
 pub mod storage;
 pub mod utilities;
 
+#[cfg(feature = "simulator")]
+pub use router::simulator;
+
 use connections::{ble::Ble, internet::Internet, ConnectionModule, Connections};
 use node::Node;
 use router::{
@@ -32,6 +35,7 @@ use router::{
 };
 use rpc::sys::Sys;
 use rpc::Rpc;
+use services::dtn::Dtn;
 use services::messaging::Messaging;
 use services::Services;
 use utilities::filelogger::FileLogger;
@@ -78,6 +82,11 @@ enum EventType {
     RoutingTable(bool),
     Messaging(bool),
     Retransmit(bool),
+    DtnSweep(bool),
+    DtnDelivery(bool),
+    ChatRetention(bool),
+    ChatFileRequest(bool),
+    FeedPrune(bool),
 }
 
 /// initialize and start libqaul with a optional custom configuration options
@@ -279,6 +288,21 @@ pub async fn start(storage_path: String, def_config: Option<BTreeMap<String, Str
     // manage the message retransmit
     let mut retransmit_ticker = Ticker::new(Duration::from_millis(1000));
 
+    // remove expired DTN messages
+    let mut dtn_sweep_ticker = Ticker::new(Duration::from_millis(60000));
+
+    // deliver stored DTN messages to online receivers
+    let mut dtn_delivery_ticker = Ticker::new(Duration::from_millis(1000));
+
+    // apply the message retention settings of the groups
+    let mut chat_retention_ticker = Ticker::new(Duration::from_millis(60000));
+
+    // request missing chunks of incomplete files
+    let mut chat_file_request_ticker = Ticker::new(Duration::from_millis(10000));
+
+    // prune feed messages exceeding the feed retention
+    let mut feed_prune_ticker = Ticker::new(Duration::from_millis(600000));
+
     // set initialized flag
     INITIALIZED.set(true);
 
@@ -300,6 +324,11 @@ pub async fn start(storage_path: String, def_config: Option<BTreeMap<String, Str
             let routing_table_fut = routing_table_ticker.next().fuse();
             let messaging_fut = messaging_ticker.next().fuse();
             let retransmit_fut = retransmit_ticker.next().fuse();
+            let dtn_sweep_fut = dtn_sweep_ticker.next().fuse();
+            let dtn_delivery_fut = dtn_delivery_ticker.next().fuse();
+            let chat_retention_fut = chat_retention_ticker.next().fuse();
+            let chat_file_request_fut = chat_file_request_ticker.next().fuse();
+            let feed_prune_fut = feed_prune_ticker.next().fuse();
 
             // This Macro is shown wrong by Rust-Language-Server > 0.2.400
             // You need to downgrade to version 0.2.400 if this happens to you
@@ -318,6 +347,11 @@ pub async fn start(storage_path: String, def_config: Option<BTreeMap<String, Str
                 routing_table_fut,
                 messaging_fut,
                 retransmit_fut,
+                dtn_sweep_fut,
+                dtn_delivery_fut,
+                chat_retention_fut,
+                chat_file_request_fut,
+                feed_prune_fut,
             );
 
             select! {
@@ -413,6 +447,11 @@ pub async fn start(storage_path: String, def_config: Option<BTreeMap<String, Str
                 _routing_table_event = routing_table_fut => Some(EventType::RoutingTable(true)),
                 _messaging_event = messaging_fut => Some(EventType::Messaging(true)),
                 _retransmit_event = retransmit_fut => Some(EventType::Retransmit(true)),
+                _dtn_sweep_event = dtn_sweep_fut => Some(EventType::DtnSweep(true)),
+                _dtn_delivery_event = dtn_delivery_fut => Some(EventType::DtnDelivery(true)),
+                _chat_retention_event = chat_retention_fut => Some(EventType::ChatRetention(true)),
+                _chat_file_request_event = chat_file_request_fut => Some(EventType::ChatFileRequest(true)),
+                _feed_prune_event = feed_prune_fut => Some(EventType::FeedPrune(true)),
             }
         };
 
@@ -701,6 +740,26 @@ pub async fn start(storage_path: String, def_config: Option<BTreeMap<String, Str
                 EventType::Retransmit(_) => {
                     services::messaging::retransmit::MessagingRetransmit::process();
                 }
+                EventType::DtnSweep(_) => {
+                    // remove expired DTN messages
+                    Dtn::sweep_expired();
+                }
+                EventType::DtnDelivery(_) => {
+                    // deliver stored DTN messages to online receivers
+                    Dtn::deliver_stored_messages();
+                }
+                EventType::ChatRetention(_) => {
+                    // delete chat messages exceeding the group retention
+                    services::chat::Chat::retention_sweep();
+                }
+                EventType::ChatFileRequest(_) => {
+                    // request missing chunks of incomplete files
+                    services::chat::ChatFile::request_missing_chunks();
+                }
+                EventType::FeedPrune(_) => {
+                    // delete feed messages exceeding the feed retention
+                    services::feed::Feed::prune();
+                }
             }
         }
     }
@@ -103,6 +103,7 @@ impl UserAccounts {
                 id: id.to_string(),
                 keys: keys_config,
                 storage: configuration::StorageOptions::default(),
+                chat: configuration::ChatOptions::default(),
             });
         }
         Configuration::save();
@@ -143,6 +144,12 @@ impl UserAccounts {
         users.users.len()
     }
 
+    /// Return the ids of all user accounts on this node.
+    pub fn get_ids() -> Vec<PeerId> {
+        let accounts = USERACCOUNTS.get().read().unwrap();
+        accounts.users.iter().map(|user| user.id).collect()
+    }
+
     /// Return the default user.
     /// The first registered user account is returned.
     pub fn get_default_user() -> Option<UserAccount> {
@@ -14,28 +14,27 @@
 
 use libp2p::PeerId;
 use prost::Message;
-use state::Storage;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
 use super::proto;
+use super::Router;
 use crate::connections::ConnectionModule;
 use crate::node;
 use crate::router::router_net_proto;
 use crate::router::{
-    neighbours::Neighbours,
-    table::{RoutingConnectionEntry, RoutingTable, RoutingUserEntry},
+    instance::RouterInstance,
+    link_metric::{self, ETX_SCALE},
+    table::{RoutingConnectionEntry, RoutingTable, RoutingUserEntry, HOP_COUNT_UNREACHABLE},
 };
 use crate::rpc::Rpc;
+use crate::storage::configuration::RoutingOptions;
 use crate::utilities::qaul_id::QaulId;
-use crate::utilities::timestamp::Timestamp;
 
-/// Mutable module state
-/// Tables with all stats for each connection module
-static LOCAL: Storage<RwLock<RoutingTable>> = Storage::new();
-static INTERNET: Storage<RwLock<ConnectionTable>> = Storage::new();
-static LAN: Storage<RwLock<ConnectionTable>> = Storage::new();
+/// maximal number of alternative routes per user
+/// and connection module in the routing table
+const ROUTES_PER_MODULE: usize = 3;
 
 /// Connection entry for UserEntry
 struct NeighbourEntry {
@@ -45,6 +44,10 @@ struct NeighbourEntry {
     rtt: u32,
     /// hop count
     hc: u8,
+    /// expected transmission count
+    etx: u32,
+    /// bandwidth of the slowest link in kbit/s
+    bw: u32,
     /// link quality
     lq: u32,
     /// time when the node was last updated
@@ -72,56 +75,211 @@ struct UserEntry {
 /// Connection table contains a hashed map of all reachable users.
 /// The connectivity updates from each neighbour are collected here.
 /// Out of this information the global routing table is generated.
+///
+/// The connection tables are part of the router instance.
 pub struct ConnectionTable {
     table: HashMap<Vec<u8>, UserEntry>,
 }
 
 impl ConnectionTable {
+    /// Create an empty connection table
+    pub fn new() -> ConnectionTable {
+        ConnectionTable {
+            table: HashMap::new(),
+        }
+    }
+
     /// Initialize connection tables
-    /// Creates a table for each ConnectionModule
-    /// and saves it to state.
+    ///
+    /// The connection tables per connection module are
+    /// part of the router instance.
+    /// Fills the state with the locally registered users.
     pub fn init() {
-        {
-            let internet = ConnectionTable {
-                table: HashMap::new(),
-            };
-            INTERNET.set(RwLock::new(internet));
+        for user in node::user_accounts::UserAccounts::get_user_info() {
+            Self::add_local_user(user.id);
+        }
+    }
 
-            let lan = ConnectionTable {
-                table: HashMap::new(),
-            };
-            LAN.set(RwLock::new(lan));
+    /// add a new local user to state
+    pub fn add_local_user(user_id: PeerId) {
+        Router::instance().add_local_user(user_id);
+    }
 
-            let local = RoutingTable {
-                table: HashMap::new(),
-            };
-            LOCAL.set(RwLock::new(local));
+    /// Create a routing table and set it to active routing table
+    pub fn create_routing_table() {
+        Router::instance().create_routing_table();
+    }
+
+    /// find the best entries
+    /// and remove all old entries
+    ///
+    /// Returns up to `ROUTES_PER_MODULE` valid entries,
+    /// ordered by their link quality, the best entry first.
+    fn find_best_connections(
+        user: &mut UserEntry,
+        config: &RoutingOptions,
+        now: u64,
+    ) -> (bool, Vec<NeighbourEntry>) {
+        // initialize helper variables
+        let mut expired_connections: Vec<PeerId> = Vec::new();
+        let mut return_entries: Vec<NeighbourEntry> = Vec::new();
+
+        //remove user after 5min from last pgid updated
+        //config.maintain_period_limit is seconds unit, need to convert into mili seconds
+        if now - user.pgid_update >= (config.maintain_period_limit * 1000) {
+            return (true, return_entries);
         }
 
-        // create filled state for locally registered users
-        {
-            for user in node::user_accounts::UserAccounts::get_user_info() {
-                Self::add_local_user(user.id);
+        // loop through all connections
+        for (key, value) in &user.connections {
+            let mut expired = true;
+
+            // check if entry is expired
+            // entry expires after 20 seconds, unit is mili seconds
+            //if now - value.last_update < (20 * 1000 * (value.hc as u64)){
+            if now - value.last_update
+                //< (2 * (config.sending_table_period * 1000) * (value.hc as u64))
+                < (config.sending_table_period * 1000 * (value.hc as u64 + 1))
+            {
+                expired = false;
+
+                return_entries.push(NeighbourEntry {
+                    id: value.id.clone(),
+                    rtt: value.rtt,
+                    hc: value.hc,
+                    etx: value.etx,
+                    bw: value.bw,
+                    lq: value.lq,
+                    last_update: value.last_update.clone(),
+                });
             }
+
+            // put connection for removal if expired
+            if expired {
+                log::info!(
+                    "expired entry={},  hc={}",
+                    (now - value.last_update),
+                    value.hc
+                );
+                expired_connections.push(key.clone());
+            }
+        }
+
+        // remove expired connections
+        for node_id in expired_connections {
+            user.connections.remove(&node_id);
+        }
+
+        // keep the best entries
+        return_entries.sort_by(|a, b| a.lq.cmp(&b.lq));
+        return_entries.truncate(ROUTES_PER_MODULE);
+
+        (false, return_entries)
+    }
+
+    /// send protobuf RPC connections list
+    pub fn rpc_send_connections_list() {
+        // create connections list
+        let connections_list = proto::ConnectionsList {
+            lan: Self::rpc_create_connection_module_list(ConnectionModule::Lan),
+            internet: Self::rpc_create_connection_module_list(ConnectionModule::Internet),
+            ble: Self::rpc_create_connection_module_list(ConnectionModule::Ble),
+            local: Self::rpc_create_connection_module_list(ConnectionModule::Local),
+        };
+
+        // create rpc connections list protobuf message
+        let proto_message = proto::Router {
+            message: Some(proto::router::Message::ConnectionsList(connections_list)),
+        };
+
+        // encode message
+        let mut buf = Vec::with_capacity(proto_message.encoded_len());
+        proto_message
+            .encode(&mut buf)
+            .expect("Vec<u8> provides capacity as needed");
+
+        // send message
+        Rpc::send_message(
+            buf,
+            crate::rpc::proto::Modules::Router.into(),
+            "".to_string(),
+            Vec::new(),
+        );
+    }
+
+    /// create rpc connection module list
+    fn rpc_create_connection_module_list(
+        conn: ConnectionModule,
+    ) -> Vec<proto::ConnectionsUserEntry> {
+        // create entry vector
+        let mut connections_list: Vec<proto::ConnectionsUserEntry> = Vec::new();
+
+        // request connection table from state
+        let router = Router::instance();
+        let connection_table;
+        match router.connection_table(&conn) {
+            Some(table) => connection_table = table.read().unwrap(),
+            None => return connections_list,
+        }
+
+        // loop through all table entries per user
+        for (id, entry) in &connection_table.table {
+            // create user entry
+            let mut user_entry = proto::ConnectionsUserEntry {
+                user_id: id.to_owned(),
+                connections: Vec::new(),
+            };
+
+            // loop through all neighbour entries of a user entry
+            for (id, neighbour) in &entry.connections {
+                // add connection
+                user_entry.connections.push(proto::ConnectionEntry {
+                    rtt: neighbour.rtt,
+                    hop_count: neighbour.hc as u32,
+                    via: id.to_bytes(),
+                });
+            }
+
+            // add user entry to list
+            connections_list.push(user_entry);
+        }
+
+        // return list
+        connections_list
+    }
+}
+
+/// Connection tables of a router instance
+impl RouterInstance {
+    /// get the connection table of a connection module
+    fn connection_table(&self, module: &ConnectionModule) -> Option<&RwLock<ConnectionTable>> {
+        match module {
+            ConnectionModule::Internet => Some(&self.internet),
+            ConnectionModule::Lan => Some(&self.lan),
+            ConnectionModule::Ble => None,
+            ConnectionModule::Local => None,
+            ConnectionModule::None => None,
         }
     }
 
     /// add a new local user to state
-    pub fn add_local_user(user_id: PeerId) {
-        let node_id = node::Node::get_id();
-        let mut routing_table = LOCAL.get().write().unwrap();
+    pub fn add_local_user(&self, user_id: PeerId) {
+        let node_id = self.node_id;
+        let mut routing_table = self.local.write().unwrap();
 
         let mut connections = Vec::new();
 
         // routing table creating is done every 1 seconds.
         // by considerate neighbour sending is done before creating routing Table.
         // we set local user online time forward 3 seconds
-        let now_ts = Timestamp::get_timestamp() + 3000;
+        let now_ts = self.now() + 3000;
         connections.push(RoutingConnectionEntry {
             module: ConnectionModule::Local,
             node: node_id,
             rtt: 0,
             hc: 0,
+            etx: 0,
+            bw: 0,
             lq: 0,
             last_update: now_ts,
         });
@@ -142,6 +300,7 @@ impl ConnectionTable {
     /// process received routing info table
     /// enter it into all modules where we are connected to
     pub fn process_received_routing_info(
+        &self,
         neighbour_id: PeerId,
         info: &Vec<router_net_proto::RoutingInfoEntry>,
     ) {
@@ -152,22 +311,93 @@ impl ConnectionTable {
         //     log::trace!("receive_routing_info user={}, hc={}, propg_id={}", userid, inf.hc[0], inf.pgid);
         // }
 
+        // get the maximal hop count
+        let hop_count_max = self.configuration.hop_count_max;
+
+        // sort out unreachable routes
+        let mut routes: Vec<router_net_proto::RoutingInfoEntry> = Vec::new();
+        let mut poisoned: Vec<Vec<u8>> = Vec::new();
+        let mut loop_detector = self.loops.write().unwrap();
+        for entry in info {
+            if entry.hc.len() == 0 {
+                continue;
+            }
+
+            // the neighbour routes this user via us
+            if entry.hc[0] == HOP_COUNT_UNREACHABLE {
+                loop_detector.count_poisoned_route();
+                poisoned.push(entry.user.clone());
+                continue;
+            }
+
+            // the route exceeds the maximal hop count
+            if entry.hc[0] as u64 + 1 > hop_count_max {
+                loop_detector.count_hop_count_exceeded();
+                poisoned.push(entry.user.clone());
+                continue;
+            }
+
+            routes.push(entry.to_owned());
+        }
+        drop(loop_detector);
+
+        // remove the unreachable routes via this neighbour
+        if poisoned.len() > 0 {
+            self.process_withdrawn_routes(neighbour_id, &poisoned);
+        }
+        let info = &routes;
+
         // try Lan module
-        if let Some(rtt) = Neighbours::get_rtt(&neighbour_id, &ConnectionModule::Lan) {
-            Self::fill_received_routing_info(ConnectionModule::Lan, neighbour_id, rtt, info);
+        if let Some(rtt) = self.get_neighbour_rtt(&neighbour_id, &ConnectionModule::Lan) {
+            let etx = self
+                .get_neighbour_etx(&neighbour_id, &ConnectionModule::Lan)
+                .unwrap_or(ETX_SCALE);
+            self.fill_received_routing_info(ConnectionModule::Lan, neighbour_id, rtt, etx, info);
         }
 
         // try Internet module
-        if let Some(rtt) = Neighbours::get_rtt(&neighbour_id, &ConnectionModule::Internet) {
-            Self::fill_received_routing_info(ConnectionModule::Internet, neighbour_id, rtt, info);
+        if let Some(rtt) = self.get_neighbour_rtt(&neighbour_id, &ConnectionModule::Internet) {
+            let etx = self
+                .get_neighbour_etx(&neighbour_id, &ConnectionModule::Internet)
+                .unwrap_or(ETX_SCALE);
+            self.fill_received_routing_info(
+                ConnectionModule::Internet,
+                neighbour_id,
+                rtt,
+                etx,
+                info,
+            );
+        }
+    }
+
+    /// process withdrawn routes
+    ///
+    /// The neighbour can't reach these users anymore.
+    /// The connections via this neighbour are removed
+    /// from all modules.
+    pub fn process_withdrawn_routes(&self, neighbour_id: PeerId, user_q8ids: &Vec<Vec<u8>>) {
+        for module in [ConnectionModule::Lan, ConnectionModule::Internet] {
+            let mut connection_table;
+            match self.connection_table(&module) {
+                Some(table) => connection_table = table.write().unwrap(),
+                None => continue,
+            }
+
+            for user_q8id in user_q8ids {
+                if let Some(user) = connection_table.table.get_mut(user_q8id) {
+                    user.connections.remove(&neighbour_id);
+                }
+            }
         }
     }
 
     /// populate connection table with incoming routing information
     fn fill_received_routing_info(
+        &self,
         conn: ConnectionModule,
         neighbour_id: PeerId,
         rtt: u32,
+        etx: u32,
         info: &Vec<router_net_proto::RoutingInfoEntry>,
     ) {
         log::trace!("fill_received_routing_info {}", info.len());
@@ -182,43 +412,46 @@ impl ConnectionTable {
                 return;
             }
 
+            // calculate expected transmission count
+            // nodes not sending the ETX of their route
+            // are treated as if their route had no losses
+            let route_etx;
+            if entry.etx > 0 || entry.hc[0] == 0 {
+                route_etx = entry.etx.saturating_add(etx);
+            } else {
+                route_etx = (entry.hc[0] as u32 * ETX_SCALE).saturating_add(etx);
+            }
+
+            // calculate the bandwidth of the slowest link
+            let route_bw =
+                link_metric::route_bandwidth(entry.bw, link_metric::module_bandwidth(&conn));
+
             // fill structure
             let neighbour = NeighbourEntry {
                 id: neighbour_id,
                 rtt: entry.rtt + rtt,
                 hc,
-                lq: Self::calculate_linkquality(entry.rtt + rtt, hc),
-                last_update: Timestamp::get_timestamp(),
+                etx: route_etx,
+                bw: route_bw,
+                lq: self.calculate_linkquality(entry.rtt + rtt, hc, route_etx, route_bw),
+                last_update: self.now(),
             };
 
             // add it to state
-            Self::add_connection(entry.user.clone(), entry.pgid, neighbour, conn.clone());
+            self.add_connection(entry.user.clone(), entry.pgid, neighbour, conn.clone());
         }
     }
 
-    /// calculate link quality
-    ///
-    /// returns the calculated link quality for a connection.
-    ///
-    /// The link quality is currently calculated using the
-    /// round trip time (rtt) and adding a penalty for each hop
-    /// according hop count (hc).
-    ///
-    /// The smaller the value is better is the link quality.
-    pub fn calculate_linkquality(rtt: u32, hc: u8) -> u32 {
-        // get the router configuration
-        let config = super::Router::get_configuration();
-
-        // calculate link quality
-        // `hop_count_penalty` is seconds unit, thus it must be converted micro seconds
-        let lq = rtt + (hc as u32 * (config.hop_count_penalty as u32) * 1000_000);
-
-        // return link quality
-        lq
+    /// calculate the link quality of a route
+    /// with the link metric configured for this router
+    pub fn calculate_linkquality(&self, rtt: u32, hc: u8, etx: u32, bw: u32) -> u32 {
+        let metric = self.configuration.link_metric.metric(&self.configuration);
+        metric.link_quality(rtt, hc, etx, bw)
     }
 
     /// add connection to local state
     fn add_connection(
+        &self,
         user_q8id: Vec<u8>,
         pgid: u32,
         connection: NeighbourEntry,
@@ -226,15 +459,12 @@ impl ConnectionTable {
     ) {
         // get access to the connection table
         let mut connection_table;
-        match module {
-            ConnectionModule::Internet => connection_table = INTERNET.get().write().unwrap(),
-            ConnectionModule::Lan => connection_table = LAN.get().write().unwrap(),
-            ConnectionModule::Ble => return,
-            ConnectionModule::Local => return,
-            ConnectionModule::None => return,
+        match self.connection_table(&module) {
+            Some(table) => connection_table = table.write().unwrap(),
+            None => return,
         }
 
-        let now_ts = Timestamp::get_timestamp();
+        let now_ts = self.now();
         // check if user already exists
         if let Some(user) = connection_table.table.get_mut(&user_q8id) {
             if connection.hc == 1 || pgid > user.pgid {
@@ -292,19 +522,19 @@ impl ConnectionTable {
     }
 
     /// update propagation id for local users
-    pub fn update_propagation_id(propagation_id: u32) {
+    pub fn update_propagation_id(&self, propagation_id: u32) {
         //update local user's propagation id
-        let mut local = LOCAL.get().write().unwrap();
+        let mut local = self.local.write().unwrap();
         for (_user_id, user) in local.table.iter_mut() {
             user.pgid = propagation_id;
             // QUESTION: is this of any use?
-            user.pgid_update = Timestamp::get_timestamp();
-            user.connections.get_mut(0).unwrap().last_update = Timestamp::get_timestamp();
+            user.pgid_update = self.now();
+            user.connections.get_mut(0).unwrap().last_update = self.now();
         }
     }
 
     /// Create a routing table and set it to active routing table
-    pub fn create_routing_table() {
+    pub fn create_routing_table(&self) {
         // create a new table
         let mut table = RoutingTable {
             table: HashMap::new(),
@@ -313,23 +543,23 @@ impl ConnectionTable {
         // set static routes for local users
         // create them first, for that they are always routed to ourselves
         {
-            table = Self::local_routes_to_intermediary_table(table);
+            table = self.local_routes_to_intermediary_table(table);
         }
 
         // calculate from lan module
-        table = Self::calculate_intermediary_table(table, ConnectionModule::Lan);
+        table = self.calculate_intermediary_table(table, ConnectionModule::Lan);
 
         // calculate from internet module
-        table = Self::calculate_intermediary_table(table, ConnectionModule::Internet);
+        table = self.calculate_intermediary_table(table, ConnectionModule::Internet);
 
         // set table as new active routing table
-        RoutingTable::set(table);
+        self.set_routing_table(table);
     }
 
     /// insert local routes into routing table
-    fn local_routes_to_intermediary_table(mut table: RoutingTable) -> RoutingTable {
+    fn local_routes_to_intermediary_table(&self, mut table: RoutingTable) -> RoutingTable {
         // get local routes
-        let local = LOCAL.get().read().unwrap();
+        let local = self.local.read().unwrap();
 
         // fill it into routing table
         for (user_id, user) in &local.table {
@@ -341,6 +571,7 @@ impl ConnectionTable {
 
     /// calculate a routing table for a module
     fn calculate_intermediary_table(
+        &self,
         mut table: RoutingTable,
         conn: ConnectionModule,
     ) -> RoutingTable {
@@ -349,38 +580,38 @@ impl ConnectionTable {
 
         // get connections table
         let mut connection_table;
-        match conn.clone() {
-            ConnectionModule::Internet => connection_table = INTERNET.get().write().unwrap(),
-            ConnectionModule::Lan => connection_table = LAN.get().write().unwrap(),
-            ConnectionModule::Ble => return table,
-            ConnectionModule::Local => return table,
-            ConnectionModule::None => return table,
+        match self.connection_table(&conn) {
+            Some(state) => connection_table = state.write().unwrap(),
+            None => return table,
         }
 
+        let now = self.now();
+
         // iterate over connection table
         for (user_id, user) in connection_table.table.iter_mut() {
-            let (b_expired_pgid, connection_entry) = Self::find_best_connection(user);
+            let (b_expired_pgid, connection_entries) =
+                ConnectionTable::find_best_connections(user, &self.configuration, now);
             if b_expired_pgid == false {
-                if let Some(connection) = connection_entry {
-                    // fill entry into routing table
-                    let routing_connection_entry = RoutingConnectionEntry {
-                        module: conn.clone(),
-                        node: connection.id,
-                        rtt: connection.rtt,
-                        hc: connection.hc,
-                        lq: connection.lq,
-                        last_update: connection.last_update,
-                    };
+                if connection_entries.len() > 0 {
+                    // fill entries into routing table
+                    let mut connections = Vec::new();
+                    for connection in connection_entries {
+                        connections.push(RoutingConnectionEntry {
+                            module: conn.clone(),
+                            node: connection.id,
+                            rtt: connection.rtt,
+                            hc: connection.hc,
+                            etx: connection.etx,
+                            bw: connection.bw,
+                            lq: connection.lq,
+                            last_update: connection.last_update,
+                        });
+                    }
 
                     // check if user entry already exists hashmap
                     if let Some(routing_user_entry) = table.table.get_mut(&user.id) {
-                        routing_user_entry
-                            .connections
-                            .push(routing_connection_entry);
+                        routing_user_entry.connections.append(&mut connections);
                     } else {
-                        let mut connections = Vec::new();
-                        connections.push(routing_connection_entry);
-
                         let routing_user_entry = RoutingUserEntry {
                             id: user_id.to_owned(),
                             pgid: user.pgid,
@@ -416,146 +647,4 @@ impl ConnectionTable {
 
         table
     }
-
-    /// find best entry
-    /// and remove all old entries
-    fn find_best_connection(user: &mut UserEntry) -> (bool, Option<NeighbourEntry>) {
-        // initialize helper variables
-        let mut expired_connections: Vec<PeerId> = Vec::new();
-        let mut return_entry = None;
-        let mut lq = u32::MAX;
-
-        //remove user after 5min from last pgid updated
-        //config.maintain_period_limit is seconds unit, need to convert into mili seconds
-        let config = super::Router::get_configuration();
-        if Timestamp::get_timestamp() - user.pgid_update >= (config.maintain_period_limit * 1000) {
-            return (true, None);
-        }
-
-        // create return value
-        {
-            let mut entry_found = None;
-
-            // loop through all connections
-            for (key, value) in &user.connections {
-                let mut expired = true;
-
-                // check if entry is expired
-                // entry expires after 20 seconds, unit is mili seconds
-                let now = Timestamp::get_timestamp();
-                //if now - value.last_update < (20 * 1000 * (value.hc as u64)){
-                if now - value.last_update
-                    //< (2 * (config.sending_table_period * 1000) * (value.hc as u64))
-                    < (config.sending_table_period * 1000 * (value.hc as u64 + 1))
-                {
-                    expired = false;
-
-                    if value.lq < lq {
-                        lq = value.lq;
-                        entry_found = Some(value);
-                    }
-                }
-
-                // put connection for removal if expired
-                if expired {
-                    log::info!(
-                        "expired entry={},  hc={}",
-                        (now - value.last_update),
-                        value.hc
-                    );
-                    expired_connections.push(key.clone());
-                }
-            }
-
-            if let Some(entry) = entry_found {
-                return_entry = Some(NeighbourEntry {
-                    id: entry.id.clone(),
-                    rtt: entry.rtt,
-                    hc: entry.hc,
-                    lq: entry.lq,
-                    last_update: entry.last_update.clone(),
-                })
-            }
-        }
-
-        // remove expired connections
-        for node_id in expired_connections {
-            user.connections.remove(&node_id);
-        }
-
-        (false, return_entry)
-    }
-
-    /// send protobuf RPC connections list
-    pub fn rpc_send_connections_list() {
-        // create connections list
-        let connections_list = proto::ConnectionsList {
-            lan: Self::rpc_create_connection_module_list(ConnectionModule::Lan),
-            internet: Self::rpc_create_connection_module_list(ConnectionModule::Internet),
-            ble: Self::rpc_create_connection_module_list(ConnectionModule::Ble),
-            local: Self::rpc_create_connection_module_list(ConnectionModule::Local),
-        };
-
-        // create rpc connections list protobuf message
-        let proto_message = proto::Router {
-            message: Some(proto::router::Message::ConnectionsList(connections_list)),
-        };
-
-        // encode message
-        let mut buf = Vec::with_capacity(proto_message.encoded_len());
-        proto_message
-            .encode(&mut buf)
-            .expect("Vec<u8> provides capacity as needed");
-
-        // send message
-        Rpc::send_message(
-            buf,
-            crate::rpc::proto::Modules::Router.into(),
-            "".to_string(),
-            Vec::new(),
-        );
-    }
-
-    /// create rpc connection module list
-    fn rpc_create_connection_module_list(
-        conn: ConnectionModule,
-    ) -> Vec<proto::ConnectionsUserEntry> {
-        // create entry vector
-        let mut connections_list: Vec<proto::ConnectionsUserEntry> = Vec::new();
-
-        // request connection table from state
-        let connection_table;
-        match conn {
-            ConnectionModule::Lan => connection_table = LAN.get().read().unwrap(),
-            ConnectionModule::Internet => connection_table = INTERNET.get().read().unwrap(),
-            ConnectionModule::Ble => return connections_list,
-            ConnectionModule::Local => return connections_list,
-            ConnectionModule::None => return connections_list,
-        }
-
-        // loop through all table entries per user
-        for (id, entry) in &connection_table.table {
-            // create user entry
-            let mut user_entry = proto::ConnectionsUserEntry {
-                user_id: id.to_owned(),
-                connections: Vec::new(),
-            };
-
-            // loop through all neighbour entries of a user entry
-            for (id, neighbour) in &entry.connections {
-                // add connection
-                user_entry.connections.push(proto::ConnectionEntry {
-                    rtt: neighbour.rtt,
-                    hop_count: neighbour.hc as u32,
-                    via: id.to_bytes(),
-                });
-            }
-
-            // add user entry to list
-            connections_list.push(user_entry);
-        }
-
-        // return list
-        connections_list
-    }
 }
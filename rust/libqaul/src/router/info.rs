@@ -11,29 +11,37 @@
 //!
 //! It is packaging and unpacking the routing information messages.
 //!
-//! It has an own list of neighbour nodes with a timer on each
-//! of them to make sure, each node is only sent the routing
-//! table over one of the interfaces.
+//! Every router instance has an own scheduler with a list of
+//! neighbour nodes and a timer on each of them to make sure,
+//! each node is only sent the routing table over one of the
+//! interfaces.
 //! The timer needs to be polled manually.
+//!
+//! ## Snapshots and Delta Updates
+//!
+//! The routing information sent to a neighbour is sequence numbered.
+//! A full snapshot of all routes is sent periodically.
+//! In between only the changes to the routes the neighbour
+//! already knows are sent as delta updates: new routes,
+//! changed fields of known routes and withdrawn routes.
+//!
+//! If a neighbour detects a gap in the sequence numbers,
+//! it requests a full snapshot with its next routing information.
+//!
+//! Neighbours that do not support the delta updates yet,
+//! are sent the full routing information in every message.
 
 use crate::utilities::qaul_id::QaulId;
-use libp2p::PeerId;
+use libp2p::{identity::Keypair, PeerId};
 use prost::Message;
 use qaul_info::QaulInfoReceived;
-use state::Storage;
-use std::{
-    collections::HashMap,
-    sync::RwLock,
-    time::{Duration, SystemTime},
-};
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     connections::ConnectionModule,
     node::Node,
-    router::{
-        connections::ConnectionTable, neighbours::Neighbours, router_net_proto,
-        table::RoutingTable, users::Users,
-    },
+    router::{instance::RouterInstance, router_net_proto, users::Users, Router},
+    storage::configuration::RoutingOptions,
     utilities::timestamp::Timestamp,
 };
 
@@ -44,10 +52,21 @@ use crate::services::feed::Feed;
 use crate::router::user_requester::UserRequester;
 use crate::router::user_requester::UserResponser;
 
-/// mutable state of Neighbours table per ConnectionModule
-static SCHEDULER: Storage<RwLock<Scheduler>> = Storage::new();
+/// version of the routing information exchange
+///
+/// 0: full routing information in every message
+/// 1: sequence numbered snapshots and delta updates
+const ROUTING_INFO_VERSION: u32 = 1;
 
-/// global scheduler state
+/// minimal change of the round trip time in percent,
+/// to be sent in a delta update
+const RTT_CHANGE_MIN: u64 = 10;
+
+/// interval in milli seconds in which the
+/// propagation id of the local users is increased
+pub const PROPAGATION_PERIOD: u64 = 10000;
+
+/// routing information scheduler of a router instance
 #[derive(Clone, Debug)]
 pub struct Scheduler {
     /// list of all neighbours, to schedule sending of
@@ -56,9 +75,9 @@ pub struct Scheduler {
     /// modules, the table is only sent on one of them.
     neighbours: HashMap<PeerId, SchedulerEntry>,
 
-    /// interval in which updated routing information
-    /// shall be sent to the neighbouring nodes.
-    interval: Duration,
+    /// interval in milli seconds in which updated routing
+    /// information shall be sent to the neighbouring nodes.
+    interval: u64,
 
     /// propagation ID
     ///
@@ -70,15 +89,74 @@ pub struct Scheduler {
     ///
     /// timestamp of the last propagation update
     propagation_timestamp: u64,
+
+    /// interval in milli seconds in which a full snapshot
+    /// of the routing information is sent to the neighbours.
+    snapshot_interval: u64,
+
+    /// routing information received from the neighbours
+    received: HashMap<PeerId, ReceivedInfo>,
+}
+
+impl Scheduler {
+    /// create a new scheduler with the sending and
+    /// snapshot intervals of the routing configuration
+    pub fn new(configuration: &RoutingOptions, now: u64) -> Scheduler {
+        Scheduler {
+            neighbours: HashMap::new(),
+            interval: configuration.sending_table_period.saturating_mul(1000),
+            propagation_id: 0,
+            propagation_timestamp: now,
+            snapshot_interval: configuration.snapshot_period.saturating_mul(1000),
+            received: HashMap::new(),
+        }
+    }
 }
 
 /// An entry for the scheduler neighbour list
 /// that contains the time stamp
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug)]
 struct SchedulerEntry {
-    /// time of the last send
-    timestamp: SystemTime,
+    /// time of the last send in milli seconds
+    timestamp: u64,
     is_first: bool,
+    /// sequence number of the last routing information sent
+    sequence: u32,
+    /// time of the last snapshot sent
+    snapshot_at: u64,
+    /// the neighbour requested a full snapshot
+    snapshot_requested: bool,
+    /// the routes the neighbour received from us
+    advertised: HashMap<Vec<u8>, router_net_proto::RoutingInfoEntry>,
+}
+
+/// Routing information received from a neighbour
+#[derive(Clone, Debug)]
+struct ReceivedInfo {
+    /// version of the routing information exchange
+    /// the neighbour supports
+    version: u32,
+    /// a snapshot was received and the
+    /// delta updates can be applied
+    synchronized: bool,
+    /// sequence number of the last routing information received
+    sequence: u32,
+    /// request a full snapshot from the neighbour
+    resync: bool,
+    /// all routes the neighbour sent us
+    routes: HashMap<Vec<u8>, router_net_proto::RoutingInfoEntry>,
+}
+
+/// Routing information update for a neighbour
+struct RoutingUpdate {
+    /// sequence number
+    sequence: u32,
+    /// this update is a full snapshot
+    snapshot: bool,
+    /// all routes in a snapshot, new routes in a delta update
+    routes: router_net_proto::RoutingInfoTable,
+    /// changed and withdrawn routes in a delta update
+    delta: Option<router_net_proto::RoutingInfoDelta>,
 }
 
 /// RouterInfo Module
@@ -86,149 +164,97 @@ struct SchedulerEntry {
 pub struct RouterInfo {}
 
 impl RouterInfo {
-    /// Initialize router info and scheduler
-    /// with the interval in seconds that the
-    /// routing information shall be sent
-    /// to neighbours.
-    pub fn init(interval_seconds: u64) {
-        // neighbours list for routing info scheduler
-        let scheduler = Scheduler {
-            neighbours: HashMap::new(),
-            interval: Duration::from_secs(interval_seconds),
-            propagation_id: 0,
-            propagation_timestamp: Timestamp::get_timestamp(),
-        };
-        SCHEDULER.set(RwLock::new(scheduler));
-    }
-
-    /// This loops over all neighbours
-    /// and checks if there is any timeout.
-    /// If it finds a timeout it returns the node id
-    /// to send a routing information to.
+    /// This checks the scheduler of the router instance
+    /// of this node.
+    /// If a neighbour is due, it returns the node id
+    /// and the encoded routing information to send to it.
     pub fn check_scheduler() -> Option<(PeerId, ConnectionModule, Vec<u8>)> {
-        let mut found_neighbour: Option<PeerId> = None;
-        let mut neighbour_last_sent: u64 = 0;
-        let mut neighbour_is_first: bool = false;
-        let mut propagation_id: u32;
-        let mut propagation_timestamp: u64;
-
-        {
-            // get state for reading
-            let scheduler = SCHEDULER.get().read().unwrap();
-
-            // loop over all neighbours
-            for (id, ctx) in scheduler.neighbours.iter() {
-                if ctx.timestamp + scheduler.interval < SystemTime::now() {
-                    found_neighbour = Some(id.clone());
-                    neighbour_last_sent = Timestamp::get_timestamp_by(&ctx.timestamp);
-                    neighbour_is_first = ctx.is_first;
-                    break;
-                }
-            }
-
-            // get propagation information
-            propagation_id = scheduler.propagation_id;
-            propagation_timestamp = scheduler.propagation_timestamp;
-        }
-
-        // check if we have to update the propagation ID
-        if Timestamp::get_timestamp() >= propagation_timestamp + 10 * 1000 {
-            propagation_id += 1;
-            propagation_timestamp = Timestamp::get_timestamp();
-
-            // get scheduler for writing
-            let mut scheduler = SCHEDULER.get().write().unwrap();
-            scheduler.propagation_id = propagation_id;
-            scheduler.propagation_timestamp = propagation_timestamp;
-
-            // update propagation ID
-            super::connections::ConnectionTable::update_propagation_id(propagation_id);
-        }
-
-        // process finding
-        if let Some(node_id) = found_neighbour {
-            // Check whether this node is
-            // still connected and over which connection module
-            // we can approach it.
-            let module = Neighbours::is_neighbour(&node_id);
-
-            // get SCHEDULER for writing
-            let mut scheduler = SCHEDULER.get().write().unwrap();
-
-            if module == ConnectionModule::None {
-                log::debug!("node is not a neighbour anymore: {:?}", node_id);
-                // delete this entry
-                scheduler.neighbours.remove(&node_id);
-            } else {
-                // update timer
-                if let Some(entry) = scheduler.neighbours.get_mut(&node_id) {
-                    entry.timestamp = SystemTime::now();
-                    entry.is_first = false;
+        match Router::instance().check_info_scheduler() {
+            Some((node_id, module, is_first, mut router_info)) => {
+                // send the latest feed ids with the first routing information
+                if is_first == true {
+                    let ids = Feed::get_latest_message_ids(5);
+                    router_info.feeds = Some(router_net_proto::FeedIdsTable { ids });
                 }
 
-                // create routing information
-                let data = Self::create(node_id.clone(), neighbour_last_sent, neighbour_is_first);
+                // create routing information message
+                let data = Self::create(router_info, &Node::get_id(), Node::get_keys());
 
-                // create result
-                return Some((node_id, module, data));
+                Some((node_id, module, data))
             }
+            None => None,
         }
-
-        None
     }
 
-    /// add new neighbour entry
-    pub fn add_neighbour(node_id: PeerId) {
-        let exists;
-        log::trace!("add new neighbour {:?} to RouterInfo scheduler", node_id);
-        // check if a neighbour entry exists
-        {
-            let scheduler = SCHEDULER.get().read().unwrap();
-            exists = scheduler.neighbours.contains_key(&node_id);
+    /// Create a delta encoded routing entry
+    ///
+    /// Only the changed fields are filled in.
+    /// The advertised entry is updated with the sent values.
+    /// Returns None if nothing changed.
+    fn create_delta_entry(
+        advertised: &mut router_net_proto::RoutingInfoEntry,
+        route: &router_net_proto::RoutingInfoEntry,
+    ) -> Option<router_net_proto::RoutingInfoDeltaEntry> {
+        let mut delta = router_net_proto::RoutingInfoDeltaEntry {
+            user: route.user.clone(),
+            rtt: 0,
+            hc: Vec::new(),
+            pgid_increment: 0,
+            etx: 0,
+            bw: 0,
+        };
+        let mut changed = false;
+
+        // round trip time, small changes are not sent
+        let rtt_change = (route.rtt as i64 - advertised.rtt as i64).unsigned_abs();
+        if route.rtt > 0 && rtt_change * 100 > advertised.rtt as u64 * RTT_CHANGE_MIN {
+            delta.rtt = route.rtt;
+            advertised.rtt = route.rtt;
+            changed = true;
         }
 
-        // if it does not exist add it to scheduler
-        if !exists {
-            let mut scheduler = SCHEDULER.get().write().unwrap();
-            let interval = scheduler.interval.clone();
-            scheduler.neighbours.insert(
-                node_id,
-                SchedulerEntry {
-                    timestamp: SystemTime::now() - interval,
-                    is_first: true,
-                },
-            );
+        // hop count
+        if route.hc != advertised.hc {
+            delta.hc = route.hc.clone();
+            advertised.hc = route.hc.clone();
+            changed = true;
         }
-    }
 
-    /// Create routing information for a neighbour node,
-    /// encode the information and return the byte code.
-    pub fn create(neighbour: PeerId, last_sent: u64, is_first: bool) -> Vec<u8> {
-        let node_id = Node::get_id();
+        // propagation id
+        if route.pgid != advertised.pgid {
+            delta.pgid_increment = route.pgid.wrapping_sub(advertised.pgid);
+            advertised.pgid = route.pgid;
+            changed = true;
+        }
 
-        // create routing table
-        let routes = RoutingTable::create_routing_info(neighbour, last_sent);
+        // expected transmission count
+        if route.etx > 0 && route.etx != advertised.etx {
+            delta.etx = route.etx;
+            advertised.etx = route.etx;
+            changed = true;
+        }
 
-        // create latest Feed ids table
-        let mut feeds = router_net_proto::FeedIdsTable { ids: Vec::new() };
+        // bandwidth
+        if route.bw > 0 && route.bw != advertised.bw {
+            delta.bw = route.bw;
+            advertised.bw = route.bw;
+            changed = true;
+        }
 
-        if is_first == true {
-            let ids = Feed::get_latest_message_ids(5);
-            for id in ids {
-                feeds.ids.push(id.clone());
-            }
+        if changed {
+            return Some(delta);
         }
 
-        // create router info protobuf message
-        let timestamp = Timestamp::get_timestamp();
-        let router_info = router_net_proto::RouterInfoMessage {
-            node: node_id.clone().to_bytes(),
-            routes: Some(routes),
-            //users: Some(users),
-            feeds: Some(feeds),
-            timestamp,
-        };
+        None
+    }
 
+    /// Encode and sign the routing information message
+    /// of a node and return the byte code.
+    pub fn create(
+        router_info: router_net_proto::RouterInfoMessage,
+        node_id: &PeerId,
+        keys: &Keypair,
+    ) -> Vec<u8> {
         // encode router info message
         let mut buf = Vec::with_capacity(router_info.encoded_len());
         router_info
@@ -240,7 +266,7 @@ impl RouterInfo {
             id: node_id.to_bytes(),
             router_info_module: router_net_proto::RouterInfoModule::RouterInfo as i32,
             content: buf,
-            time: timestamp,
+            time: router_info.timestamp,
         };
 
         // encode content message
@@ -250,7 +276,6 @@ impl RouterInfo {
             .expect("Vec<u8> provides capacity as needed");
 
         // sign data
-        let keys = Node::get_keys();
         let signature = keys.sign(&buf).unwrap();
 
         // create signed container
@@ -458,154 +483,753 @@ impl RouterInfo {
         buf
     }
 
-    /// process received qaul_info message
-    pub fn received(received: QaulInfoReceived) {
-        // decode message to structure
-        let decoding_result = router_net_proto::RouterInfoContainer::decode(&received.data[..]);
+    /// process received routing information of a neighbour
+    ///
+    /// The routing information is processed by the router
+    /// instance of this node.
+    /// Unknown users of the routes are requested from the neighbour.
+    fn process_routing_info(neighbour_id: &PeerId, message: router_net_proto::RouterInfoMessage) {
+        let user_ids = Router::instance().process_routing_info(neighbour_id, message);
+
+        //check missed user ids
+        if user_ids.len() > 0 {
+            let missed_users = Users::get_missed_ids(&user_ids);
+            if missed_users.len() > 0 {
+                UserRequester::add(neighbour_id, &missed_users);
+            }
+        }
+    }
 
-        match decoding_result {
+    /// decode a received qaul_info message
+    ///
+    /// Returns the content of the signed container.
+    pub fn decode(data: &Vec<u8>) -> Option<router_net_proto::RouterInfoContent> {
+        // decode message to structure
+        match router_net_proto::RouterInfoContainer::decode(&data[..]) {
             Ok(container) => {
                 // TODO: check signature
                 //let signature = container.signature;message.ids
 
                 // decode message
-                let message_result =
-                    router_net_proto::RouterInfoContent::decode(&container.message[..]);
-
-                match message_result {
-                    Ok(content) => {
-                        match router_net_proto::RouterInfoModule::from_i32(
-                            content.router_info_module,
-                        ) {
-                            Some(router_net_proto::RouterInfoModule::RouterInfo) => {
-                                let message_info = router_net_proto::RouterInfoMessage::decode(
-                                    &content.content[..],
-                                );
-                                if let Ok(message) = message_info {
-                                    // collect users and routes
-                                    let messages = message;
-                                    //let users = messages.users;
-                                    let routes = messages.routes;
-                                    let feeds = messages.feeds;
-
-                                    // match users {
-                                    //     Some(router_net_proto::UserInfoTable { info }) => {
-                                    //         Users::add_user_info_table(info);
-                                    //     }
-                                    //     _ => {}
-                                    // }
-
-                                    match routes {
-                                        Some(router_net_proto::RoutingInfoTable { entry }) => {
-                                            //check missed user ids
-                                            let mut user_ids: Vec<Vec<u8>> = vec![];
-                                            for e in &entry {
-                                                user_ids.push(e.user.clone());
-                                            }
-                                            let missed_users = Users::get_missed_ids(&user_ids);
-                                            if missed_users.len() > 0 {
-                                                UserRequester::add(
-                                                    &received.received_from,
-                                                    &missed_users,
-                                                );
-                                            }
-
-                                            //process routing table
-                                            ConnectionTable::process_received_routing_info(
-                                                received.received_from,
-                                                &entry,
-                                            );
-                                        }
-                                        _ => {}
-                                    }
-                                    match feeds {
-                                        Some(router_net_proto::FeedIdsTable { ids }) => {
-                                            let missing_ids = Feed::process_received_feed_ids(&ids);
-                                            if missing_ids.len() > 0 {
-                                                FeedRequester::add(
-                                                    &received.received_from,
-                                                    &missing_ids,
-                                                );
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
+                match router_net_proto::RouterInfoContent::decode(&container.message[..]) {
+                    Ok(content) => return Some(content),
+                    Err(msg) => {
+                        log::error!("RouterInfoContent decode {:?}", msg);
+                    }
+                }
+            }
+            Err(msg) => {
+                log::error!("RouterInfoContainer decode {:?}", msg);
+            }
+        }
+
+        None
+    }
+
+    /// process received qaul_info message
+    pub fn received(received: QaulInfoReceived) {
+        // decode message to structure
+        let content;
+        match Self::decode(&received.data) {
+            Some(decoded) => content = decoded,
+            None => return,
+        }
+
+        match router_net_proto::RouterInfoModule::from_i32(content.router_info_module) {
+            Some(router_net_proto::RouterInfoModule::RouterInfo) => {
+                let message_info =
+                    router_net_proto::RouterInfoMessage::decode(&content.content[..]);
+                if let Ok(message) = message_info {
+                    // collect users and routes
+                    let feeds = message.feeds.clone();
+
+                    // match users {
+                    //     Some(router_net_proto::UserInfoTable { info }) => {
+                    //         Users::add_user_info_table(info);
+                    //     }
+                    //     _ => {}
+                    // }
+
+                    // process routing information
+                    Self::process_routing_info(&received.received_from, message);
+
+                    match feeds {
+                        Some(router_net_proto::FeedIdsTable { ids }) => {
+                            let missing_ids = Feed::process_received_feed_ids(&ids);
+                            if missing_ids.len() > 0 {
+                                FeedRequester::add(&received.received_from, &missing_ids);
                             }
-                            Some(router_net_proto::RouterInfoModule::FeedRequest) => {
-                                let message_info = router_net_proto::FeedRequestMessage::decode(
-                                    &content.content[..],
-                                );
-                                if let Ok(message) = message_info {
-                                    match message.feeds {
-                                        Some(table) => {
-                                            let feeds = Feed::get_messges_by_ids(&table.ids);
-                                            if feeds.len() > 0 {
-                                                FeedResponser::add(&received.received_from, &feeds);
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Some(router_net_proto::RouterInfoModule::FeedRequest) => {
+                let message_info =
+                    router_net_proto::FeedRequestMessage::decode(&content.content[..]);
+                if let Ok(message) = message_info {
+                    match message.feeds {
+                        Some(table) => {
+                            let feeds = Feed::get_messges_by_ids(&table.ids);
+                            if feeds.len() > 0 {
+                                FeedResponser::add(&received.received_from, &feeds);
                             }
-                            Some(router_net_proto::RouterInfoModule::FeedResponse) => {
-                                let message_info = router_net_proto::FeedResponseMessage::decode(
-                                    &content.content[..],
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Some(router_net_proto::RouterInfoModule::FeedResponse) => {
+                let message_info =
+                    router_net_proto::FeedResponseMessage::decode(&content.content[..]);
+                if let Ok(message) = message_info {
+                    match message.feeds {
+                        Some(table) => {
+                            let mut user_ids: Vec<Vec<u8>> = vec![];
+                            for feed in table.messages {
+                                user_ids.push(QaulId::bytes_to_q8id(feed.sender_id.clone()));
+                                Feed::save_message_by_sync(
+                                    &feed.message_id,
+                                    &feed.sender_id,
+                                    feed.content,
+                                    feed.time,
                                 );
-                                if let Ok(message) = message_info {
-                                    match message.feeds {
-                                        Some(table) => {
-                                            let mut user_ids: Vec<Vec<u8>> = vec![];
-                                            for feed in table.messages {
-                                                user_ids.push(QaulId::bytes_to_q8id(
-                                                    feed.sender_id.clone(),
-                                                ));
-                                                Feed::save_message_by_sync(
-                                                    &feed.message_id,
-                                                    &feed.sender_id,
-                                                    feed.content,
-                                                    feed.time,
-                                                );
-                                            }
-                                            // check missed users
-                                            let missed_users = Users::get_missed_ids(&user_ids);
-                                            if missed_users.len() > 0 {
-                                                UserRequester::add(
-                                                    &received.received_from,
-                                                    &missed_users,
-                                                );
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
                             }
-                            Some(router_net_proto::RouterInfoModule::UserRequest) => {
-                                let message_info =
-                                    router_net_proto::UserIdTable::decode(&content.content[..]);
-                                if let Ok(message) = message_info {
-                                    let table = Users::get_user_info_table_by_q8ids(&message.ids);
-                                    UserResponser::add(&received.received_from, &table);
-                                }
+                            // check missed users
+                            let missed_users = Users::get_missed_ids(&user_ids);
+                            if missed_users.len() > 0 {
+                                UserRequester::add(&received.received_from, &missed_users);
                             }
-                            Some(router_net_proto::RouterInfoModule::UserResponse) => {
-                                let message_info =
-                                    router_net_proto::UserInfoTable::decode(&content.content[..]);
-                                if let Ok(message) = message_info {
-                                    Users::add_user_info_table(&message.info);
-                                }
-                            }
-                            _ => {}
                         }
+                        _ => {}
                     }
-                    Err(msg) => {
-                        log::error!("RouterInfoContent decode {:?}", msg);
+                }
+            }
+            Some(router_net_proto::RouterInfoModule::UserRequest) => {
+                let message_info = router_net_proto::UserIdTable::decode(&content.content[..]);
+                if let Ok(message) = message_info {
+                    let table = Users::get_user_info_table_by_q8ids(&message.ids);
+                    UserResponser::add(&received.received_from, &table);
+                }
+            }
+            Some(router_net_proto::RouterInfoModule::UserResponse) => {
+                let message_info = router_net_proto::UserInfoTable::decode(&content.content[..]);
+                if let Ok(message) = message_info {
+                    Users::add_user_info_table(&message.info);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Routing information scheduler of the router instance
+impl RouterInstance {
+    /// add a new neighbour to the routing information scheduler
+    pub fn add_info_neighbour(&self, node_id: PeerId) {
+        log::trace!("add new neighbour {:?} to RouterInfo scheduler", node_id);
+        let now = self.now();
+        let mut scheduler = self.scheduler.write().unwrap();
+
+        // if it does not exist add it to scheduler
+        if !scheduler.neighbours.contains_key(&node_id) {
+            let timestamp = now.saturating_sub(scheduler.interval);
+            scheduler.neighbours.insert(
+                node_id,
+                SchedulerEntry {
+                    timestamp,
+                    is_first: true,
+                    sequence: 0,
+                    snapshot_at: 0,
+                    snapshot_requested: false,
+                    advertised: HashMap::new(),
+                },
+            );
+        }
+    }
+
+    /// This loops over all neighbours
+    /// and checks if there is any timeout.
+    /// If it finds a timeout it returns the node id,
+    /// the connection module to reach it, whether it
+    /// is the first routing information for this node
+    /// and the routing information message to send to it.
+    pub fn check_info_scheduler(
+        &self,
+    ) -> Option<(
+        PeerId,
+        ConnectionModule,
+        bool,
+        router_net_proto::RouterInfoMessage,
+    )> {
+        let now = self.now();
+        let mut found_neighbour: Option<PeerId> = None;
+        let mut neighbour_last_sent: u64 = 0;
+        let mut neighbour_is_first: bool = false;
+        let mut propagation_update: Option<u32> = None;
+
+        {
+            // get scheduler for writing
+            let mut scheduler = self.scheduler.write().unwrap();
+
+            // loop over all neighbours
+            for (id, ctx) in scheduler.neighbours.iter() {
+                if ctx.timestamp + scheduler.interval <= now {
+                    found_neighbour = Some(id.clone());
+                    neighbour_last_sent = ctx.timestamp;
+                    neighbour_is_first = ctx.is_first;
+                    break;
+                }
+            }
+
+            // check if we have to update the propagation ID
+            if now >= scheduler.propagation_timestamp + PROPAGATION_PERIOD {
+                scheduler.propagation_id += 1;
+                scheduler.propagation_timestamp = now;
+                propagation_update = Some(scheduler.propagation_id);
+            }
+        }
+
+        // update propagation ID
+        if let Some(propagation_id) = propagation_update {
+            self.update_propagation_id(propagation_id);
+        }
+
+        // process finding
+        if let Some(node_id) = found_neighbour {
+            // Check whether this node is
+            // still connected and over which connection module
+            // we can approach it.
+            let module = self.is_neighbour(&node_id);
+
+            let mut update = None;
+            let mut resync = false;
+            {
+                // get scheduler for writing
+                let mut state = self.scheduler.write().unwrap();
+                let scheduler = &mut *state;
+
+                if module == ConnectionModule::None {
+                    log::debug!("node is not a neighbour anymore: {:?}", node_id);
+                    // delete this entry
+                    scheduler.neighbours.remove(&node_id);
+                    scheduler.received.remove(&node_id);
+                } else {
+                    // check which version the neighbour supports
+                    // and if we need to request a snapshot
+                    let mut peer_version = None;
+                    if let Some(received) = scheduler.received.get_mut(&node_id) {
+                        peer_version = Some(received.version);
+                        resync = received.resync;
+                        received.resync = false;
+                    }
+
+                    // update timer
+                    if let Some(entry) = scheduler.neighbours.get_mut(&node_id) {
+                        entry.timestamp = now;
+                        entry.is_first = false;
+
+                        // create routing information
+                        update = Some(self.create_routing_update(
+                            &node_id,
+                            entry,
+                            peer_version,
+                            neighbour_last_sent,
+                            scheduler.snapshot_interval,
+                        ));
                     }
                 }
             }
-            Err(msg) => {
-                log::error!("RouterInfoContainer decode {:?}", msg);
+
+            if let Some(update) = update {
+                // create router info protobuf message
+                let router_info = router_net_proto::RouterInfoMessage {
+                    node: self.node_id.to_bytes(),
+                    routes: Some(update.routes),
+                    feeds: Some(router_net_proto::FeedIdsTable { ids: Vec::new() }),
+                    timestamp: now,
+                    version: ROUTING_INFO_VERSION,
+                    sequence: update.sequence,
+                    snapshot: update.snapshot,
+                    delta: update.delta,
+                    resync,
+                };
+
+                // create result
+                return Some((node_id, module, neighbour_is_first, router_info));
+            }
+        }
+
+        None
+    }
+
+    /// Create the routing information update for a neighbour node
+    ///
+    /// A full snapshot is created periodically, when the neighbour
+    /// requested it or when the neighbour does not support
+    /// delta updates.
+    /// Otherwise a delta update to the routes the neighbour
+    /// already received is created.
+    fn create_routing_update(
+        &self,
+        neighbour: &PeerId,
+        entry: &mut SchedulerEntry,
+        peer_version: Option<u32>,
+        last_sent: u64,
+        snapshot_interval: u64,
+    ) -> RoutingUpdate {
+        let now = self.now();
+        entry.sequence = entry.sequence.wrapping_add(1);
+
+        // neighbours without delta support are sent all
+        // routes updated since the last sending
+        if let Some(version) = peer_version {
+            if version < ROUTING_INFO_VERSION {
+                entry.snapshot_at = now;
+                entry.advertised = HashMap::new();
+
+                return RoutingUpdate {
+                    sequence: entry.sequence,
+                    snapshot: true,
+                    routes: self.create_routing_info(neighbour.to_owned(), last_sent),
+                    delta: None,
+                };
+            }
+        }
+
+        // get all routes for this neighbour
+        let table = self.create_routing_info(neighbour.to_owned(), 0);
+
+        // create full snapshot
+        if peer_version.is_none()
+            || entry.snapshot_requested
+            || now >= entry.snapshot_at + snapshot_interval
+        {
+            entry.snapshot_requested = false;
+            entry.snapshot_at = now;
+            entry.advertised = HashMap::new();
+            for route in &table.entry {
+                entry.advertised.insert(route.user.clone(), route.clone());
+            }
+
+            return RoutingUpdate {
+                sequence: entry.sequence,
+                snapshot: true,
+                routes: table,
+                delta: None,
+            };
+        }
+
+        // create delta update
+        let mut routes = router_net_proto::RoutingInfoTable { entry: Vec::new() };
+        let mut delta = router_net_proto::RoutingInfoDelta {
+            entry: Vec::new(),
+            withdrawn: Vec::new(),
+        };
+        let mut users: HashSet<Vec<u8>> = HashSet::new();
+
+        for route in table.entry {
+            users.insert(route.user.clone());
+
+            match entry.advertised.get_mut(&route.user) {
+                Some(advertised) => {
+                    if let Some(delta_entry) = RouterInfo::create_delta_entry(advertised, &route) {
+                        delta.entry.push(delta_entry);
+                    }
+                }
+                None => {
+                    entry.advertised.insert(route.user.clone(), route.clone());
+                    routes.entry.push(route);
+                }
             }
         }
+
+        // withdraw the routes that don't exist anymore
+        for user in entry.advertised.keys() {
+            if !users.contains(user) {
+                delta.withdrawn.push(user.clone());
+            }
+        }
+        for user in &delta.withdrawn {
+            entry.advertised.remove(user);
+        }
+
+        RoutingUpdate {
+            sequence: entry.sequence,
+            snapshot: false,
+            routes,
+            delta: Some(delta),
+        }
+    }
+
+    /// process received routing information of a neighbour
+    ///
+    /// Snapshots replace all routes of the neighbour,
+    /// delta updates are applied to them.
+    /// If a gap in the sequence numbers is detected,
+    /// the delta update is dropped and a snapshot is requested.
+    ///
+    /// Returns the user ids of all processed routes.
+    pub fn process_routing_info(
+        &self,
+        neighbour_id: &PeerId,
+        message: router_net_proto::RouterInfoMessage,
+    ) -> Vec<Vec<u8>> {
+        let mut routes: Vec<router_net_proto::RoutingInfoEntry> = Vec::new();
+        let mut withdrawn: Vec<Vec<u8>> = Vec::new();
+        let new_routes = match message.routes {
+            Some(table) => table.entry,
+            None => Vec::new(),
+        };
+        let now = self.now();
+
+        {
+            let mut state = self.scheduler.write().unwrap();
+            let scheduler = &mut *state;
+            let due = now.saturating_sub(scheduler.interval);
+
+            // the neighbour requested a full snapshot
+            if message.resync {
+                if let Some(entry) = scheduler.neighbours.get_mut(neighbour_id) {
+                    entry.snapshot_requested = true;
+                    entry.timestamp = due;
+                }
+            }
+
+            let received =
+                scheduler
+                    .received
+                    .entry(neighbour_id.to_owned())
+                    .or_insert(ReceivedInfo {
+                        version: message.version,
+                        synchronized: false,
+                        sequence: 0,
+                        resync: false,
+                        routes: HashMap::new(),
+                    });
+            received.version = message.version;
+
+            if message.version < ROUTING_INFO_VERSION {
+                // the neighbour sends the full routing information
+                routes = new_routes;
+            } else if message.snapshot {
+                // routes missing in the snapshot are withdrawn
+                let mut snapshot_routes = HashMap::new();
+                for route in new_routes {
+                    snapshot_routes.insert(route.user.clone(), route);
+                }
+                for user in received.routes.keys() {
+                    if !snapshot_routes.contains_key(user) {
+                        withdrawn.push(user.clone());
+                    }
+                }
+
+                received.routes = snapshot_routes;
+                received.sequence = message.sequence;
+                received.synchronized = true;
+                received.resync = false;
+                routes = received.routes.values().cloned().collect();
+            } else if received.synchronized && message.sequence == received.sequence.wrapping_add(1)
+            {
+                // add new routes
+                for route in new_routes {
+                    received.routes.insert(route.user.clone(), route);
+                }
+
+                // apply delta update
+                if let Some(delta) = message.delta {
+                    for delta_entry in delta.entry {
+                        if let Some(route) = received.routes.get_mut(&delta_entry.user) {
+                            if delta_entry.rtt > 0 {
+                                route.rtt = delta_entry.rtt;
+                            }
+                            if delta_entry.hc.len() > 0 {
+                                route.hc = delta_entry.hc;
+                            }
+                            route.pgid = route.pgid.wrapping_add(delta_entry.pgid_increment);
+                            if delta_entry.etx > 0 {
+                                route.etx = delta_entry.etx;
+                            }
+                            if delta_entry.bw > 0 {
+                                route.bw = delta_entry.bw;
+                            }
+                        }
+                    }
+                    for user in delta.withdrawn {
+                        if received.routes.remove(&user).is_some() {
+                            withdrawn.push(user);
+                        }
+                    }
+                }
+
+                received.sequence = message.sequence;
+                routes = received.routes.values().cloned().collect();
+            } else {
+                // gap detected, request a snapshot
+                log::debug!(
+                    "routing info gap from {:?}: expected {}, received {}",
+                    neighbour_id,
+                    received.sequence.wrapping_add(1),
+                    message.sequence
+                );
+                received.resync = true;
+                if let Some(entry) = scheduler.neighbours.get_mut(neighbour_id) {
+                    entry.timestamp = due;
+                }
+            }
+        }
+
+        // remove withdrawn routes
+        if withdrawn.len() > 0 {
+            self.process_withdrawn_routes(neighbour_id.to_owned(), &withdrawn);
+        }
+
+        let mut user_ids: Vec<Vec<u8>> = vec![];
+        if routes.len() == 0 {
+            return user_ids;
+        }
+
+        for route in &routes {
+            user_ids.push(route.user.clone());
+        }
+
+        //process routing table
+        self.process_received_routing_info(neighbour_id.to_owned(), &routes);
+
+        user_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::instance::{Clock, RouterHandle};
+    use std::sync::atomic::AtomicU64;
+
+    /// virtual start time of the tests in milli seconds
+    const START: u64 = 1_000_000_000;
+
+    /// create a router instance with a virtual clock
+    /// and a neighbour connected via LAN
+    fn create_router(neighbour: &PeerId) -> RouterHandle {
+        let router = RouterInstance::new(
+            PeerId::random(),
+            RoutingOptions::default(),
+            Clock::Virtual(AtomicU64::new(START)),
+        );
+        router.update_neighbour(ConnectionModule::Lan, neighbour.to_owned(), 1000);
+        router
+    }
+
+    /// create a routing entry to a user
+    fn route(user: &PeerId, hc: u8) -> router_net_proto::RoutingInfoEntry {
+        router_net_proto::RoutingInfoEntry {
+            user: QaulId::to_q8id(user.to_owned()),
+            rtt: 1000,
+            hc: vec![hc],
+            pgid: 1,
+            etx: 0,
+            bw: 0,
+        }
+    }
+
+    /// create a routing information message
+    fn message(
+        sequence: u32,
+        snapshot: bool,
+        routes: Vec<router_net_proto::RoutingInfoEntry>,
+        delta: Option<router_net_proto::RoutingInfoDelta>,
+    ) -> router_net_proto::RouterInfoMessage {
+        router_net_proto::RouterInfoMessage {
+            node: Vec::new(),
+            routes: Some(router_net_proto::RoutingInfoTable { entry: routes }),
+            feeds: None,
+            timestamp: START,
+            version: ROUTING_INFO_VERSION,
+            sequence,
+            snapshot,
+            delta,
+            resync: false,
+        }
+    }
+
+    /// get the routes received from a neighbour
+    fn received_routes(
+        router: &RouterHandle,
+        neighbour: &PeerId,
+    ) -> HashMap<Vec<u8>, router_net_proto::RoutingInfoEntry> {
+        let scheduler = router.scheduler.read().unwrap();
+        scheduler.received.get(neighbour).unwrap().routes.clone()
+    }
+
+    #[test]
+    fn delta_entry() {
+        let user = PeerId::random();
+        let mut advertised = route(&user, 1);
+        advertised.etx = 100;
+
+        // nothing changed
+        let unchanged = advertised.clone();
+        assert!(RouterInfo::create_delta_entry(&mut advertised, &unchanged).is_none());
+
+        // small round trip time changes are not sent
+        let mut changed = advertised.clone();
+        changed.rtt = 1050;
+        changed.hc = vec![2];
+        changed.pgid = 3;
+        let delta = RouterInfo::create_delta_entry(&mut advertised, &changed).unwrap();
+        assert_eq!(delta.user, changed.user);
+        assert_eq!(delta.rtt, 0);
+        assert_eq!(delta.hc, vec![2]);
+        assert_eq!(delta.pgid_increment, 2);
+        assert_eq!(delta.etx, 0);
+        assert_eq!(delta.bw, 0);
+        assert_eq!(advertised.rtt, 1000);
+        assert_eq!(advertised.hc, vec![2]);
+        assert_eq!(advertised.pgid, 3);
+
+        // the propagation id increment wraps around
+        let mut changed = advertised.clone();
+        changed.rtt = 1200;
+        changed.pgid = 1;
+        let delta = RouterInfo::create_delta_entry(&mut advertised, &changed).unwrap();
+        assert_eq!(delta.rtt, 1200);
+        assert_eq!(delta.hc, Vec::<u8>::new());
+        assert_eq!(delta.pgid_increment, u32::MAX - 1);
+        assert_eq!(advertised.pgid, 1);
+    }
+
+    #[test]
+    fn in_order_delta() {
+        let neighbour = PeerId::random();
+        let router = create_router(&neighbour);
+        let user_a = PeerId::random();
+        let user_b = PeerId::random();
+        let user_c = PeerId::random();
+
+        router.process_routing_info(
+            &neighbour,
+            message(1, true, vec![route(&user_a, 1), route(&user_b, 1)], None),
+        );
+
+        // add user c, change user a and withdraw user b
+        let delta = router_net_proto::RoutingInfoDelta {
+            entry: vec![router_net_proto::RoutingInfoDeltaEntry {
+                user: QaulId::to_q8id(user_a),
+                rtt: 0,
+                hc: vec![2],
+                pgid_increment: 1,
+                etx: 0,
+                bw: 0,
+            }],
+            withdrawn: vec![QaulId::to_q8id(user_b)],
+        };
+        let user_ids = router.process_routing_info(
+            &neighbour,
+            message(2, false, vec![route(&user_c, 1)], Some(delta)),
+        );
+        assert_eq!(user_ids.len(), 2);
+
+        let routes = received_routes(&router, &neighbour);
+        assert_eq!(routes.len(), 2);
+        let route_a = routes.get(&QaulId::to_q8id(user_a)).unwrap();
+        assert_eq!(route_a.hc, vec![2]);
+        assert_eq!(route_a.pgid, 2);
+        assert_eq!(route_a.rtt, 1000);
+        assert!(routes.contains_key(&QaulId::to_q8id(user_c)));
+        assert!(!routes.contains_key(&QaulId::to_q8id(user_b)));
+    }
+
+    #[test]
+    fn lost_delta_requests_snapshot() {
+        let neighbour = PeerId::random();
+        let router = create_router(&neighbour);
+        let user_a = PeerId::random();
+        let user_b = PeerId::random();
+
+        router.process_routing_info(&neighbour, message(1, true, vec![route(&user_a, 1)], None));
+
+        // the delta update with sequence number 2 was lost
+        let user_ids = router
+            .process_routing_info(&neighbour, message(3, false, vec![route(&user_b, 1)], None));
+        assert!(user_ids.is_empty());
+        assert_eq!(received_routes(&router, &neighbour).len(), 1);
+
+        // the next routing information requests a snapshot
+        let (node_id, module, is_first, router_info) = router.check_info_scheduler().unwrap();
+        assert_eq!(node_id, neighbour);
+        assert!(module == ConnectionModule::Lan);
+        assert!(is_first);
+        assert!(router_info.resync);
+
+        // the delta updates are dropped until the snapshot arrives
+        let user_ids = router.process_routing_info(&neighbour, message(4, false, Vec::new(), None));
+        assert!(user_ids.is_empty());
+
+        let user_ids = router.process_routing_info(
+            &neighbour,
+            message(7, true, vec![route(&user_a, 1), route(&user_b, 1)], None),
+        );
+        assert_eq!(user_ids.len(), 2);
+
+        let user_ids = router.process_routing_info(&neighbour, message(8, false, Vec::new(), None));
+        assert_eq!(user_ids.len(), 2);
+    }
+
+    #[test]
+    fn snapshot_replaces_routes() {
+        let neighbour = PeerId::random();
+        let router = create_router(&neighbour);
+        let user_a = PeerId::random();
+        let user_b = PeerId::random();
+
+        router.process_routing_info(
+            &neighbour,
+            message(1, true, vec![route(&user_a, 1), route(&user_b, 1)], None),
+        );
+        router.create_routing_table();
+        assert_eq!(router.get_routes_to_user(user_a).len(), 1);
+        assert_eq!(router.get_routes_to_user(user_b).len(), 1);
+        assert_eq!(router.get_routes_to_user(user_b)[0].node, neighbour);
+
+        // user b is missing in the snapshot
+        let user_ids = router
+            .process_routing_info(&neighbour, message(2, true, vec![route(&user_a, 1)], None));
+        assert_eq!(user_ids, vec![QaulId::to_q8id(user_a)]);
+
+        router.create_routing_table();
+        assert_eq!(router.get_routes_to_user(user_a).len(), 1);
+        assert!(router.get_routes_to_user(user_b).is_empty());
+    }
+
+    #[test]
+    fn version_0_neighbour() {
+        let neighbour = PeerId::random();
+        let router = create_router(&neighbour);
+        let user_a = PeerId::random();
+        let user_b = PeerId::random();
+
+        // the full routing information is processed in every message
+        let mut info = message(0, false, vec![route(&user_a, 1)], None);
+        info.version = 0;
+        let user_ids = router.process_routing_info(&neighbour, info);
+        assert_eq!(user_ids, vec![QaulId::to_q8id(user_a)]);
+
+        let mut info = message(0, false, vec![route(&user_b, 1)], None);
+        info.version = 0;
+        let user_ids = router.process_routing_info(&neighbour, info);
+        assert_eq!(user_ids, vec![QaulId::to_q8id(user_b)]);
+
+        // the neighbour is only sent snapshots
+        for _ in 0..2 {
+            let (node_id, _module, _is_first, router_info) = router.check_info_scheduler().unwrap();
+            assert_eq!(node_id, neighbour);
+            assert!(router_info.snapshot);
+            assert!(router_info.delta.is_none());
+            assert!(!router_info.resync);
+
+            let interval = router.scheduler.read().unwrap().interval;
+            router.set_time(router.now() + interval);
+        }
     }
 }
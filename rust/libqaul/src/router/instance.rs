@@ -0,0 +1,124 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Router Instance
+//!
+//! The router instance contains the mutable state of the router:
+//!
+//! * the neighbour tables per connection module
+//! * the connection tables per connection module
+//! * the global routing table
+//! * the loop detector
+//! * the routing information scheduler
+//!
+//! libqaul runs a single router instance, which is accessed
+//! via `Router::instance()`.
+//! The router simulator creates an instance for every
+//! simulated node, that many routers can run in one process.
+//!
+//! The routing logic is implemented on the instance in the
+//! submodules of the respective tables.
+
+use libp2p::PeerId;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use super::connections::ConnectionTable;
+use super::info::Scheduler;
+use super::loops::LoopDetector;
+use super::neighbours::Neighbours;
+use super::table::RoutingTable;
+use crate::storage::configuration::RoutingOptions;
+use crate::utilities::timestamp::Timestamp;
+
+/// Shared handle to a router instance
+pub type RouterHandle = Arc<RouterInstance>;
+
+/// Time source of a router instance
+pub enum Clock {
+    /// the system time
+    System,
+    /// a virtual time in milli seconds,
+    /// which only advances when it is told so
+    #[cfg_attr(not(feature = "simulator"), allow(dead_code))]
+    Virtual(AtomicU64),
+}
+
+impl Clock {
+    /// get the current time of the clock
+    /// in milli seconds since UNIX_EPOCH
+    fn now(&self) -> u64 {
+        match self {
+            Clock::System => Timestamp::get_timestamp(),
+            Clock::Virtual(time) => time.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Router Instance
+///
+/// Contains all tables of a router.
+pub struct RouterInstance {
+    /// node id of this router
+    pub node_id: PeerId,
+    /// router configuration
+    pub configuration: RoutingOptions,
+    /// time source
+    clock: Clock,
+    /// Internet neighbour node table
+    pub(super) internet_neighbours: RwLock<Neighbours>,
+    /// LAN neighbour node table
+    pub(super) lan_neighbours: RwLock<Neighbours>,
+    /// BLE neighbour node table
+    pub(super) ble_neighbours: RwLock<Neighbours>,
+    /// routes of the local users
+    pub(super) local: RwLock<RoutingTable>,
+    /// Internet connection table
+    pub(super) internet: RwLock<ConnectionTable>,
+    /// LAN connection table
+    pub(super) lan: RwLock<ConnectionTable>,
+    /// global routing table
+    pub(super) table: RwLock<RoutingTable>,
+    /// loop detector
+    pub(super) loops: RwLock<LoopDetector>,
+    /// routing information scheduler
+    pub(super) scheduler: RwLock<Scheduler>,
+}
+
+impl RouterInstance {
+    /// create a new router instance with empty tables
+    pub fn new(node_id: PeerId, configuration: RoutingOptions, clock: Clock) -> RouterHandle {
+        let scheduler = Scheduler::new(&configuration, clock.now());
+        Arc::new(RouterInstance {
+            node_id,
+            configuration,
+            clock,
+            internet_neighbours: RwLock::new(Neighbours::new()),
+            lan_neighbours: RwLock::new(Neighbours::new()),
+            ble_neighbours: RwLock::new(Neighbours::new()),
+            local: RwLock::new(RoutingTable::new()),
+            internet: RwLock::new(ConnectionTable::new()),
+            lan: RwLock::new(ConnectionTable::new()),
+            table: RwLock::new(RoutingTable::new()),
+            loops: RwLock::new(LoopDetector::new()),
+            scheduler: RwLock::new(scheduler),
+        })
+    }
+
+    /// get the current time of this router
+    /// in milli seconds since UNIX_EPOCH
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    /// set the virtual time of this router
+    ///
+    /// The system clock can't be set.
+    #[cfg_attr(not(feature = "simulator"), allow(dead_code))]
+    pub fn set_time(&self, timestamp: u64) {
+        match &self.clock {
+            Clock::System => log::error!("the system clock can't be set"),
+            Clock::Virtual(time) => time.store(timestamp, Ordering::SeqCst),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Link Quality Metrics
+//!
+//! The link quality (lq) of a route decides which neighbour
+//! is chosen to reach a user. The smaller the value is,
+//! the better is the route.
+//!
+//! The following metrics can be selected in the `RoutingOptions`
+//! of the configuration:
+//!
+//! * `Latency`: round trip time plus a penalty per hop.
+//!   This is the original qaul metric.
+//! * `Etx`: expected transmission count (ETX).
+//!   Instead of a fixed penalty per hop, every expected
+//!   transmission is penalized. Lossy links therefore get
+//!   a worse link quality than links without losses.
+//! * `Ett`: expected transmission time (ETT).
+//!   Like `Etx`, but every expected transmission is
+//!   additionally penalized by the time it takes to transmit
+//!   it over the slowest link of the route.
+//!
+//! The ETX of a link is estimated from the delivery ratio of
+//! the pings to the neighbour and of the confirmations of the
+//! messages sent via this neighbour.
+//! The ETX of a route is the sum of the ETX of all it's links
+//! and is carried in the `RoutingInfoEntry`.
+//!
+//! The bandwidth of a link is the nominal bandwidth of
+//! the connection module over which the neighbour is connected.
+//! The bandwidth of a route is the bandwidth of it's slowest link
+//! and is carried in the `RoutingInfoEntry` as well.
+
+use serde::{Deserialize, Serialize};
+
+use crate::connections::ConnectionModule;
+use crate::storage::configuration::RoutingOptions;
+
+/// ETX values are fixed point numbers with this scale
+///
+/// A perfect link without any losses has an ETX of `ETX_SCALE`.
+pub const ETX_SCALE: u32 = 1000;
+
+/// minimal delivery ratio in per mille
+///
+/// This limits the ETX of a single link to 20 transmissions.
+const DELIVERY_RATIO_MIN: u32 = 50;
+
+/// weight of the history in the delivery ratio average
+/// of 8 samples
+const DELIVERY_HISTORY_WEIGHT: u32 = 7;
+
+/// reference bandwidth in kbit/s
+///
+/// Links with this bandwidth or faster are not penalized
+/// by the ETT metric.
+const BANDWIDTH_REFERENCE: u64 = 1000;
+
+/// maximal slow down of a transmission in per mille
+///
+/// This limits the penalty of very slow links to
+/// 100 times the penalty of a fast link.
+const SLOWDOWN_MAX: u64 = 100_000;
+
+/// Selectable link quality metrics
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LinkMetricType {
+    /// round trip time and hop count penalty
+    Latency,
+    /// expected transmission count
+    Etx,
+    /// expected transmission time
+    Ett,
+}
+
+impl Default for LinkMetricType {
+    fn default() -> Self {
+        LinkMetricType::Latency
+    }
+}
+
+impl LinkMetricType {
+    /// get the link metric implementation for this type
+    pub fn metric(&self, config: &RoutingOptions) -> Box<dyn LinkMetric> {
+        match self {
+            LinkMetricType::Latency => Box::new(LatencyMetric {
+                hop_count_penalty: config.hop_count_penalty,
+            }),
+            LinkMetricType::Etx => Box::new(EtxMetric {
+                hop_count_penalty: config.hop_count_penalty,
+            }),
+            LinkMetricType::Ett => Box::new(EttMetric {
+                hop_count_penalty: config.hop_count_penalty,
+            }),
+        }
+    }
+}
+
+/// Link quality metric
+pub trait LinkMetric {
+    /// calculate the link quality of a route
+    ///
+    /// * `rtt` the round trip time of the route in micro seconds
+    /// * `hc` the hop count of the route
+    /// * `etx` the expected transmission count of the route,
+    ///   scaled by `ETX_SCALE`
+    /// * `bw` the bandwidth of the slowest link of the route
+    ///   in kbit/s, 0 if it is unknown
+    ///
+    /// The smaller the value is, the better is the link quality.
+    fn link_quality(&self, rtt: u32, hc: u8, etx: u32, bw: u32) -> u32;
+}
+
+/// Latency metric
+///
+/// Round trip time plus the hop count penalty for each hop.
+pub struct LatencyMetric {
+    /// hop count penalty in seconds
+    hop_count_penalty: u64,
+}
+
+impl LinkMetric for LatencyMetric {
+    fn link_quality(&self, rtt: u32, hc: u8, _etx: u32, _bw: u32) -> u32 {
+        // `hop_count_penalty` is seconds unit, thus it must be converted micro seconds
+        let lq = rtt as u64 + hc as u64 * self.hop_count_penalty * 1000_000;
+
+        lq.min(u32::MAX as u64) as u32
+    }
+}
+
+/// ETX metric
+///
+/// Round trip time plus the hop count penalty for every
+/// expected transmission.
+/// On routes without any losses, the result is the same
+/// as the one of the latency metric.
+pub struct EtxMetric {
+    /// hop count penalty in seconds
+    hop_count_penalty: u64,
+}
+
+impl LinkMetric for EtxMetric {
+    fn link_quality(&self, rtt: u32, _hc: u8, etx: u32, _bw: u32) -> u32 {
+        let penalty = etx as u64 * self.hop_count_penalty * 1000_000 / ETX_SCALE as u64;
+        let lq = rtt as u64 + penalty;
+
+        lq.min(u32::MAX as u64) as u32
+    }
+}
+
+/// ETT metric
+///
+/// Round trip time plus the hop count penalty for every
+/// expected transmission, slowed down by the bandwidth
+/// of the slowest link of the route.
+/// On routes with the reference bandwidth or an unknown
+/// bandwidth, the result is the same as the one of the
+/// ETX metric.
+pub struct EttMetric {
+    /// hop count penalty in seconds
+    hop_count_penalty: u64,
+}
+
+impl LinkMetric for EttMetric {
+    fn link_quality(&self, rtt: u32, _hc: u8, etx: u32, bw: u32) -> u32 {
+        // slow down of a transmission in per mille
+        let mut slowdown = 1000;
+        if bw > 0 {
+            slowdown = (BANDWIDTH_REFERENCE * 1000 / bw as u64).clamp(1000, SLOWDOWN_MAX);
+        }
+
+        let penalty = etx as u64 * self.hop_count_penalty * 1000_000 / ETX_SCALE as u64;
+        let lq = rtt as u64 + penalty.saturating_mul(slowdown) / 1000;
+
+        lq.min(u32::MAX as u64) as u32
+    }
+}
+
+/// get the nominal bandwidth of a connection module
+///
+/// returns the bandwidth in kbit/s, or 0 if the
+/// bandwidth is unknown or unlimited.
+pub fn module_bandwidth(module: &ConnectionModule) -> u32 {
+    match module {
+        ConnectionModule::Lan => 100_000,
+        ConnectionModule::Internet => 10_000,
+        ConnectionModule::Ble => 250,
+        ConnectionModule::Local | ConnectionModule::None => 0,
+    }
+}
+
+/// get the bandwidth of a route
+///
+/// The bandwidth of a route is the bandwidth of it's slowest link.
+/// Unknown bandwidths with the value 0 are ignored.
+pub fn route_bandwidth(route_bw: u32, link_bw: u32) -> u32 {
+    if route_bw == 0 {
+        return link_bw;
+    }
+    if link_bw == 0 {
+        return route_bw;
+    }
+    route_bw.min(link_bw)
+}
+
+/// Delivery statistics of a link to a neighbour
+///
+/// The delivery ratio is a moving average over the
+/// successes and failures of the last transmissions.
+#[derive(Debug, Clone, Copy)]
+pub struct LinkStatistics {
+    /// delivery ratio in per mille
+    delivery_ratio: u32,
+}
+
+impl LinkStatistics {
+    /// create statistics of a new link without any losses
+    pub fn new() -> LinkStatistics {
+        LinkStatistics {
+            delivery_ratio: 1000,
+        }
+    }
+
+    /// add the result of a transmission to the statistics
+    pub fn update(&mut self, success: bool) {
+        // successes are rounded up, that a link
+        // can recover to a delivery ratio of 1000
+        let sample = if success {
+            1000 + DELIVERY_HISTORY_WEIGHT
+        } else {
+            0
+        };
+        let ratio = (self.delivery_ratio * DELIVERY_HISTORY_WEIGHT + sample)
+            / (DELIVERY_HISTORY_WEIGHT + 1);
+
+        self.delivery_ratio = ratio.max(DELIVERY_RATIO_MIN);
+    }
+
+    /// get the expected transmission count of the link
+    ///
+    /// The value is scaled by `ETX_SCALE`.
+    pub fn etx(&self) -> u32 {
+        ETX_SCALE * 1000 / self.delivery_ratio
+    }
+}
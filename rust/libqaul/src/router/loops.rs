@@ -0,0 +1,202 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Loop Detection
+//!
+//! The loop detector remembers the signatures of all messages
+//! this node sent or forwarded within the configured `loop_window`
+//! of the routing options, together with the neighbour the
+//! message was received from.
+//! If a message with the same signature arrives again from
+//! another neighbour within this window, the message was routed
+//! back through this node and is caught in a routing loop.
+//!
+//! Looping messages are dropped and counted.
+//! Retransmissions of the messaging service are sent with the
+//! same signature. Messages resent by the user are sent
+//! immediately, they arrive from the same neighbour and
+//! are forwarded again.
+//! Automatic retransmissions are only sent after the
+//! confirmation timeout, which is longer than the window.
+//!
+//! The statistics are exposed via the router RPC, together with
+//! the counters of the routing loop protection of the
+//! routing information.
+
+use libp2p::PeerId;
+use prost::Message;
+use std::collections::{HashMap, VecDeque};
+
+use super::instance::RouterInstance;
+use super::proto;
+use super::Router;
+use crate::rpc::Rpc;
+
+/// Loop Detector
+///
+/// The loop detector is part of the router instance.
+pub struct LoopDetector {
+    /// signatures of the recently seen messages,
+    /// the time they were seen and the neighbour they
+    /// were received from, None for messages sent by this node
+    seen: HashMap<Vec<u8>, (u64, Option<PeerId>)>,
+    /// signatures in the order they were seen
+    seen_order: VecDeque<(u64, Vec<u8>)>,
+    /// number of forwarded messages
+    forwarded: u64,
+    /// number of detected loops
+    loops_detected: u64,
+    /// time of the last detected loop
+    last_loop_at: u64,
+    /// signature of the last looping message
+    last_loop_signature: Vec<u8>,
+    /// number of poisoned routes received
+    routes_poisoned: u64,
+    /// number of routes dropped because of the hop count ceiling
+    routes_hop_count_exceeded: u64,
+}
+
+impl LoopDetector {
+    /// Create a new loop detector
+    pub fn new() -> LoopDetector {
+        LoopDetector {
+            seen: HashMap::new(),
+            seen_order: VecDeque::new(),
+            forwarded: 0,
+            loops_detected: 0,
+            last_loop_at: 0,
+            last_loop_signature: Vec::new(),
+            routes_poisoned: 0,
+            routes_hop_count_exceeded: 0,
+        }
+    }
+
+    /// remember a message this node sends
+    pub fn add_sent(signature: &Vec<u8>) {
+        Router::instance().add_sent(signature);
+    }
+
+    /// check a message received from a neighbour before forwarding it
+    pub fn is_loop(signature: &Vec<u8>, received_from: &PeerId) -> bool {
+        Router::instance().is_loop(signature, received_from)
+    }
+
+    /// count a poisoned route received from a neighbour
+    pub fn count_poisoned_route(&mut self) {
+        self.routes_poisoned += 1;
+    }
+
+    /// count a route that exceeded the maximal hop count
+    pub fn count_hop_count_exceeded(&mut self) {
+        self.routes_hop_count_exceeded += 1;
+    }
+
+    /// remember a signature
+    fn remember(&mut self, signature: &Vec<u8>, received_from: Option<PeerId>, now: u64) {
+        self.seen.insert(signature.to_owned(), (now, received_from));
+        self.seen_order.push_back((now, signature.to_owned()));
+    }
+
+    /// remove all signatures older than the loop time window
+    fn remove_expired(&mut self, now: u64, window: u64) {
+        while let Some((seen_at, _signature)) = self.seen_order.front() {
+            if *seen_at + window > now {
+                break;
+            }
+
+            if let Some((seen_at, signature)) = self.seen_order.pop_front() {
+                // the signature may have been seen again later
+                if let Some((last_seen, _received_from)) = self.seen.get(&signature) {
+                    if *last_seen == seen_at {
+                        self.seen.remove(&signature);
+                    }
+                }
+            }
+        }
+    }
+
+    /// send protobuf RPC loop statistics
+    pub fn rpc_send_loop_statistics() {
+        // create statistics message
+        let statistics;
+        {
+            let router = Router::instance();
+            let loop_detector = router.loops.read().unwrap();
+            statistics = proto::LoopStatistics {
+                forwarded: loop_detector.forwarded,
+                loops_detected: loop_detector.loops_detected,
+                last_loop_at: loop_detector.last_loop_at,
+                last_loop_signature: loop_detector.last_loop_signature.clone(),
+                routes_poisoned: loop_detector.routes_poisoned,
+                routes_hop_count_exceeded: loop_detector.routes_hop_count_exceeded,
+            };
+        }
+
+        // create rpc protobuf message
+        let proto_message = proto::Router {
+            message: Some(proto::router::Message::LoopStatistics(statistics)),
+        };
+
+        // encode message
+        let mut buf = Vec::with_capacity(proto_message.encoded_len());
+        proto_message
+            .encode(&mut buf)
+            .expect("Vec<u8> provides capacity as needed");
+
+        // send message
+        Rpc::send_message(
+            buf,
+            crate::rpc::proto::Modules::Router.into(),
+            "".to_string(),
+            Vec::new(),
+        );
+    }
+}
+
+impl RouterInstance {
+    /// time window of the loop detection in milli seconds
+    fn loop_window(&self) -> u64 {
+        self.configuration.loop_window.saturating_mul(1000)
+    }
+
+    /// remember a message this node sends
+    pub fn add_sent(&self, signature: &Vec<u8>) {
+        let window = self.loop_window();
+        let now = self.now();
+        let mut loop_detector = self.loops.write().unwrap();
+
+        loop_detector.remove_expired(now, window);
+        if window > 0 {
+            loop_detector.remember(signature, None, now);
+        }
+    }
+
+    /// check a message received from a neighbour before forwarding it
+    ///
+    /// Returns true if the message was sent by this node or
+    /// forwarded from another neighbour within the loop window.
+    /// Otherwise the message is remembered and false is returned.
+    pub fn is_loop(&self, signature: &Vec<u8>, received_from: &PeerId) -> bool {
+        let window = self.loop_window();
+        let now = self.now();
+        let mut loop_detector = self.loops.write().unwrap();
+
+        loop_detector.remove_expired(now, window);
+
+        if let Some((_seen_at, previous_hop)) = loop_detector.seen.get(signature) {
+            if *previous_hop != Some(*received_from) {
+                loop_detector.loops_detected += 1;
+                loop_detector.last_loop_at = now;
+                loop_detector.last_loop_signature = signature.to_owned();
+                return true;
+            }
+        }
+
+        loop_detector.forwarded += 1;
+        if window > 0 {
+            loop_detector.remember(signature, Some(*received_from), now);
+        }
+
+        false
+    }
+}
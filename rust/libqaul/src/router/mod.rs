@@ -8,22 +8,28 @@
 
 use prost::Message;
 use state::Storage;
-use std::sync::RwLock;
 
 pub mod connections;
 pub mod feed_requester;
 pub mod flooder;
 pub mod info;
+pub mod instance;
+pub mod link_metric;
+pub mod loops;
 pub mod neighbours;
+#[cfg(feature = "simulator")]
+pub mod simulator;
 pub mod table;
 pub mod user_requester;
 pub mod users;
 
-use crate::storage::configuration::{Configuration, RoutingOptions};
+use crate::node;
+use crate::storage::configuration::Configuration;
 use connections::ConnectionTable;
 use feed_requester::{FeedRequester, FeedResponser};
 use flooder::Flooder;
-use info::RouterInfo;
+use instance::{Clock, RouterHandle, RouterInstance};
+use loops::LoopDetector;
 use neighbours::Neighbours;
 use table::RoutingTable;
 use user_requester::{UserRequester, UserResponser};
@@ -38,27 +44,25 @@ pub mod router_net_proto {
     include!("qaul.net.router_net_info.rs");
 }
 
-/// mutable state of router,
-/// used for storing the router configuration
-static ROUTER: Storage<RwLock<Router>> = Storage::new();
+/// router instance of this node,
+/// containing the router configuration and all routing tables
+static ROUTER: Storage<RouterHandle> = Storage::new();
 
 /// qaul community router access
 #[derive(Clone)]
-pub struct Router {
-    pub configuration: RoutingOptions,
-}
+pub struct Router {}
 
 impl Router {
     /// Initialize the qaul router
     pub fn init() {
         let config = Configuration::get();
-        let router = Router {
-            configuration: config.routing.clone(),
-        };
-        // set configuration to state
-        ROUTER.set(RwLock::new(router));
 
-        // initialize direct neighbours table
+        // create the router instance and set it to state
+        let router =
+            RouterInstance::new(node::Node::get_id(), config.routing.clone(), Clock::System);
+        ROUTER.set(router);
+
+        // initialize direct neighbours data base
         Neighbours::init();
 
         // initialize users table
@@ -79,23 +83,14 @@ impl Router {
         // initialize user_response queue
         UserResponser::init();
 
-        // initialize the global routing table
-        RoutingTable::init();
-
-        // initialize the routing information collection
-        // tables per connection module
+        // add the local users to the
+        // routing information collection tables
         ConnectionTable::init();
-
-        // initialize RouterInfo submodule that
-        // schedules the sending of the routing information
-        // to the neighbouring nodes.
-        RouterInfo::init(config.routing.sending_table_period);
     }
 
-    /// Get router configuration from state
-    pub fn get_configuration() -> RoutingOptions {
-        let router = ROUTER.get().read().unwrap();
-        router.configuration.clone()
+    /// Get the router instance of this node
+    pub fn instance() -> RouterHandle {
+        ROUTER.get().clone()
     }
 
     /// Process incoming RPC request messages and send them to
@@ -116,6 +111,10 @@ impl Router {
                         // send neighbours list
                         Neighbours::rpc_send_neighbours_list();
                     }
+                    Some(proto::router::Message::LoopStatisticsRequest(_request)) => {
+                        // send loop detection statistics
+                        LoopDetector::rpc_send_loop_statistics();
+                    }
                     _ => {}
                 }
             }
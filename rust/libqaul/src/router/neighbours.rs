@@ -12,19 +12,14 @@ use sled_extensions::{bincode::Tree, DbExt};
 use state::Storage;
 use std::{collections::HashMap, sync::RwLock};
 
-use super::info::RouterInfo;
+use super::instance::RouterInstance;
+use super::link_metric::LinkStatistics;
 use super::proto;
+use super::Router;
 use crate::connections::ConnectionModule;
 use crate::rpc::Rpc;
 use crate::storage::database::DataBase;
-use crate::utilities::{qaul_id::QaulId, timestamp::Timestamp};
-
-/// mutable state of Internet neighbour node table
-static INTERNET: Storage<RwLock<Neighbours>> = Storage::new();
-/// mutable state of LAN neighbour node table
-static LAN: Storage<RwLock<Neighbours>> = Storage::new();
-/// mutable state of BLE neighbour node table
-static BLE: Storage<RwLock<Neighbours>> = Storage::new();
+use crate::utilities::qaul_id::QaulId;
 
 /// Data base table of all ever discovered neighbour nodes
 ///
@@ -53,31 +48,25 @@ pub struct Neighbours {
 pub struct Neighbour {
     /// round trip time in micro seconds
     rtt: u32,
+    /// delivery statistics of the link
+    statistics: LinkStatistics,
     /// when was this node last seen
     updated_at: u64,
 }
 
 impl Neighbours {
-    /// Initialize neighbours module
-    pub fn init() {
-        // neighbours table for internet connection module
-        let internet = Neighbours {
-            nodes: HashMap::new(),
-        };
-        INTERNET.set(RwLock::new(internet));
-
-        // neighbours table for lan connection module
-        let lan = Neighbours {
-            nodes: HashMap::new(),
-        };
-        LAN.set(RwLock::new(lan));
-
-        // neighbours table for ble connection module
-        let ble = Neighbours {
+    /// Create an empty neighbours table
+    pub fn new() -> Neighbours {
+        Neighbours {
             nodes: HashMap::new(),
-        };
-        BLE.set(RwLock::new(ble));
+        }
+    }
 
+    /// Initialize neighbours module
+    ///
+    /// The neighbours tables per connection module
+    /// are part of the router instance.
+    pub fn init() {
         // get nodes tree from data base and set it to state
         let db = DataBase::get_node_db();
         let tree = db.open_bincode_tree("nodes").unwrap();
@@ -89,35 +78,8 @@ impl Neighbours {
     /// If the node already exists, it updates it's rtt value.
     /// If the node does not yet exist, it creates it.
     pub fn update_node(module: ConnectionModule, node_id: PeerId, rtt: u32) {
-        log::trace!("update_node node {:?}", node_id);
-        // get table
-        let mut neighbours;
-        match module {
-            ConnectionModule::Lan => neighbours = LAN.get().write().unwrap(),
-            ConnectionModule::Internet => neighbours = INTERNET.get().write().unwrap(),
-            ConnectionModule::Ble => neighbours = BLE.get().write().unwrap(),
-            ConnectionModule::Local => return,
-            ConnectionModule::None => return,
-        }
-
-        // get node from table
-        let node_option = neighbours.nodes.get_mut(&node_id);
-        if let Some(node) = node_option {
-            node.rtt = Self::calculate_rtt(node.rtt, rtt);
-            node.updated_at = Timestamp::get_timestamp();
-        } else {
-            log::trace!("add node {:?} to neighbours table", node_id);
-            neighbours.nodes.insert(
-                node_id,
-                Neighbour {
-                    rtt,
-                    updated_at: Timestamp::get_timestamp(),
-                },
-            );
-
-            // add neighbour in RouterInfo neighbours table
-            RouterInfo::add_neighbour(node_id);
-
+        let router = Router::instance();
+        if router.update_neighbour(module, node_id, rtt) {
             // add node to nodes table
             {
                 let tree = NODES.get();
@@ -126,7 +88,7 @@ impl Neighbours {
                 // create data base entry
                 let node = Node {
                     id: id.clone(),
-                    connected_at: Timestamp::get_timestamp(),
+                    connected_at: router.now(),
                 };
 
                 // save user
@@ -141,20 +103,18 @@ impl Neighbours {
         }
     }
 
+    /// update the delivery statistics of a neighbour
+    ///
+    /// This function is called with the result of each ping
+    /// and each confirmed or retransmitted message that
+    /// was sent via this neighbour.
+    pub fn update_statistics(module: ConnectionModule, node_id: PeerId, success: bool) {
+        Router::instance().update_neighbour_statistics(module, node_id, success);
+    }
+
     /// Delete Neighbour
     pub fn delete(module: ConnectionModule, node_id: PeerId) {
-        // get table
-        let mut neighbours;
-        match module {
-            ConnectionModule::Lan => neighbours = LAN.get().write().unwrap(),
-            ConnectionModule::Internet => neighbours = INTERNET.get().write().unwrap(),
-            ConnectionModule::Ble => neighbours = BLE.get().write().unwrap(),
-            ConnectionModule::Local => return,
-            ConnectionModule::None => return,
-        }
-
-        // delete entry
-        neighbours.nodes.remove(&node_id);
+        Router::instance().delete_neighbour(module, node_id);
     }
 
     /// Calculate average rtt
@@ -166,55 +126,10 @@ impl Neighbours {
         new_rtt
     }
 
-    /// get rtt for a neighbour
-    /// returns the round trip time for the neighbour in the
-    /// connection module.
-    /// If the neighbour does not exist, it returns None.
-    pub fn get_rtt(neighbour_id: &PeerId, module: &ConnectionModule) -> Option<u32> {
-        // get table
-        let neighbours;
-        match module {
-            ConnectionModule::Lan => neighbours = LAN.get().read().unwrap(),
-            ConnectionModule::Internet => neighbours = INTERNET.get().read().unwrap(),
-            ConnectionModule::Ble => neighbours = BLE.get().read().unwrap(),
-            ConnectionModule::Local => return Some(0),
-            ConnectionModule::None => return None,
-        }
-
-        // search for neighbour
-        if let Some(neighbour) = neighbours.nodes.get(neighbour_id) {
-            return Some(neighbour.rtt);
-        } else {
-            return None;
-        }
-    }
-
     /// Is this node ID a neighbour in any module?
     /// returns the first found module or `None`
     pub fn is_neighbour(node_id: &PeerId) -> ConnectionModule {
-        // check if neighbour is in Lan table
-        {
-            let lan = LAN.get().read().unwrap();
-            if lan.nodes.contains_key(node_id) {
-                return ConnectionModule::Lan;
-            }
-        }
-        // check if neighbour exists in Internet table
-        {
-            let internet = INTERNET.get().read().unwrap();
-            if internet.nodes.contains_key(node_id) {
-                return ConnectionModule::Internet;
-            }
-        }
-        // check if neighbour exists in BLE table
-        {
-            let ble = BLE.get().read().unwrap();
-            if ble.nodes.contains_key(node_id) {
-                return ConnectionModule::Ble;
-            }
-        }
-
-        ConnectionModule::None
+        Router::instance().is_neighbour(node_id)
     }
 
     /// Search for a neighbour by it's small qaul ID
@@ -267,12 +182,13 @@ impl Neighbours {
         let mut nodes: Vec<PeerId> = Vec::new();
 
         // get state
-        let ble = BLE.get().read().unwrap();
+        let router = Router::instance();
+        let ble = router.ble_neighbours.read().unwrap();
 
         // check if we have nodes listed
         if ble.nodes.len() > 0 {
-            let lan = LAN.get().read().unwrap();
-            let internet = INTERNET.get().read().unwrap();
+            let lan = router.lan_neighbours.read().unwrap();
+            let internet = router.internet_neighbours.read().unwrap();
 
             // search for all nodes that are only reachable via BLE
             for (id, _val) in ble.nodes.iter() {
@@ -302,9 +218,12 @@ impl Neighbours {
         let mut internet_neighbours: Vec<proto::NeighboursEntry> = Vec::new();
         let mut ble_neighbours: Vec<proto::NeighboursEntry> = Vec::new();
 
+        // get router instance
+        let router = Router::instance();
+
         // fill lan connection module neighbours
         {
-            let lan = LAN.get().read().unwrap();
+            let lan = router.lan_neighbours.read().unwrap();
 
             for (id, value) in &lan.nodes {
                 lan_neighbours.push(proto::NeighboursEntry {
@@ -316,7 +235,7 @@ impl Neighbours {
 
         // fill internet connection module neighbours
         {
-            let internet = INTERNET.get().write().unwrap();
+            let internet = router.internet_neighbours.read().unwrap();
 
             for (id, value) in &internet.nodes {
                 internet_neighbours.push(proto::NeighboursEntry {
@@ -328,7 +247,7 @@ impl Neighbours {
 
         // fill ble connection module neighbours
         {
-            let ble = BLE.get().write().unwrap();
+            let ble = router.ble_neighbours.read().unwrap();
 
             for (id, value) in &ble.nodes {
                 ble_neighbours.push(proto::NeighboursEntry {
@@ -364,3 +283,149 @@ impl Neighbours {
         );
     }
 }
+
+/// Neighbour tables of a router instance
+impl RouterInstance {
+    /// get the neighbours table of a connection module
+    fn neighbours_table(&self, module: &ConnectionModule) -> Option<&RwLock<Neighbours>> {
+        match module {
+            ConnectionModule::Lan => Some(&self.lan_neighbours),
+            ConnectionModule::Internet => Some(&self.internet_neighbours),
+            ConnectionModule::Ble => Some(&self.ble_neighbours),
+            ConnectionModule::Local => None,
+            ConnectionModule::None => None,
+        }
+    }
+
+    /// update the neighbours table of a connection module
+    /// with a new round trip time
+    ///
+    /// If the node was not yet a neighbour in this connection
+    /// module, it is added to the routing information scheduler
+    /// and true is returned.
+    pub fn update_neighbour(&self, module: ConnectionModule, node_id: PeerId, rtt: u32) -> bool {
+        log::trace!("update_node node {:?}", node_id);
+        // get table
+        let mut neighbours;
+        match self.neighbours_table(&module) {
+            Some(table) => neighbours = table.write().unwrap(),
+            None => return false,
+        }
+
+        // get node from table
+        let node_option = neighbours.nodes.get_mut(&node_id);
+        if let Some(node) = node_option {
+            node.rtt = Neighbours::calculate_rtt(node.rtt, rtt);
+            node.statistics.update(true);
+            node.updated_at = self.now();
+            false
+        } else {
+            log::trace!("add node {:?} to neighbours table", node_id);
+            neighbours.nodes.insert(
+                node_id,
+                Neighbour {
+                    rtt,
+                    statistics: LinkStatistics::new(),
+                    updated_at: self.now(),
+                },
+            );
+            drop(neighbours);
+
+            // add neighbour in RouterInfo neighbours table
+            self.add_info_neighbour(node_id);
+            true
+        }
+    }
+
+    /// update the delivery statistics of a neighbour
+    pub fn update_neighbour_statistics(
+        &self,
+        module: ConnectionModule,
+        node_id: PeerId,
+        success: bool,
+    ) {
+        if let Some(table) = self.neighbours_table(&module) {
+            let mut neighbours = table.write().unwrap();
+            if let Some(node) = neighbours.nodes.get_mut(&node_id) {
+                node.statistics.update(success);
+            }
+        }
+    }
+
+    /// delete a neighbour from the table of a connection module
+    pub fn delete_neighbour(&self, module: ConnectionModule, node_id: PeerId) {
+        if let Some(table) = self.neighbours_table(&module) {
+            let mut neighbours = table.write().unwrap();
+            neighbours.nodes.remove(&node_id);
+        }
+    }
+
+    /// get the round trip time of a neighbour
+    /// in a connection module
+    pub fn get_neighbour_rtt(
+        &self,
+        neighbour_id: &PeerId,
+        module: &ConnectionModule,
+    ) -> Option<u32> {
+        if *module == ConnectionModule::Local {
+            return Some(0);
+        }
+
+        // get table
+        let neighbours;
+        match self.neighbours_table(module) {
+            Some(table) => neighbours = table.read().unwrap(),
+            None => return None,
+        }
+
+        // search for neighbour
+        match neighbours.nodes.get(neighbour_id) {
+            Some(neighbour) => Some(neighbour.rtt),
+            None => None,
+        }
+    }
+
+    /// get the expected transmission count of a neighbour
+    /// in a connection module
+    pub fn get_neighbour_etx(
+        &self,
+        neighbour_id: &PeerId,
+        module: &ConnectionModule,
+    ) -> Option<u32> {
+        if *module == ConnectionModule::Local {
+            return Some(0);
+        }
+
+        // get table
+        let neighbours;
+        match self.neighbours_table(module) {
+            Some(table) => neighbours = table.read().unwrap(),
+            None => return None,
+        }
+
+        // search for neighbour
+        match neighbours.nodes.get(neighbour_id) {
+            Some(neighbour) => Some(neighbour.statistics.etx()),
+            None => None,
+        }
+    }
+
+    /// Is this node ID a neighbour in any module?
+    /// returns the first found module or `None`
+    pub fn is_neighbour(&self, node_id: &PeerId) -> ConnectionModule {
+        for module in [
+            ConnectionModule::Lan,
+            ConnectionModule::Internet,
+            ConnectionModule::Ble,
+        ] {
+            if let Some(table) = self.neighbours_table(&module) {
+                let neighbours = table.read().unwrap();
+                if neighbours.nodes.contains_key(node_id) {
+                    return module;
+                }
+            }
+        }
+
+        ConnectionModule::None
+    }
+}
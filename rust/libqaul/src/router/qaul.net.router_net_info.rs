@@ -31,6 +31,9 @@ pub struct RouterInfoMessage {
     #[prost(bytes="vec", tag="1")]
     pub node: ::prost::alloc::vec::Vec<u8>,
     /// Routing information table
+    ///
+    /// In a snapshot, it contains all routes.
+    /// In a delta update, it contains the new routes.
     #[prost(message, optional, tag="2")]
     pub routes: ::core::option::Option<RoutingInfoTable>,
     /// Latest Feed ids table
@@ -39,6 +42,26 @@ pub struct RouterInfoMessage {
     /// timestamp
     #[prost(uint64, tag="5")]
     pub timestamp: u64,
+    /// version of the routing information exchange
+    ///
+    /// 0: full routing information in every message
+    /// 1: sequence numbered snapshots and delta updates
+    #[prost(uint32, tag="6")]
+    pub version: u32,
+    /// sequence number of the routing information
+    /// sent to this neighbour
+    #[prost(uint32, tag="7")]
+    pub sequence: u32,
+    /// this message is a full snapshot of all routes
+    #[prost(bool, tag="8")]
+    pub snapshot: bool,
+    /// delta update to the routing information
+    /// with the previous sequence number
+    #[prost(message, optional, tag="9")]
+    pub delta: ::core::option::Option<RoutingInfoDelta>,
+    /// request a full snapshot from the receiving neighbour
+    #[prost(bool, tag="10")]
+    pub resync: bool,
 }
 /// Routing information to send to neighbours
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -46,6 +69,41 @@ pub struct RoutingInfoTable {
     #[prost(message, repeated, tag="1")]
     pub entry: ::prost::alloc::vec::Vec<RoutingInfoEntry>,
 }
+/// Delta update of the routing information
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RoutingInfoDelta {
+    /// changed routes
+    #[prost(message, repeated, tag="1")]
+    pub entry: ::prost::alloc::vec::Vec<RoutingInfoDeltaEntry>,
+    /// withdrawn routes
+    /// q8ids of the users that are not reachable anymore
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub withdrawn: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// Delta encoded routing entry
+///
+/// Fields with the value 0 or empty fields did not change.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RoutingInfoDeltaEntry {
+    /// user id
+    #[prost(bytes="vec", tag="1")]
+    pub user: ::prost::alloc::vec::Vec<u8>,
+    /// new round trip time
+    #[prost(uint32, tag="2")]
+    pub rtt: u32,
+    /// new hop count
+    #[prost(bytes="vec", tag="3")]
+    pub hc: ::prost::alloc::vec::Vec<u8>,
+    /// increase of the propagation id
+    #[prost(uint32, tag="4")]
+    pub pgid_increment: u32,
+    /// new expected transmission count of the route
+    #[prost(uint32, tag="5")]
+    pub etx: u32,
+    /// new bandwidth of the route
+    #[prost(uint32, tag="6")]
+    pub bw: u32,
+}
 /// Routing structures to send over the network
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RoutingInfoEntry {
@@ -61,6 +119,14 @@ pub struct RoutingInfoEntry {
     /// propagation id
     #[prost(uint32, tag="5")]
     pub pgid: u32,
+    /// expected transmission count of the route
+    /// fixed point number, 1000 is one transmission
+    #[prost(uint32, tag="6")]
+    pub etx: u32,
+    /// bandwidth of the slowest link of the route in kbit/s
+    /// 0 if it is unknown
+    #[prost(uint32, tag="7")]
+    pub bw: u32,
 }
 /// User information table
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -0,0 +1,562 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Router Simulator
+//!
+//! Deterministic simulation of many qaul routers in one process.
+//!
+//! Every simulated node has it's own router instance with a
+//! virtual clock and one user.
+//! The nodes are connected via virtual links of a connection
+//! module, with a configurable latency and loss rate.
+//! Only LAN and Internet links carry routing information.
+//!
+//! The simulator pings the neighbours, exchanges the routing
+//! information and creates the routing tables in the same
+//! intervals as libqaul does, but on a virtual time line.
+//! No network is used and simulations with the same seed
+//! produce the same results.
+//!
+//! The simulator covers the router instances: the neighbour
+//! tables, the connection tables, the routing tables and the
+//! routing information scheduler.
+//! The routing information is scheduled, encoded, signed and
+//! processed the same way as in libqaul, including the delta
+//! updates, the withdrawn routes and the snapshot requests.
+//!
+//! The following is not simulated:
+//!
+//! * the forwarding of messages and the loop detector
+//! * the exchange of the feed messages and the user information
+//!
+//! The users table, the flooder, the nodes data base and the
+//! feed and user requesters are global to the libqaul process
+//! and not part of the router instances.
+//!
+//! The simulator is used to test the convergence of the
+//! routing tables, the healing of network partitions and
+//! route flapping.
+//!
+//! It is only compiled with the cargo feature `simulator`.
+
+use libp2p::{identity, PeerId};
+use prost::Message;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::AtomicU64;
+
+use super::info::RouterInfo;
+use super::instance::{Clock, RouterHandle, RouterInstance};
+use super::router_net_proto;
+use super::table::RoutingConnectionEntry;
+pub use crate::connections::ConnectionModule;
+pub use crate::storage::configuration::RoutingOptions;
+
+/// virtual time in milli seconds at which every simulation starts
+const SIMULATION_START: u64 = 1_000_000_000;
+
+/// interval in milli seconds in which the routing tables are created
+const ROUTING_TABLE_PERIOD: u64 = 1000;
+
+/// interval in milli seconds in which the routing
+/// information scheduler is checked
+const ROUTING_INFO_PERIOD: u64 = 100;
+
+/// time in milli seconds after which an unanswered ping fails
+const PING_TIMEOUT: u64 = 20000;
+
+/// Virtual link between two simulated nodes
+#[derive(Clone, Copy, Debug)]
+pub struct VirtualLink {
+    /// connection module of the link
+    pub module: ConnectionModule,
+    /// one way latency in milli seconds
+    pub latency: u64,
+    /// loss rate of each transmission in per mille
+    pub loss: u32,
+}
+
+impl VirtualLink {
+    /// create a new virtual link
+    pub fn new(module: ConnectionModule, latency: u64, loss: u32) -> VirtualLink {
+        VirtualLink {
+            module,
+            latency,
+            loss,
+        }
+    }
+}
+
+/// state of a virtual link
+struct LinkState {
+    /// link configuration
+    link: VirtualLink,
+    /// is the link connected
+    up: bool,
+}
+
+/// Simulated node
+pub struct SimulatedNode {
+    /// node id
+    pub node_id: PeerId,
+    /// id of the user on this node
+    pub user_id: PeerId,
+    /// router instance of the node
+    pub router: RouterHandle,
+    /// keys of the node to sign the routing information
+    keys: identity::Keypair,
+}
+
+/// Statistics of a simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SimulationStatistics {
+    /// number of messages sent over virtual links
+    pub messages_sent: u64,
+    /// number of messages lost on virtual links
+    pub messages_lost: u64,
+    /// number of times a node changed the next hop
+    /// of the best route to a user
+    pub route_changes: u64,
+}
+
+/// simulation events
+enum Event {
+    /// ping all neighbours of a node
+    Ping(usize),
+    /// result of a ping, the round trip time in
+    /// micro seconds or None if the ping failed
+    PingResult {
+        node: usize,
+        neighbour: usize,
+        rtt: Option<u32>,
+    },
+    /// check the routing information scheduler of a node
+    CheckInfoScheduler(usize),
+    /// encoded routing information arrives at a node
+    RoutingInfo {
+        from: usize,
+        to: usize,
+        data: Vec<u8>,
+    },
+    /// create the routing table of a node
+    CreateRoutingTable(usize),
+}
+
+/// Router Simulator
+pub struct Simulator {
+    /// current virtual time in milli seconds
+    now: u64,
+    /// routing configuration of all nodes
+    configuration: RoutingOptions,
+    /// simulated nodes
+    nodes: Vec<SimulatedNode>,
+    /// virtual links, the key is the ordered pair of node indexes
+    links: BTreeMap<(usize, usize), LinkState>,
+    /// scheduled events ordered by time and sequence number
+    events: BTreeMap<(u64, u64), Event>,
+    /// sequence number of the next scheduled event
+    sequence: u64,
+    /// seeded random number generator
+    random: StdRng,
+    /// next hop of the best route per node and user
+    next_hops: BTreeMap<(usize, usize), Option<PeerId>>,
+    /// simulation statistics
+    statistics: SimulationStatistics,
+}
+
+impl Simulator {
+    /// create a new simulator
+    ///
+    /// All nodes use the routing configuration.
+    /// The seed decides about the node ids, the loss of
+    /// messages and the timing of the nodes.
+    pub fn new(seed: u64, configuration: RoutingOptions) -> Simulator {
+        Simulator {
+            now: SIMULATION_START,
+            configuration,
+            nodes: Vec::new(),
+            links: BTreeMap::new(),
+            events: BTreeMap::new(),
+            sequence: 0,
+            random: StdRng::seed_from_u64(seed),
+            next_hops: BTreeMap::new(),
+            statistics: SimulationStatistics::default(),
+        }
+    }
+
+    /// add a new node to the simulation
+    ///
+    /// Returns the index of the node.
+    pub fn add_node(&mut self) -> usize {
+        let keys = self.create_keys();
+        let node_id = keys.public().to_peer_id();
+        let user_id = self.create_keys().public().to_peer_id();
+        let router = RouterInstance::new(
+            node_id,
+            self.configuration.clone(),
+            Clock::Virtual(AtomicU64::new(self.now)),
+        );
+        router.add_local_user(user_id);
+
+        let index = self.nodes.len();
+        self.nodes.push(SimulatedNode {
+            node_id,
+            user_id,
+            router,
+            keys,
+        });
+
+        // start the timers of the node at a random offset
+        let ping_period = self.configuration.ping_neighbour_period * 1000;
+        let offset = self.random.gen_range(0..ping_period.max(1));
+        self.schedule(offset, Event::Ping(index));
+        let offset = self.random.gen_range(0..ROUTING_INFO_PERIOD);
+        self.schedule(offset, Event::CheckInfoScheduler(index));
+        let offset = self.random.gen_range(0..ROUTING_TABLE_PERIOD);
+        self.schedule(offset, Event::CreateRoutingTable(index));
+
+        index
+    }
+
+    /// create deterministic keys of a qaul id
+    fn create_keys(&mut self) -> identity::Keypair {
+        let mut bytes: [u8; 32] = self.random.gen();
+        let secret = identity::ed25519::SecretKey::from_bytes(&mut bytes)
+            .expect("32 bytes are a valid ed25519 secret key");
+        identity::Keypair::Ed25519(identity::ed25519::Keypair::from(secret))
+    }
+
+    /// connect two nodes with a virtual link
+    ///
+    /// An existing link between the nodes is replaced.
+    pub fn connect(&mut self, a: usize, b: usize, link: VirtualLink) {
+        self.set_link_up(a, b, false);
+        self.links
+            .insert(Self::link_key(a, b), LinkState { link, up: true });
+    }
+
+    /// connect or disconnect the virtual link between two nodes
+    ///
+    /// When a link is disconnected, both nodes remove each
+    /// other from their neighbours table.
+    pub fn set_link_up(&mut self, a: usize, b: usize, up: bool) {
+        let module;
+        match self.links.get_mut(&Self::link_key(a, b)) {
+            Some(state) => {
+                state.up = up;
+                module = state.link.module;
+            }
+            None => return,
+        }
+
+        if !up {
+            let node_a = self.nodes[a].node_id;
+            let node_b = self.nodes[b].node_id;
+            self.nodes[a].router.delete_neighbour(module, node_b);
+            self.nodes[b].router.delete_neighbour(module, node_a);
+        }
+    }
+
+    /// run the simulation for the duration in milli seconds
+    pub fn run(&mut self, duration: u64) {
+        let end = self.now + duration;
+
+        loop {
+            let key;
+            match self.events.keys().next() {
+                Some(next) if next.0 <= end => key = *next,
+                _ => break,
+            }
+
+            if let Some(event) = self.events.remove(&key) {
+                self.now = key.0;
+                self.process(event);
+            }
+        }
+
+        self.now = end;
+    }
+
+    /// get the elapsed virtual time in milli seconds
+    pub fn elapsed(&self) -> u64 {
+        self.now - SIMULATION_START
+    }
+
+    /// get a simulated node
+    pub fn node(&self, index: usize) -> &SimulatedNode {
+        &self.nodes[index]
+    }
+
+    /// get the number of simulated nodes
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// get the simulation statistics
+    pub fn statistics(&self) -> SimulationStatistics {
+        self.statistics
+    }
+
+    /// get the best route of a node to the user of another node
+    pub fn route(&self, from: usize, to: usize) -> Option<RoutingConnectionEntry> {
+        self.nodes[from]
+            .router
+            .get_routes_to_user(self.nodes[to].user_id)
+            .into_iter()
+            .next()
+    }
+
+    /// get the index of the node via which a node routes
+    /// to the user of another node
+    pub fn next_hop(&self, from: usize, to: usize) -> Option<usize> {
+        match self.route(from, to) {
+            Some(route) => self
+                .nodes
+                .iter()
+                .position(|node| node.node_id == route.node),
+            None => None,
+        }
+    }
+
+    /// get the hop count of the best route of a node
+    /// to the user of another node
+    pub fn hop_count(&self, from: usize, to: usize) -> Option<u8> {
+        match self.route(from, to) {
+            Some(route) => Some(route.hc),
+            None => None,
+        }
+    }
+
+    /// get the hop count of the shortest path between two
+    /// nodes over the connected links
+    pub fn shortest_hop_count(&self, from: usize, to: usize) -> Option<u8> {
+        let mut hops: Vec<Option<u8>> = vec![None; self.nodes.len()];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        hops[from] = Some(0);
+        queue.push_back(from);
+
+        while let Some(node) = queue.pop_front() {
+            let hc = hops[node].unwrap_or(0);
+            for (neighbour, _link) in self.connected_links(node) {
+                if hops[neighbour].is_none() {
+                    hops[neighbour] = Some(hc.saturating_add(1));
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        hops[to]
+    }
+
+    /// is the user of a node reachable from another node
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        self.route(from, to).is_some()
+    }
+
+    /// have all nodes a route to the users of all nodes
+    /// they are connected to
+    ///
+    /// The routes must have the hop count of the shortest
+    /// path and users without a path must be unreachable.
+    /// This presumes link latencies well below the hop
+    /// count penalty of the configuration.
+    pub fn is_converged(&self) -> bool {
+        for from in 0..self.nodes.len() {
+            for to in 0..self.nodes.len() {
+                if self.hop_count(from, to) != self.shortest_hop_count(from, to) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// get the connected links of a node
+    ///
+    /// Returns the index of the neighbour and the link.
+    fn connected_links(&self, node: usize) -> Vec<(usize, VirtualLink)> {
+        let mut links = Vec::new();
+        for ((a, b), state) in &self.links {
+            if !state.up {
+                continue;
+            }
+            if *a == node {
+                links.push((*b, state.link));
+            } else if *b == node {
+                links.push((*a, state.link));
+            }
+        }
+        links
+    }
+
+    /// get the connected link between two nodes
+    fn connected_link(&self, a: usize, b: usize) -> Option<VirtualLink> {
+        match self.links.get(&Self::link_key(a, b)) {
+            Some(state) if state.up => Some(state.link),
+            _ => None,
+        }
+    }
+
+    /// create the key of a link
+    fn link_key(a: usize, b: usize) -> (usize, usize) {
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// schedule an event after the delay in milli seconds
+    fn schedule(&mut self, delay: u64, event: Event) {
+        self.events.insert((self.now + delay, self.sequence), event);
+        self.sequence += 1;
+    }
+
+    /// send a message over a link
+    ///
+    /// Returns true if the message arrives.
+    fn transmit(&mut self, link: &VirtualLink) -> bool {
+        self.statistics.messages_sent += 1;
+        if self.random.gen_range(0..1000) < link.loss {
+            self.statistics.messages_lost += 1;
+            return false;
+        }
+        true
+    }
+
+    /// process an event
+    fn process(&mut self, event: Event) {
+        match event {
+            Event::Ping(node) => {
+                self.nodes[node].router.set_time(self.now);
+
+                for (neighbour, link) in self.connected_links(node) {
+                    // a ping succeeds if the request and
+                    // the response arrive
+                    if self.transmit(&link) && self.transmit(&link) {
+                        let rtt = (link.latency * 2 * 1000).min(u32::MAX as u64) as u32;
+                        let result = Event::PingResult {
+                            node,
+                            neighbour,
+                            rtt: Some(rtt),
+                        };
+                        self.schedule(link.latency * 2, result);
+                    } else {
+                        let result = Event::PingResult {
+                            node,
+                            neighbour,
+                            rtt: None,
+                        };
+                        self.schedule(PING_TIMEOUT, result);
+                    }
+                }
+
+                let period = self.configuration.ping_neighbour_period * 1000;
+                self.schedule(period, Event::Ping(node));
+            }
+            Event::PingResult {
+                node,
+                neighbour,
+                rtt,
+            } => {
+                let link;
+                match self.connected_link(node, neighbour) {
+                    Some(v) => link = v,
+                    None => return,
+                }
+
+                let router = &self.nodes[node].router;
+                let neighbour_id = self.nodes[neighbour].node_id;
+                router.set_time(self.now);
+                match rtt {
+                    Some(rtt) => {
+                        router.update_neighbour(link.module, neighbour_id, rtt);
+                    }
+                    None => {
+                        router.update_neighbour_statistics(link.module, neighbour_id, false);
+                    }
+                }
+            }
+            Event::CheckInfoScheduler(node) => {
+                let router = &self.nodes[node].router;
+                router.set_time(self.now);
+
+                // send the routing information to the neighbour that is due
+                if let Some((neighbour_id, _module, _is_first, router_info)) =
+                    router.check_info_scheduler()
+                {
+                    let simulated_node = &self.nodes[node];
+                    let data = RouterInfo::create(
+                        router_info,
+                        &simulated_node.node_id,
+                        &simulated_node.keys,
+                    );
+
+                    let neighbour = self
+                        .nodes
+                        .iter()
+                        .position(|simulated| simulated.node_id == neighbour_id);
+                    if let Some(neighbour) = neighbour {
+                        if let Some(link) = self.connected_link(node, neighbour) {
+                            if self.transmit(&link) {
+                                let message = Event::RoutingInfo {
+                                    from: node,
+                                    to: neighbour,
+                                    data,
+                                };
+                                self.schedule(link.latency, message);
+                            }
+                        }
+                    }
+                }
+
+                self.schedule(ROUTING_INFO_PERIOD, Event::CheckInfoScheduler(node));
+            }
+            Event::RoutingInfo { from, to, data } => {
+                if self.connected_link(from, to).is_none() {
+                    return;
+                }
+
+                let content;
+                match RouterInfo::decode(&data) {
+                    Some(decoded) => content = decoded,
+                    None => return,
+                }
+
+                match router_net_proto::RouterInfoMessage::decode(&content.content[..]) {
+                    Ok(message) => {
+                        let router = &self.nodes[to].router;
+                        router.set_time(self.now);
+                        router.process_routing_info(&self.nodes[from].node_id, message);
+                    }
+                    Err(msg) => {
+                        log::error!("RouterInfoMessage decode {:?}", msg);
+                    }
+                }
+            }
+            Event::CreateRoutingTable(node) => {
+                self.nodes[node].router.set_time(self.now);
+                self.nodes[node].router.create_routing_table();
+                self.count_route_changes(node);
+
+                self.schedule(ROUTING_TABLE_PERIOD, Event::CreateRoutingTable(node));
+            }
+        }
+    }
+
+    /// count the changed next hops of the best routes of a node
+    fn count_route_changes(&mut self, node: usize) {
+        for user in 0..self.nodes.len() {
+            if user == node {
+                continue;
+            }
+
+            let next_hop = self.route(node, user).map(|route| route.node);
+            let previous = self.next_hops.insert((node, user), next_hop);
+            if previous.unwrap_or(None) != next_hop {
+                self.statistics.route_changes += 1;
+            }
+        }
+    }
+}
@@ -7,23 +7,26 @@
 //!
 //! * contains all currently reachable users.
 //! * There is an entry for each user over which connection modules
-//!   it can be reached. Each connection module only contains
-//!   information of the best node.
+//!   it can be reached. Each connection module contains
+//!   information of the best nodes, ranked by link quality.
 
 use libp2p::PeerId;
 use prost::Message;
-use state::Storage;
 use std::collections::{BTreeMap, HashMap};
-use std::sync::RwLock;
 
+use super::instance::RouterInstance;
 use super::proto;
+use super::Router;
 use crate::connections::ConnectionModule;
 use crate::router::router_net_proto;
 use crate::rpc::Rpc;
 use crate::utilities::qaul_id::QaulId;
 
-/// mutable state of table
-static ROUTINGTABLE: Storage<RwLock<RoutingTable>> = Storage::new();
+/// hop count of an unreachable route
+///
+/// Routes are advertised with this hop count to the neighbour
+/// via which they are routed (split horizon with poisoned reverse).
+pub const HOP_COUNT_UNREACHABLE: u8 = 255;
 
 /// table entry per user
 #[derive(Debug, Clone)]
@@ -38,7 +41,10 @@ pub struct RoutingUserEntry {
     pub pgid_update_hc: u8,
     //online time
     pub online_time: u64,
-    /// best routing entry per connection module
+    /// routing entries per connection module
+    ///
+    /// The entries of a connection module are ordered by
+    /// their link quality, the best entry comes first.
     pub connections: Vec<RoutingConnectionEntry>,
 }
 
@@ -56,6 +62,12 @@ pub struct RoutingConnectionEntry {
     /// hop count
     /// how many hops has the connection
     pub hc: u8,
+    /// expected transmission count
+    /// addition of the ETX of all hops
+    pub etx: u32,
+    /// bandwidth
+    /// of the slowest hop in kbit/s
+    pub bw: u32,
     /// link quality
     pub lq: u32,
     /// last_update
@@ -66,69 +78,23 @@ pub struct RoutingConnectionEntry {
 ///
 /// This is the table to turn to when checking where to send
 /// a package.
+/// The global routing table is part of the router instance.
 pub struct RoutingTable {
     /// routing table key is a users q8id
     pub table: HashMap<Vec<u8>, RoutingUserEntry>,
 }
 
 impl RoutingTable {
-    /// Initialize routing table
-    /// Creates global routing table and saves it to state.
-    pub fn init() {
-        // create global routing table and save it to state
-        let table = RoutingTable {
+    /// Create an empty routing table
+    pub fn new() -> RoutingTable {
+        RoutingTable {
             table: HashMap::new(),
-        };
-        ROUTINGTABLE.set(RwLock::new(table));
+        }
     }
 
     /// set and replace routing table with a new table
     pub fn set(new_table: RoutingTable) {
-        let mut table = ROUTINGTABLE.get().write().unwrap();
-        table.table = new_table.table;
-    }
-
-    /// Create routing information for a specific neighbour node,
-    /// to be sent to this neighbour node.
-    pub fn create_routing_info(
-        neighbour: PeerId,
-        last_sent: u64,
-    ) -> router_net_proto::RoutingInfoTable {
-        let mut table = router_net_proto::RoutingInfoTable { entry: Vec::new() };
-
-        // get access to routing table
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
-
-        // loop through routing table
-        for (user_id, user) in routing_table.table.iter() {
-            if user.connections.len() == 0 {
-                continue;
-            }
-
-            // choose best link quality
-            let mut min_conn = user.connections[0].clone();
-            for i in 0..user.connections.len() {
-                if user.connections[i].lq < min_conn.lq {
-                    min_conn = user.connections[i].clone();
-                }
-            }
-
-            if neighbour != min_conn.node && (min_conn.last_update >= last_sent || min_conn.hc == 0)
-            {
-                let mut hc = Vec::new();
-                hc.push(min_conn.hc);
-
-                let table_entry = router_net_proto::RoutingInfoEntry {
-                    user: user_id.to_owned(),
-                    rtt: min_conn.rtt,
-                    hc,
-                    pgid: user.pgid,
-                };
-                table.entry.push(table_entry);
-            }
-        }
-
-        table
+        Router::instance().set_routing_table(new_table);
     }
 
     /// get online users and hope count    
@@ -136,7 +102,8 @@ impl RoutingTable {
         let mut user_ids: BTreeMap<Vec<u8>, u8> = BTreeMap::new();
 
         // get access to routing table
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
+        let router = Router::instance();
+        let routing_table = router.table.read().unwrap();
 
         // loop through routing table
         for (user_id, user) in routing_table.table.iter() {
@@ -152,7 +119,8 @@ impl RoutingTable {
         let mut users: BTreeMap<Vec<u8>, Vec<RoutingConnectionEntry>> = BTreeMap::new();
 
         // get access to routing table
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
+        let router = Router::instance();
+        let routing_table = router.table.read().unwrap();
 
         // loop through routing table
         for (user_id, user) in routing_table.table.iter() {
@@ -169,7 +137,8 @@ impl RoutingTable {
         let mut user_ids: Vec<Vec<u8>> = vec![];
 
         // get access to routing table
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
+        let router = Router::instance();
+        let routing_table = router.table.read().unwrap();
 
         // loop through routing table
         for (user_id, user) in routing_table.table.iter() {
@@ -186,7 +155,8 @@ impl RoutingTable {
         let mut table_list: Vec<proto::RoutingTableEntry> = Vec::new();
 
         // get routing table state
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
+        let router = Router::instance();
+        let routing_table = router.table.read().unwrap();
 
         // loop through all user table entries
         for (id, entry) in &routing_table.table {
@@ -253,56 +223,81 @@ impl RoutingTable {
     /// It selects the best route according to the rank_routing_connection function.
     ///
     pub fn get_route_to_user(user_id: PeerId) -> Option<RoutingConnectionEntry> {
-        // get routing table state
-        let routing_table = ROUTINGTABLE.get().read().unwrap();
+        Self::get_routes_to_user(user_id).into_iter().next()
+    }
 
-        // get q8id for qaul user
-        let user_q8id = QaulId::to_q8id(user_id);
+    /// Get all routing connection entries for a specific user
+    ///
+    /// The routes are ranked from the best to the worst route.
+    /// They are ordered according to the rank_routing_connection
+    /// function first and to their link quality second.
+    pub fn get_routes_to_user(user_id: PeerId) -> Vec<RoutingConnectionEntry> {
+        Router::instance().get_routes_to_user(user_id)
+    }
 
-        // find user
-        if let Some(user_entry) = routing_table.table.get(&user_q8id) {
-            let mut compare: Option<&RoutingConnectionEntry> = None;
-
-            // find best route
-            for connection in &user_entry.connections {
-                match compare {
-                    Some(current) => {
-                        if Self::compare_connections(current, connection) {
-                            compare = Some(connection);
-                        }
-                    }
-                    None => compare = Some(connection),
-                }
-            }
+    /// Get an alternative route to a specific user
+    ///
+    /// Returns the route at the position `index` of the ranked
+    /// routes. An index of 0 returns the best route.
+    /// If there are less routes than the index, the best
+    /// route is returned.
+    pub fn get_alternative_route_to_user(
+        user_id: PeerId,
+        index: usize,
+    ) -> Option<RoutingConnectionEntry> {
+        let mut routes = Self::get_routes_to_user(user_id);
+        if index < routes.len() {
+            return Some(routes.swap_remove(index));
+        }
 
-            // return route
-            match compare {
-                None => return None,
-                Some(connection) => return Some(connection.to_owned()),
+        routes.into_iter().next()
+    }
+
+    /// Get the best route to a specific user avoiding a route
+    ///
+    /// Returns the best route, that is not sent via the
+    /// neighbour `node_id` over the connection `module`.
+    /// If there is no other route, the best route is returned.
+    pub fn get_route_to_user_avoiding(
+        user_id: PeerId,
+        node_id: &PeerId,
+        module: &ConnectionModule,
+    ) -> Option<RoutingConnectionEntry> {
+        let routes = Self::get_routes_to_user(user_id);
+        for route in &routes {
+            if route.node != *node_id || route.module != *module {
+                return Some(route.to_owned());
             }
         }
-        None
+
+        routes.into_iter().next()
     }
 
-    /// Compare two routing connections and decides which one is better
+    /// Get the index of a disjoint route to a specific user
     ///
-    /// This function decides which connection to favour based on the
-    /// rank_routing_connection function
-    ///
-    /// Return values:
-    ///
-    /// * returns true, when the new connection is better
-    /// * returns false, when the current connection is better
-    ///
-    fn compare_connections(current: &RoutingConnectionEntry, new: &RoutingConnectionEntry) -> bool {
-        let current_value = Self::rank_routing_connection(current);
-        let new_value = Self::rank_routing_connection(new);
+    /// Disjoint routes are sent via different neighbour nodes.
+    /// The function returns the index in the ranked routes
+    /// of the disjoint route at position `path`.
+    /// If there are less disjoint routes than the path,
+    /// the disjoint routes are iterated again from the beginning.
+    pub fn get_disjoint_route_index(user_id: PeerId, path: usize) -> usize {
+        let routes = Self::get_routes_to_user(user_id);
+
+        // find the best route per neighbour node
+        let mut nodes: Vec<PeerId> = Vec::new();
+        let mut indexes: Vec<usize> = Vec::new();
+        for (index, route) in routes.iter().enumerate() {
+            if !nodes.contains(&route.node) {
+                nodes.push(route.node);
+                indexes.push(index);
+            }
+        }
 
-        if current_value < new_value {
-            return true;
+        if indexes.len() == 0 {
+            return 0;
         }
 
-        false
+        indexes[path % indexes.len()]
     }
 
     /// give a ranking to the routing connection
@@ -322,3 +317,118 @@ impl RoutingTable {
         }
     }
 }
+
+/// Global routing table of a router instance
+impl RouterInstance {
+    /// set and replace the routing table with a new table
+    pub fn set_routing_table(&self, new_table: RoutingTable) {
+        let mut table = self.table.write().unwrap();
+        table.table = new_table.table;
+    }
+
+    /// Create routing information for a specific neighbour node,
+    /// to be sent to this neighbour node.
+    ///
+    /// Routes via this neighbour are advertised as unreachable
+    /// (split horizon with poisoned reverse).
+    /// The poisoned routes are appended at the end of the table,
+    /// as nodes that don't know poisoned routes stop processing
+    /// the table at the first unreachable route.
+    /// Routes that reached the maximal hop count are not advertised.
+    pub fn create_routing_info(
+        &self,
+        neighbour: PeerId,
+        last_sent: u64,
+    ) -> router_net_proto::RoutingInfoTable {
+        let mut table = router_net_proto::RoutingInfoTable { entry: Vec::new() };
+        let mut poisoned: Vec<router_net_proto::RoutingInfoEntry> = Vec::new();
+
+        // get the maximal hop count
+        let hop_count_max = self.configuration.hop_count_max;
+
+        // get access to routing table
+        let routing_table = self.table.read().unwrap();
+
+        // loop through routing table
+        for (user_id, user) in routing_table.table.iter() {
+            if user.connections.len() == 0 {
+                continue;
+            }
+
+            // choose best link quality
+            let mut min_conn = user.connections[0].clone();
+            for i in 0..user.connections.len() {
+                if user.connections[i].lq < min_conn.lq {
+                    min_conn = user.connections[i].clone();
+                }
+            }
+
+            if min_conn.last_update >= last_sent || min_conn.hc == 0 {
+                // the neighbour would exceed the maximal hop count
+                if min_conn.hc as u64 >= hop_count_max {
+                    continue;
+                }
+
+                // poison the routes via the neighbour
+                if neighbour == min_conn.node {
+                    poisoned.push(router_net_proto::RoutingInfoEntry {
+                        user: user_id.to_owned(),
+                        rtt: 0,
+                        hc: vec![HOP_COUNT_UNREACHABLE],
+                        pgid: user.pgid,
+                        etx: 0,
+                        bw: 0,
+                    });
+                    continue;
+                }
+
+                let mut hc = Vec::new();
+                hc.push(min_conn.hc);
+
+                let table_entry = router_net_proto::RoutingInfoEntry {
+                    user: user_id.to_owned(),
+                    rtt: min_conn.rtt,
+                    hc,
+                    pgid: user.pgid,
+                    etx: min_conn.etx,
+                    bw: min_conn.bw,
+                };
+                table.entry.push(table_entry);
+            }
+        }
+
+        // append poisoned routes at the end
+        table.entry.append(&mut poisoned);
+
+        table
+    }
+
+    /// Get all routing connection entries for a specific user
+    ///
+    /// The routes are ranked from the best to the worst route.
+    /// They are ordered according to the rank_routing_connection
+    /// function first and to their link quality second.
+    pub fn get_routes_to_user(&self, user_id: PeerId) -> Vec<RoutingConnectionEntry> {
+        // get routing table state
+        let routing_table = self.table.read().unwrap();
+
+        // get q8id for qaul user
+        let user_q8id = QaulId::to_q8id(user_id);
+
+        // find user
+        let mut routes;
+        match routing_table.table.get(&user_q8id) {
+            Some(user_entry) => routes = user_entry.connections.clone(),
+            None => return Vec::new(),
+        }
+
+        // rank routes
+        routes.sort_by(|a, b| {
+            RoutingTable::rank_routing_connection(b)
+                .cmp(&RoutingTable::rank_routing_connection(a))
+                .then(a.lq.cmp(&b.lq))
+        });
+
+        routes
+    }
+}
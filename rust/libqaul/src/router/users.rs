@@ -144,6 +144,17 @@ impl Users {
         return res;
     }
 
+    /// check if a user is blocked
+    pub fn is_blocked(user_id: &PeerId) -> bool {
+        let q8id = QaulId::to_q8id(user_id.to_owned());
+
+        let store = USERS.get().read().unwrap();
+        match store.users.get(&q8id) {
+            Some(user) => user.blocked,
+            None => false,
+        }
+    }
+
     /// get the public key of a known user
     pub fn get_pub_key(user_id: &PeerId) -> Option<PublicKey> {
         // get q8id
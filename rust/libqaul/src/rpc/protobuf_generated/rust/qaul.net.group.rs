@@ -68,6 +68,26 @@ pub struct GroupInfo {
     /// updated members
     #[prost(message, repeated, tag="5")]
     pub members: ::prost::alloc::vec::Vec<GroupMember>,
+    /// message retention settings
+    #[prost(message, optional, tag="6")]
+    pub retention: ::core::option::Option<GroupRetention>,
+}
+/// Group message retention settings
+///
+/// A value of 0 disables the setting.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupRetention {
+    /// maximal age of a message in seconds
+    #[prost(uint64, tag="1")]
+    pub max_age: u64,
+    /// maximal number of messages in the conversation
+    #[prost(uint32, tag="2")]
+    pub max_count: u32,
+    /// time in seconds after which a message
+    /// disappears, once it arrived on a device
+    #[prost(uint64, tag="3")]
+    pub disappear_after: u64,
 }
 /// Reply to Invite
 ///
@@ -29,6 +29,13 @@ pub struct Envelope {
 /// envelop payload
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EnvelopPayload {
+    /// time to live of a DTN message in seconds
+    ///
+    /// This value is requested by the sender of a DTN message.
+    /// The storage node deletes the message once it expired.
+    /// 0 = use the default time to live of the storage node
+    #[prost(uint64, tag="3")]
+    pub dtn_ttl: u64,
     #[prost(oneof="envelop_payload::Payload", tags="1, 2")]
     pub payload: ::core::option::Option<envelop_payload::Payload>,
 }
@@ -78,7 +85,7 @@ pub struct Data {
 /// messaging unified message
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Messaging {
-    #[prost(oneof="messaging::Message", tags="1, 2, 3, 4, 5, 6")]
+    #[prost(oneof="messaging::Message", tags="1, 2, 3, 4, 5, 6, 7, 8")]
     pub message: ::core::option::Option<messaging::Message>,
 }
 /// Nested message and enum types in `Messaging`.
@@ -103,6 +110,18 @@ pub mod messaging {
         /// common message
         #[prost(message, tag="6")]
         CommonMessage(super::CommonMessage),
+        /// typing notification
+        ///
+        /// Typing notifications are ephemeral. They are neither
+        /// confirmed, retransmitted nor stored in DTN.
+        #[prost(message, tag="7")]
+        ChatTyping(super::ChatTyping),
+        /// read receipt
+        ///
+        /// Read receipts are neither confirmed, retransmitted
+        /// nor stored in DTN.
+        #[prost(message, tag="8")]
+        ChatRead(super::ChatRead),
     }
 }
 /// message received confirmation
@@ -139,6 +158,16 @@ pub struct GroupInviteMessage {
     #[prost(bytes="vec", tag="1")]
     pub content: ::prost::alloc::vec::Vec<u8>,
 }
+/// typing notification
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatTyping {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// true: user started typing, false: user stopped typing
+    #[prost(bool, tag="2")]
+    pub typing: bool,
+}
 /// common message
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CommonMessage {
@@ -152,7 +181,7 @@ pub struct CommonMessage {
     #[prost(uint64, tag="3")]
     pub sent_at: u64,
     /// payload
-    #[prost(oneof="common_message::Payload", tags="4, 5, 6, 7")]
+    #[prost(oneof="common_message::Payload", tags="4, 5, 6, 7, 8, 9, 10")]
     pub payload: ::core::option::Option<common_message::Payload>,
 }
 /// Nested message and enum types in `CommonMessage`.
@@ -172,6 +201,15 @@ pub mod common_message {
         /// rtc message
         #[prost(message, tag="7")]
         RtcMessage(super::RtcMessage),
+        /// reply to a chat message
+        #[prost(message, tag="8")]
+        ChatReply(super::ChatReply),
+        /// reaction to a chat message
+        #[prost(message, tag="9")]
+        ChatReaction(super::ChatReaction),
+        /// edit of a chat message
+        #[prost(message, tag="10")]
+        ChatEdit(super::ChatEdit),
     }
 }
 /// chat content
@@ -182,6 +220,67 @@ pub struct ChatMessage {
     #[prost(string, tag="1")]
     pub content: ::prost::alloc::string::String,
 }
+/// reply to a chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReply {
+    /// message id of the message replied to
+    #[prost(bytes="vec", tag="1")]
+    pub parent_id: ::prost::alloc::vec::Vec<u8>,
+    /// content
+    #[prost(string, tag="2")]
+    pub content: ::prost::alloc::string::String,
+}
+/// reaction to a chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReaction {
+    /// message id of the message reacted to
+    #[prost(bytes="vec", tag="1")]
+    pub parent_id: ::prost::alloc::vec::Vec<u8>,
+    /// reaction emoji
+    #[prost(string, tag="2")]
+    pub reaction: ::prost::alloc::string::String,
+    /// remove the reaction
+    #[prost(bool, tag="3")]
+    pub remove: bool,
+}
+/// edit or redaction of a chat message
+///
+/// only the sender of a message can edit it
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatEdit {
+    /// message id of the edited message
+    #[prost(bytes="vec", tag="1")]
+    pub parent_id: ::prost::alloc::vec::Vec<u8>,
+    /// new content
+    #[prost(string, tag="2")]
+    pub content: ::prost::alloc::string::String,
+    /// redact the message
+    #[prost(bool, tag="3")]
+    pub redact: bool,
+}
+/// read receipt
+///
+/// Informs the group members up to which message
+/// the sender has read the conversation.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatRead {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// read marker per group member
+    #[prost(message, repeated, tag="2")]
+    pub read_markers: ::prost::alloc::vec::Vec<ChatReadMarker>,
+}
+/// read marker of the messages of one group member
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReadMarker {
+    /// user id of the member who sent the messages
+    #[prost(bytes="vec", tag="1")]
+    pub user_id: ::prost::alloc::vec::Vec<u8>,
+    /// message id of the last read chat message of this member
+    #[prost(bytes="vec", tag="2")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+}
 /// file message
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FileMessage {
@@ -230,6 +329,13 @@ pub struct DtnResponse {
     /// reason of rejection
     #[prost(enumeration="dtn_response::Reason", tag="3")]
     pub reason: i32,
+    /// custody transfer acknowledgement
+    ///
+    /// The storage node took over the custody of the message
+    /// and is responsible for its delivery from now on.
+    /// The sender can stop retransmitting the message.
+    #[prost(bool, tag="4")]
+    pub custody: bool,
 }
 /// Nested message and enum types in `DtnResponse`.
 pub mod dtn_response {
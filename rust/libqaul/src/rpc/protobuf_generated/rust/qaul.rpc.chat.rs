@@ -2,7 +2,7 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Chat {
     /// message type
-    #[prost(oneof="chat::Message", tags="3, 4, 5")]
+    #[prost(oneof="chat::Message", tags="3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21")]
     pub message: ::core::option::Option<chat::Message>,
 }
 /// Nested message and enum types in `Chat`.
@@ -19,6 +19,54 @@ pub mod chat {
         /// send a new chat message
         #[prost(message, tag="5")]
         Send(super::ChatMessageSend),
+        /// request the delivery status of a message
+        #[prost(message, tag="6")]
+        StatusRequest(super::ChatMessageStatusRequest),
+        /// delivery status of a message
+        #[prost(message, tag="7")]
+        StatusResponse(super::ChatMessageStatusResponse),
+        /// resend an unconfirmed or failed message
+        #[prost(message, tag="8")]
+        ResendRequest(super::ChatMessageResendRequest),
+        /// cancel the sending of an unconfirmed message
+        #[prost(message, tag="9")]
+        CancelRequest(super::ChatMessageCancelRequest),
+        /// delete chat messages
+        #[prost(message, tag="10")]
+        DeleteRequest(super::ChatDeleteRequest),
+        /// result of the deletion
+        #[prost(message, tag="11")]
+        DeleteResponse(super::ChatDeleteResponse),
+        /// search chat messages
+        #[prost(message, tag="12")]
+        SearchRequest(super::ChatSearchRequest),
+        /// search results
+        #[prost(message, tag="13")]
+        SearchResponse(super::ChatSearchResponse),
+        /// react to a chat message
+        #[prost(message, tag="14")]
+        ReactionSend(super::ChatReactionSend),
+        /// edit or redact an own chat message
+        #[prost(message, tag="15")]
+        EditSend(super::ChatEditSend),
+        /// enable or disable sending read receipts
+        #[prost(message, tag="16")]
+        ReadReceiptsSet(super::ChatReadReceiptsSet),
+        /// request the read receipts of a group
+        #[prost(message, tag="17")]
+        ReadReceiptsRequest(super::ChatReadReceiptsRequest),
+        /// read receipts of a group
+        #[prost(message, tag="18")]
+        ReadReceiptsResponse(super::ChatReadReceiptsResponse),
+        /// send a typing notification
+        #[prost(message, tag="19")]
+        TypingSend(super::ChatTypingSend),
+        /// request the typing users of a group
+        #[prost(message, tag="20")]
+        TypingRequest(super::ChatTypingRequest),
+        /// typing users of a group
+        #[prost(message, tag="21")]
+        TypingResponse(super::ChatTypingResponse),
     }
 }
 /// request messages of a specific chat conversation
@@ -77,6 +125,170 @@ pub struct ChatMessage {
     /// chat content message
     #[prost(bytes="vec", tag="8")]
     pub content: ::prost::alloc::vec::Vec<u8>,
+    /// reactions to this message
+    #[prost(message, repeated, tag="11")]
+    pub reactions: ::prost::alloc::vec::Vec<MessageReaction>,
+    /// time when the message was edited
+    ///
+    /// 0 if the message was never edited
+    #[prost(uint64, tag="12")]
+    pub edited_at: u64,
+    /// the message was redacted by its sender
+    #[prost(bool, tag="13")]
+    pub redacted: bool,
+}
+/// reaction to a chat message
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageReaction {
+    /// user id
+    #[prost(bytes="vec", tag="1")]
+    pub user_id: ::prost::alloc::vec::Vec<u8>,
+    /// reaction emoji
+    #[prost(string, tag="2")]
+    pub reaction: ::prost::alloc::string::String,
+    /// time of the reaction
+    #[prost(uint64, tag="3")]
+    pub reacted_at: u64,
+}
+/// request the delivery status of a chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatMessageStatusRequest {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// delivery status of a chat message
+///
+/// this message is also sent as the answer
+/// to a resend or cancel request
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatMessageStatusResponse {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+    /// message was found
+    #[prost(bool, tag="2")]
+    pub found: bool,
+    /// the chat message
+    #[prost(message, optional, tag="3")]
+    pub message: ::core::option::Option<ChatMessage>,
+    /// delivery to the receivers that
+    /// haven't confirmed the message yet
+    #[prost(message, repeated, tag="4")]
+    pub deliveries: ::prost::alloc::vec::Vec<ChatMessageDelivery>,
+    /// number of changed deliveries
+    ///
+    /// set on answers to resend and cancel requests
+    #[prost(uint32, tag="5")]
+    pub changed: u32,
+}
+/// delivery of a message to a receiver
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatMessageDelivery {
+    /// receiver id
+    #[prost(bytes="vec", tag="1")]
+    pub receiver_id: ::prost::alloc::vec::Vec<u8>,
+    /// delivery state
+    #[prost(enumeration="DeliveryState", tag="2")]
+    pub state: i32,
+    /// number of send attempts
+    #[prost(uint32, tag="3")]
+    pub try_count: u32,
+    /// time when the message was created
+    #[prost(uint64, tag="4")]
+    pub created_at: u64,
+    /// time of the last send attempt
+    #[prost(uint64, tag="5")]
+    pub last_try: u64,
+    /// reason of the failure
+    #[prost(string, tag="6")]
+    pub failure: ::prost::alloc::string::String,
+}
+/// resend an unconfirmed or failed chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatMessageResendRequest {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// cancel the sending of an unconfirmed chat message
+///
+/// the message is not retransmitted anymore
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatMessageCancelRequest {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// delete chat messages of a conversation
+///
+/// The messages are only deleted on this node.
+/// Messages can be addressed by their message ids
+/// and by an index range.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatDeleteRequest {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// message ids
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub message_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// first index of the range to delete
+    #[prost(uint64, tag="3")]
+    pub first_index: u64,
+    /// last index of the range to delete
+    ///
+    /// no range is deleted if this value is 0
+    #[prost(uint64, tag="4")]
+    pub last_index: u64,
+}
+/// result of a chat message deletion
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatDeleteResponse {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// number of deleted messages
+    #[prost(uint32, tag="2")]
+    pub deleted: u32,
+}
+/// search the chat messages
+///
+/// All words of the query need to be contained in a message.
+/// The words are matched as prefixes.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatSearchRequest {
+    /// search query
+    #[prost(string, tag="1")]
+    pub query: ::prost::alloc::string::String,
+    /// only search in this group, if set
+    #[prost(bytes="vec", tag="2")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// only search messages of this sender, if set
+    #[prost(bytes="vec", tag="3")]
+    pub sender_id: ::prost::alloc::vec::Vec<u8>,
+    /// only messages sent at or after this time, if set
+    #[prost(uint64, tag="4")]
+    pub from: u64,
+    /// only messages sent at or before this time, if set
+    #[prost(uint64, tag="5")]
+    pub to: u64,
+    /// maximal number of results
+    ///
+    /// the default of 50 results is used if this value is 0
+    #[prost(uint32, tag="6")]
+    pub limit: u32,
+}
+/// chat search results
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatSearchResponse {
+    /// search query
+    #[prost(string, tag="1")]
+    pub query: ::prost::alloc::string::String,
+    /// found messages, newest first
+    #[prost(message, repeated, tag="2")]
+    pub message_list: ::prost::alloc::vec::Vec<ChatMessage>,
 }
 /// message reception confirmed
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -116,6 +328,11 @@ pub struct ChatContent {
     /// message text
     #[prost(string, tag="1")]
     pub text: ::prost::alloc::string::String,
+    /// message id of the message replied to
+    ///
+    /// empty if this message is not a reply
+    #[prost(bytes="vec", tag="2")]
+    pub reply_to: ::prost::alloc::vec::Vec<u8>,
 }
 /// file content
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -130,11 +347,17 @@ pub struct FileContent {
     #[prost(string, tag="3")]
     pub file_extension: ::prost::alloc::string::String,
     /// file size
-    #[prost(uint32, tag="4")]
-    pub file_size: u32,
+    #[prost(uint64, tag="4")]
+    pub file_size: u64,
     /// file description
     #[prost(string, tag="5")]
     pub file_description: ::prost::alloc::string::String,
+    /// MIME type of the file
+    #[prost(string, tag="6")]
+    pub mime_type: ::prost::alloc::string::String,
+    /// small preview of the file, empty if none
+    #[prost(bytes="vec", tag="7")]
+    pub preview: ::prost::alloc::vec::Vec<u8>,
 }
 /// Group event information
 /// this message is purely informational
@@ -156,6 +379,112 @@ pub struct ChatMessageSend {
     /// content of the message
     #[prost(string, tag="2")]
     pub content: ::prost::alloc::string::String,
+    /// message id of the message replied to, if set
+    #[prost(bytes="vec", tag="3")]
+    pub reply_to: ::prost::alloc::vec::Vec<u8>,
+}
+/// enable or disable sending read receipts
+///
+/// Read receipts are sent to the group members, when
+/// the unread messages of a group are cleared.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReadReceiptsSet {
+    /// send read receipts
+    #[prost(bool, tag="1")]
+    pub enabled: bool,
+}
+/// request the read receipts of a group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReadReceiptsRequest {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// read receipts of a group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReadReceiptsResponse {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// sending of read receipts is enabled for this account
+    #[prost(bool, tag="2")]
+    pub enabled: bool,
+    /// read receipts of the group members
+    #[prost(message, repeated, tag="3")]
+    pub receipts: ::prost::alloc::vec::Vec<ChatReadReceipt>,
+}
+/// read receipt of a group member
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReadReceipt {
+    /// user id of the reader
+    #[prost(bytes="vec", tag="1")]
+    pub user_id: ::prost::alloc::vec::Vec<u8>,
+    /// the reader has read all our messages up to the
+    /// message with this message id
+    #[prost(bytes="vec", tag="2")]
+    pub last_message_id: ::prost::alloc::vec::Vec<u8>,
+    /// time the receipt was sent
+    #[prost(uint64, tag="3")]
+    pub read_at: u64,
+}
+/// send a typing notification to the group members
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatTypingSend {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// true: started typing, false: stopped typing
+    #[prost(bool, tag="2")]
+    pub typing: bool,
+}
+/// request the typing users of a group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatTypingRequest {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// typing users of a group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatTypingResponse {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// user ids of the users currently typing
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub user_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// react to a chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatReactionSend {
+    /// group id of the message
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// message id of the message reacted to
+    #[prost(bytes="vec", tag="2")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+    /// reaction emoji
+    #[prost(string, tag="3")]
+    pub reaction: ::prost::alloc::string::String,
+    /// remove the reaction
+    #[prost(bool, tag="4")]
+    pub remove: bool,
+}
+/// edit or redact an own chat message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatEditSend {
+    /// group id of the message
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// message id of the edited message
+    #[prost(bytes="vec", tag="2")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+    /// new content of the message
+    #[prost(string, tag="3")]
+    pub content: ::prost::alloc::string::String,
+    /// redact the message
+    #[prost(bool, tag="4")]
+    pub redact: bool,
 }
 /// Sending status of sent messages
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -176,6 +505,11 @@ pub enum MessageStatus {
     Receiving = 4,
     /// message received
     Received = 5,
+    /// sending failed
+    ///
+    /// the message could not be delivered
+    /// and is not retransmitted anymore
+    Failed = 6,
 }
 impl MessageStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -190,6 +524,37 @@ impl MessageStatus {
             MessageStatus::ConfirmedByAll => "CONFIRMED_BY_ALL",
             MessageStatus::Receiving => "RECEIVING",
             MessageStatus::Received => "RECEIVED",
+            MessageStatus::Failed => "FAILED",
+        }
+    }
+}
+/// delivery state of a message to a receiver
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DeliveryState {
+    /// waiting to be sent
+    DeliveryPending = 0,
+    /// sent, waiting for the confirmation
+    DeliverySent = 1,
+    /// handed to storage nodes
+    DeliveryDtn = 2,
+    /// a storage node took over the custody
+    DeliveryCustody = 3,
+    /// sending failed
+    DeliveryFailed = 4,
+}
+impl DeliveryState {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DeliveryState::DeliveryPending => "DELIVERY_PENDING",
+            DeliveryState::DeliverySent => "DELIVERY_SENT",
+            DeliveryState::DeliveryDtn => "DELIVERY_DTN",
+            DeliveryState::DeliveryCustody => "DELIVERY_CUSTODY",
+            DeliveryState::DeliveryFailed => "DELIVERY_FAILED",
         }
     }
 }
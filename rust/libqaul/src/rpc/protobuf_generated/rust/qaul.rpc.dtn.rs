@@ -2,7 +2,7 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Dtn {
     /// message type
-    #[prost(oneof="dtn::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
+    #[prost(oneof="dtn::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14")]
     pub message: ::core::option::Option<dtn::Message>,
 }
 /// Nested message and enum types in `DTN`.
@@ -40,6 +40,18 @@ pub mod dtn {
         /// dtn set total size response
         #[prost(message, tag="10")]
         DtnSetTotalSizeResponse(super::DtnSetTotalSizeResponse),
+        /// dtn per user usage request
+        #[prost(message, tag="11")]
+        DtnUsageRequest(super::DtnUsageRequest),
+        /// dtn per user usage response
+        #[prost(message, tag="12")]
+        DtnUsageResponse(super::DtnUsageResponse),
+        /// dtn set user quota request
+        #[prost(message, tag="13")]
+        DtnSetUserQuotaRequest(super::DtnSetUserQuotaRequest),
+        /// dtn set user quota response
+        #[prost(message, tag="14")]
+        DtnSetUserQuotaResponse(super::DtnSetUserQuotaResponse),
     }
 }
 /// Dtn State Request
@@ -58,6 +70,13 @@ pub struct DtnStateResponse {
     /// unconfirmed count
     #[prost(uint32, tag="3")]
     pub unconfirmed_count: u32,
+    /// number of stored messages that expired
+    /// since the start of this node
+    #[prost(uint32, tag="4")]
+    pub expired_count: u32,
+    /// time stamp of the last expiry sweep
+    #[prost(uint64, tag="5")]
+    pub last_sweep_at: u64,
 }
 /// Dtn Config Request
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -72,6 +91,14 @@ pub struct DtnConfigResponse {
     /// users
     #[prost(bytes="vec", repeated, tag="2")]
     pub users: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// maximal storage size per user in MB
+    /// 0 = unlimited
+    #[prost(uint32, tag="3")]
+    pub user_size_max: u32,
+    /// maximal message count per user
+    /// 0 = unlimited
+    #[prost(uint32, tag="4")]
+    pub user_message_max: u32,
 }
 /// Dtn Add User Request
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -124,3 +151,55 @@ pub struct DtnSetTotalSizeResponse {
     #[prost(string, tag="2")]
     pub message: ::prost::alloc::string::String,
 }
+/// Dtn Usage Request
+///
+/// Request the storage usage per origin user
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DtnUsageRequest {
+    /// user id
+    /// if empty, the usage of all users is returned
+    #[prost(bytes="vec", tag="1")]
+    pub user_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// Dtn Usage Response
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DtnUsageResponse {
+    /// usage per user
+    #[prost(message, repeated, tag="1")]
+    pub users: ::prost::alloc::vec::Vec<DtnUserUsage>,
+}
+/// Dtn storage usage of a single origin user
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DtnUserUsage {
+    /// user id
+    #[prost(bytes="vec", tag="1")]
+    pub user_id: ::prost::alloc::vec::Vec<u8>,
+    /// used size in bytes
+    #[prost(uint64, tag="2")]
+    pub used_size: u64,
+    /// stored message count
+    #[prost(uint32, tag="3")]
+    pub message_count: u32,
+}
+/// Dtn Set User Quota Request
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DtnSetUserQuotaRequest {
+    /// maximal storage size per user in MB
+    /// 0 = unlimited
+    #[prost(uint32, tag="1")]
+    pub user_size_max: u32,
+    /// maximal message count per user
+    /// 0 = unlimited
+    #[prost(uint32, tag="2")]
+    pub user_message_max: u32,
+}
+/// Dtn Set User Quota Response
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DtnSetUserQuotaResponse {
+    /// status
+    #[prost(bool, tag="1")]
+    pub status: bool,
+    /// message
+    #[prost(string, tag="2")]
+    pub message: ::prost::alloc::string::String,
+}
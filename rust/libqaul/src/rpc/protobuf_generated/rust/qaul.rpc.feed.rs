@@ -2,7 +2,7 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Feed {
     /// message type
-    #[prost(oneof="feed::Message", tags="1, 2, 3")]
+    #[prost(oneof="feed::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
     pub message: ::core::option::Option<feed::Message>,
 }
 /// Nested message and enum types in `Feed`.
@@ -19,8 +19,53 @@ pub mod feed {
         /// request received messages
         #[prost(message, tag="3")]
         Request(super::FeedMessageRequest),
+        /// request a page of feed messages
+        #[prost(message, tag="4")]
+        PageRequest(super::FeedPageRequest),
+        /// a page of feed messages
+        #[prost(message, tag="5")]
+        Page(super::FeedPage),
+        /// look up a single feed message
+        #[prost(message, tag="6")]
+        LookupRequest(super::FeedLookupRequest),
+        /// result of the feed message lookup
+        #[prost(message, tag="7")]
+        LookupResponse(super::FeedLookupResponse),
+        /// mute or unmute a sender
+        #[prost(message, tag="8")]
+        MuteRequest(super::FeedMuteRequest),
+        /// request the list of muted senders
+        #[prost(message, tag="9")]
+        MuteListRequest(super::FeedMuteListRequest),
+        /// list of muted senders
+        #[prost(message, tag="10")]
+        MuteList(super::FeedMuteList),
     }
 }
+/// mute or unmute the feed messages of a sender
+///
+/// The messages of muted senders are still stored
+/// and forwarded, but not displayed.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedMuteRequest {
+    /// user id of the sender
+    #[prost(bytes="vec", tag="1")]
+    pub sender_id: ::prost::alloc::vec::Vec<u8>,
+    /// true to mute, false to unmute
+    #[prost(bool, tag="2")]
+    pub mute: bool,
+}
+/// request the list of muted senders
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedMuteListRequest {
+}
+/// list of muted senders
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedMuteList {
+    /// user ids of the muted senders
+    #[prost(bytes="vec", repeated, tag="1")]
+    pub sender_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
 /// request feed messages
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FeedMessageRequest {
@@ -39,6 +84,72 @@ pub struct FeedMessageRequest {
     #[prost(uint64, tag="2")]
     pub last_index: u64,
 }
+/// request a page of feed messages
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedPageRequest {
+    /// data base index to start from, the message
+    /// with this index is not part of the page
+    ///
+    /// 0 starts at the newest message when paging
+    /// to older messages and at the oldest message
+    /// when paging to newer messages.
+    #[prost(uint64, tag="1")]
+    pub start_index: u64,
+    /// paging direction
+    #[prost(enumeration="FeedPageDirection", tag="2")]
+    pub direction: i32,
+    /// maximal number of messages in the page
+    ///
+    /// 0 = default limit
+    #[prost(uint32, tag="3")]
+    pub limit: u32,
+    /// only messages of this sender
+    ///
+    /// all senders if empty
+    #[prost(bytes="vec", tag="4")]
+    pub sender_id: ::prost::alloc::vec::Vec<u8>,
+    /// only messages sent at or after this time
+    ///
+    /// in milli seconds, 0 = no limit
+    #[prost(uint64, tag="5")]
+    pub time_from: u64,
+    /// only messages sent before this time
+    ///
+    /// in milli seconds, 0 = no limit
+    #[prost(uint64, tag="6")]
+    pub time_to: u64,
+}
+/// a page of feed messages
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedPage {
+    /// feed messages in paging order
+    #[prost(message, repeated, tag="1")]
+    pub feed_message: ::prost::alloc::vec::Vec<FeedMessage>,
+    /// are there more matching messages
+    /// in the paging direction
+    #[prost(bool, tag="2")]
+    pub has_more: bool,
+}
+/// look up a single feed message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedLookupRequest {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// result of the feed message lookup
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedLookupResponse {
+    /// message id
+    #[prost(bytes="vec", tag="1")]
+    pub message_id: ::prost::alloc::vec::Vec<u8>,
+    /// was the message found
+    #[prost(bool, tag="2")]
+    pub found: bool,
+    /// the feed message
+    #[prost(message, optional, tag="3")]
+    pub feed_message: ::core::option::Option<FeedMessage>,
+}
 /// List of feed messages
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FeedMessageList {
@@ -79,3 +190,26 @@ pub struct SendMessage {
     #[prost(string, tag="1")]
     pub content: ::prost::alloc::string::String,
 }
+/// Paging direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FeedPageDirection {
+    /// messages older than the start index,
+    /// newest message first
+    FeedOlder = 0,
+    /// messages newer than the start index,
+    /// oldest message first
+    FeedNewer = 1,
+}
+impl FeedPageDirection {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FeedPageDirection::FeedOlder => "FEED_OLDER",
+            FeedPageDirection::FeedNewer => "FEED_NEWER",
+        }
+    }
+}
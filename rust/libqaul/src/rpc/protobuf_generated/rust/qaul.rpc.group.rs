@@ -2,7 +2,7 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Group {
     /// message type
-    #[prost(oneof="group::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16")]
+    #[prost(oneof="group::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18")]
     pub message: ::core::option::Option<group::Message>,
 }
 /// Nested message and enum types in `Group`.
@@ -58,6 +58,12 @@ pub mod group {
         /// group invited response
         #[prost(message, tag="16")]
         GroupInvitedResponse(super::GroupInvitedResponse),
+        /// group retention request
+        #[prost(message, tag="17")]
+        GroupRetentionRequest(super::GroupRetentionRequest),
+        /// group retention response
+        #[prost(message, tag="18")]
+        GroupRetentionResponse(super::GroupRetentionResponse),
     }
 }
 /// Group Result
@@ -246,6 +252,49 @@ pub struct GroupInfo {
     /// sender of the last message
     #[prost(bytes="vec", tag="11")]
     pub last_message_sender_id: ::prost::alloc::vec::Vec<u8>,
+    /// message retention settings
+    #[prost(message, optional, tag="12")]
+    pub retention: ::core::option::Option<GroupRetention>,
+}
+/// Group message retention settings
+///
+/// A value of 0 disables the setting.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupRetention {
+    /// maximal age of a message in seconds
+    #[prost(uint64, tag="1")]
+    pub max_age: u64,
+    /// maximal number of messages in the conversation
+    #[prost(uint32, tag="2")]
+    pub max_count: u32,
+    /// time in seconds after which a message
+    /// disappears, once it arrived on a device
+    #[prost(uint64, tag="3")]
+    pub disappear_after: u64,
+}
+/// Group retention request
+///
+/// Sets the message retention settings of a group.
+/// Only administrators can change them.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupRetentionRequest {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// retention settings
+    #[prost(message, optional, tag="2")]
+    pub retention: ::core::option::Option<GroupRetention>,
+}
+/// Group retention response
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupRetentionResponse {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// result
+    #[prost(message, optional, tag="2")]
+    pub result: ::core::option::Option<GroupResult>,
 }
 /// Group list request
 #[derive(Clone, PartialEq, ::prost::Message)]
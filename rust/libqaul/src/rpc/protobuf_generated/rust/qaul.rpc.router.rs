@@ -1,7 +1,7 @@
 /// router rpc message container
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Router {
-    #[prost(oneof="router::Message", tags="1, 2, 3, 4, 5, 6")]
+    #[prost(oneof="router::Message", tags="1, 2, 3, 4, 5, 6, 7, 8")]
     pub message: ::core::option::Option<router::Message>,
 }
 /// Nested message and enum types in `Router`.
@@ -20,6 +20,10 @@ pub mod router {
         NeighboursRequest(super::NeighboursRequest),
         #[prost(message, tag="6")]
         NeighboursList(super::NeighboursList),
+        #[prost(message, tag="7")]
+        LoopStatisticsRequest(super::LoopStatisticsRequest),
+        #[prost(message, tag="8")]
+        LoopStatistics(super::LoopStatistics),
     }
 }
 /// UI request for routing table list
@@ -131,6 +135,35 @@ pub struct NeighboursEntry {
     #[prost(uint32, tag="2")]
     pub rtt: u32,
 }
+/// UI request for the loop detection statistics
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoopStatisticsRequest {
+}
+/// loop detection statistics of this node
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LoopStatistics {
+    /// number of messages forwarded by this node
+    #[prost(uint64, tag="1")]
+    pub forwarded: u64,
+    /// number of messages that were forwarded back
+    /// through this node
+    #[prost(uint64, tag="2")]
+    pub loops_detected: u64,
+    /// time of the last detected loop in milli seconds
+    /// 0 if no loop was detected
+    #[prost(uint64, tag="3")]
+    pub last_loop_at: u64,
+    /// signature of the last message that looped
+    #[prost(bytes="vec", tag="4")]
+    pub last_loop_signature: ::prost::alloc::vec::Vec<u8>,
+    /// number of poisoned routes received from neighbours
+    #[prost(uint64, tag="5")]
+    pub routes_poisoned: u64,
+    /// number of received routes dropped because they
+    /// exceeded the maximal hop count
+    #[prost(uint64, tag="6")]
+    pub routes_hop_count_exceeded: u64,
+}
 /// Connection modules
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -9,6 +9,7 @@
 use libp2p::PeerId;
 use prost::Message;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sled_extensions::{
     bincode::{BincodeEncoding, Tree},
     structured::Iter,
@@ -19,16 +20,19 @@ use std::{
     collections::BTreeMap,
     convert::TryInto,
     ffi::OsStr,
-    fs::{self, File},
-    io::{Read, Write},
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     sync::RwLock,
 };
 
-use super::ChatStorage;
+use super::{Chat, ChatStorage};
+use crate::connections::ConnectionModule;
+use crate::router::neighbours::Neighbours;
+use crate::router::table::RoutingTable;
 use crate::services::messaging::{self, Messaging, MessagingServiceType};
+use crate::storage::configuration::Configuration;
 use crate::storage::database::DataBase;
-use crate::utilities::timestamp;
 use crate::utilities::timestamp::Timestamp;
 use crate::{
     node::user_accounts::{UserAccount, UserAccounts},
@@ -47,14 +51,80 @@ pub mod proto_net {
 }
 
 /// Size of the biggest file data package
+///
+/// The chunk size is chosen per transport from the configuration
+/// and limited to this size, to fit into a messaging frame.
 pub const DEF_PACKAGE_SIZE: u32 = 64000;
 
+/// Size of the smallest file data package
+pub const MIN_PACKAGE_SIZE: u32 = 512;
+
+/// Version of the file info message
+///
+/// * 0: legacy file info with 32 bit file size
+/// * 1: 64 bit file size
+pub const FILE_INFO_VERSION: u32 = 1;
+
+/// maximal size of a file preview in bytes
+pub const MAX_PREVIEW_SIZE: usize = 16384;
+
+/// time in milli seconds without incoming chunks,
+/// after which the missing chunks of a file are requested
+const CHUNK_REQUEST_TIMEOUT: u64 = 60000;
+
+/// maximal number of chunks requested at once
+const CHUNK_REQUEST_MAX: usize = 64;
+
+/// maximal number of chunks buffered per file,
+/// that arrived before the file info message
+const EARLY_CHUNKS_MAX: usize = 64;
+
 /// mutable state of all file
 static ALLFILES: Storage<RwLock<AllFiles>> = Storage::new();
 
 /// Structure to management for file histories based on the each user_id.
 pub struct AllFiles {
     pub db_ref: BTreeMap<Vec<u8>, UserFiles>,
+    /// reception activity of incomplete files
+    ///
+    /// index: account id & file id
+    pub activity: BTreeMap<Vec<u8>, FileActivity>,
+    /// chunks received before the file info message
+    ///
+    /// index: account id & file id
+    pub early_chunks: BTreeMap<Vec<u8>, EarlyChunks>,
+}
+
+/// Chunks of a file that arrived before its file info message
+///
+/// The chunks are only kept in memory and are processed
+/// once the file info message arrives.
+/// If it doesn't arrive within the chunk request timeout,
+/// the chunks are dropped.
+pub struct EarlyChunks {
+    /// time the first chunk was received
+    pub received_at: u64,
+    /// unverified chunks
+    ///
+    /// The chunks are verified once the file info
+    /// with the chunk hashes has arrived.
+    pub chunks: Vec<proto_net::ChatFileData>,
+}
+
+/// Reception activity of an incomplete file
+///
+/// This state is only kept in memory. After a restart
+/// the missing chunks of all incomplete files are requested.
+pub struct FileActivity {
+    /// time of the last received chunk or chunk request
+    pub last_at: u64,
+    /// number of chunk requests sent
+    pub request_count: u32,
+    /// number of received chunks
+    ///
+    /// The chunks are counted once in the data base,
+    /// afterwards every new chunk is added.
+    pub chunk_count: Option<u32>,
 }
 
 /// User file histories structure
@@ -64,12 +134,28 @@ pub struct UserFiles {
     ///
     /// index: file ID
     pub histories: Tree<FileHistory>,
-    /// file data chunks
+    /// received file data chunks
     ///
-    /// Storage of incoming chunks until receiving is completed.
+    /// Marks the incoming chunks until receiving is completed.
+    /// The chunk data itself is written to the partial file
+    /// on disk, the value of the entries is empty.
     ///
     /// index: file_id & chunk_index
     pub file_chunks: Tree<Vec<u8>>,
+    /// MIME type and preview of the files
+    ///
+    /// The previews are kept apart from the file histories,
+    /// so that they are only loaded when needed.
+    ///
+    /// index: file ID
+    pub media: Tree<FileMedia>,
+    /// SHA-256 hashes of the data chunks of the files
+    ///
+    /// The hashes are signed by the file sender
+    /// within the file info message.
+    ///
+    /// index: file ID
+    pub chunk_hashes: Tree<Vec<Vec<u8>>>,
 }
 
 impl UserFiles {
@@ -86,6 +172,74 @@ impl UserFiles {
         None
     }
 
+    /// get the file history of a chat message
+    pub fn get_filehistory_by_message_id(&self, message_id: &Vec<u8>) -> Option<FileHistory> {
+        for entry in self.histories.iter() {
+            match entry {
+                Ok((_key, file_history)) => {
+                    if file_history.message_id == *message_id {
+                        return Some(file_history);
+                    }
+                }
+                Err(e) => log::error!("{}", e),
+            }
+        }
+
+        None
+    }
+
+    /// get the MIME type and preview of a file
+    pub fn get_media(&self, file_id: u64) -> FileMedia {
+        match self.media.get(file_id.to_be_bytes().to_vec()) {
+            Ok(Some(media)) => return media,
+            Ok(None) => {}
+            Err(e) => log::error!("{}", e),
+        }
+
+        FileMedia {
+            mime_type: "".to_string(),
+            preview: Vec::new(),
+        }
+    }
+
+    /// save the MIME type and preview of a file
+    pub fn save_media(&self, file_id: u64, media: FileMedia) {
+        if let Err(e) = self.media.insert(file_id.to_be_bytes().to_vec(), media) {
+            log::error!("Error saving file media to data base: {}", e);
+            return;
+        }
+        if let Err(e) = self.media.flush() {
+            log::error!("Error file media flush: {}", e);
+        }
+    }
+
+    /// get the chunk hashes of a file
+    ///
+    /// Returns an empty list for files without chunk hashes.
+    pub fn get_chunk_hashes(&self, file_id: u64) -> Vec<Vec<u8>> {
+        match self.chunk_hashes.get(file_id.to_be_bytes().to_vec()) {
+            Ok(Some(hashes)) => return hashes,
+            Ok(None) => {}
+            Err(e) => log::error!("{}", e),
+        }
+
+        Vec::new()
+    }
+
+    /// save the chunk hashes of a file
+    pub fn save_chunk_hashes(&self, file_id: u64, hashes: Vec<Vec<u8>>) {
+        if let Err(e) = self
+            .chunk_hashes
+            .insert(file_id.to_be_bytes().to_vec(), hashes)
+        {
+            log::error!("Error saving chunk hashes to data base: {}", e);
+            return;
+        }
+        if let Err(e) = self.chunk_hashes.flush() {
+            log::error!("Error chunk hashes flush: {}", e);
+        }
+    }
+
     /// get file history iterator
     pub fn get_filehistory_iterator(&self) -> Iter<FileHistory, BincodeEncoding> {
         // get key range
@@ -136,28 +290,36 @@ impl UserFiles {
         (first_key, last_key)
     }
 
-    /// save file chunk
-    pub fn save_file_chunk(&self, file_id: u64, index: u32, data: Vec<u8>) {
+    /// mark a file chunk as received
+    ///
+    /// Returns true if the chunk was not marked before.
+    pub fn mark_file_chunk(&self, file_id: u64, index: u32) -> bool {
         // get chunk key
         let key = Self::get_chunk_key(&file_id.to_be_bytes().to_vec(), index);
 
-        log::trace!("save file chunk {} with key: {:?}", index, key);
+        log::trace!("mark file chunk {} with key: {:?}", index, key);
 
-        // save file chunk into data base
-        if let Err(e) = self.file_chunks.insert(key, data) {
-            log::error!("Error saving file chunk to data base: {}", e);
-            return;
+        // save file chunk marker into data base
+        let new_chunk;
+        match self.file_chunks.insert(key, Vec::new()) {
+            Ok(previous) => new_chunk = previous.is_none(),
+            Err(e) => {
+                log::error!("Error saving file chunk to data base: {}", e);
+                return false;
+            }
         }
 
         // flush trees to disk
         if let Err(e) = self.file_chunks.flush() {
             log::error!("Error file history flush: {}", e);
         }
+
+        new_chunk
     }
 
     /// count file chunks
     ///
-    /// Count how many chunks of a file we already have in the data base
+    /// Count how many chunks of a file we already have received
     pub fn count_file_chunks(&self, file_id: &Vec<u8>) -> usize {
         // get key range
         let (first_key, last_key) = Self::get_chunk_key_range(file_id);
@@ -168,15 +330,54 @@ impl UserFiles {
         result.count()
     }
 
-    /// get all file chunks for a specific id
-    pub fn get_file_chunks(&self, file_id: &Vec<u8>) -> Iter<Vec<u8>, BincodeEncoding> {
-        // get key range
-        let (first_key, last_key) = Self::get_chunk_key_range(file_id);
+    /// remove the marker of a received file chunk
+    pub fn unmark_file_chunk(&self, file_id: u64, index: u32) {
+        let key = Self::get_chunk_key(&file_id.to_be_bytes().to_vec(), index);
+        if let Err(e) = self.file_chunks.remove(key) {
+            log::error!("Error removing file chunk: {}", e);
+        }
+        if let Err(e) = self.file_chunks.flush() {
+            log::error!("Error file chunks flush: {}", e);
+        }
+    }
 
-        // get results from data base
-        let result = self.file_chunks.range(first_key..last_key);
+    /// get the indexes of the missing chunks of a file
+    ///
+    /// Returns maximally `max` indexes.
+    pub fn missing_file_chunks(&self, file_id: u64, chunk_count: u32, max: usize) -> Vec<u32> {
+        let file_id_bytes = file_id.to_be_bytes().to_vec();
+        let mut missing: Vec<u32> = Vec::new();
+
+        for index in 0..chunk_count {
+            if missing.len() >= max {
+                break;
+            }
+            let key = Self::get_chunk_key(&file_id_bytes, index);
+            match self.file_chunks.contains_key(key) {
+                Ok(true) => {}
+                Ok(false) => missing.push(index),
+                Err(e) => log::error!("{}", e),
+            }
+        }
 
-        result
+        missing
+    }
+
+    /// remove all chunks of a file
+    pub fn remove_file_chunks(&self, file_id: u64) {
+        let (first_key, last_key) = Self::get_chunk_key_range(&file_id.to_be_bytes().to_vec());
+
+        for result in self.file_chunks.range(first_key..last_key) {
+            if let Ok((key, _chunk)) = result {
+                if let Err(e) = self.file_chunks.remove(key) {
+                    log::error!("Error removing file chunk: {}", e);
+                }
+            }
+        }
+
+        if let Err(e) = self.file_chunks.flush() {
+            log::error!("Error file chunks flush: {}", e);
+        }
     }
 }
 
@@ -195,6 +396,32 @@ pub enum FileState {
     Receiving,
     /// File successfully received
     Received,
+    /// Sending failed
+    Failed,
+    /// The received file content did not match its hash
+    VerificationFailed,
+    /// Reception is paused
+    Paused,
+    /// Transfer was cancelled
+    Cancelled,
+}
+
+impl FileState {
+    /// convert to the RPC file history state
+    pub fn to_rpc(&self) -> proto_rpc::FileHistoryState {
+        match self {
+            FileState::Sending => proto_rpc::FileHistoryState::FileSending,
+            FileState::Sent => proto_rpc::FileHistoryState::FileSent,
+            FileState::Confirmed => proto_rpc::FileHistoryState::FileConfirmed,
+            FileState::ConfirmedByAll => proto_rpc::FileHistoryState::FileConfirmedByAll,
+            FileState::Receiving => proto_rpc::FileHistoryState::FileReceiving,
+            FileState::Received => proto_rpc::FileHistoryState::FileReceived,
+            FileState::Failed => proto_rpc::FileHistoryState::FileFailed,
+            FileState::VerificationFailed => proto_rpc::FileHistoryState::FileVerificationFailed,
+            FileState::Paused => proto_rpc::FileHistoryState::FilePaused,
+            FileState::Cancelled => proto_rpc::FileHistoryState::FileCancelled,
+        }
+    }
 }
 
 /// User Reception Tracking
@@ -232,14 +459,71 @@ pub struct FileHistory {
     /// file extension
     pub file_extension: String,
     /// file size in bytes
-    pub file_size: u32,
+    pub file_size: u64,
     /// file sent
     pub sent_at: u64,
     /// file received
     pub received_at: u64,
+    /// SHA-256 hash of the file content
+    ///
+    /// Empty for files of senders not providing a hash.
+    pub file_hash: Vec<u8>,
+}
+
+/// MIME type and preview of a file
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FileMedia {
+    /// MIME type of the file
+    pub mime_type: String,
+    /// small preview of the file, empty if none
+    pub preview: Vec<u8>,
 }
 
 impl FileHistory {
+    /// number of data chunks of the file
+    ///
+    /// The message count includes the file info message.
+    pub fn chunk_count(&self) -> u32 {
+        if self.message_count > 0 {
+            return self.message_count - 1;
+        }
+        0
+    }
+
+    /// expected size of a data chunk in bytes
+    ///
+    /// All chunks have the chunk size, except for the last
+    /// chunk, which contains the rest of the file.
+    /// Returns None for chunk indexes outside of the file.
+    pub fn chunk_length(&self, index: u32) -> Option<u64> {
+        if index >= self.chunk_count() {
+            return None;
+        }
+        let offset = index as u64 * self.chunk_size as u64;
+        if offset >= self.file_size {
+            return None;
+        }
+        Some((self.file_size - offset).min(self.chunk_size as u64))
+    }
+
+    /// number of data chunks confirmed by all receivers
+    pub fn chunks_confirmed(&self) -> u32 {
+        let mut confirmed: Option<u32> = None;
+        for tracking in self.reception_tracking.values() {
+            // the package count includes the file info message
+            let chunks = tracking.package_count.saturating_sub(1);
+            match confirmed {
+                Some(current) if current <= chunks => {}
+                _ => confirmed = Some(chunks),
+            }
+        }
+
+        match confirmed {
+            Some(chunks) => chunks.min(self.chunk_count()),
+            None => 0,
+        }
+    }
+
     /// the reception of a file message has successfully been confirmed
     ///
     /// the function returns a boolean that indicates, whether the user finished receiving
@@ -285,6 +569,8 @@ impl ChatFile {
         // create file history state
         let all_files = AllFiles {
             db_ref: BTreeMap::new(),
+            activity: BTreeMap::new(),
+            early_chunks: BTreeMap::new(),
         };
         ALLFILES.set(RwLock::new(all_files));
     }
@@ -302,6 +588,8 @@ impl ChatFile {
                 return UserFiles {
                     histories: user_files.histories.clone(),
                     file_chunks: user_files.file_chunks.clone(),
+                    media: user_files.media.clone(),
+                    chunk_hashes: user_files.chunk_hashes.clone(),
                 };
             }
         }
@@ -313,6 +601,8 @@ impl ChatFile {
         UserFiles {
             histories: user_files.histories.clone(),
             file_chunks: user_files.file_chunks.clone(),
+            media: user_files.media.clone(),
+            chunk_hashes: user_files.chunk_hashes.clone(),
         }
     }
 
@@ -324,12 +614,20 @@ impl ChatFile {
         // open trees
         let histories: Tree<FileHistory> = db.open_bincode_tree("chat_file").unwrap();
         let file_chunks: Tree<Vec<u8>> = db.open_bincode_tree("file_chunks").unwrap();
+        let media: Tree<FileMedia> = db.open_bincode_tree("chat_file_media").unwrap();
+        let chunk_hashes: Tree<Vec<Vec<u8>>> =
+            db.open_bincode_tree("chat_file_chunk_hashes").unwrap();
 
         let user_files = UserFiles {
             histories,
             file_chunks,
+            media,
+            chunk_hashes,
         };
 
+        // move chunks buffered in the data base to the partial files
+        Self::migrate_file_chunks(user_id, &user_files);
+
         // get chat state for writing
         let mut all_files = ALLFILES.get().write().unwrap();
 
@@ -342,11 +640,50 @@ impl ChatFile {
         user_files
     }
 
+    /// Move file chunks to the partial files
+    ///
+    /// Before the chunks were streamed to disk, the incoming chunk
+    /// data was stored in the data base until the file was complete.
+    /// The data is written to the partial files and only the
+    /// chunk markers are kept.
+    fn migrate_file_chunks(user_id: &PeerId, user_files: &UserFiles) {
+        for result in user_files.file_chunks.iter() {
+            if let Ok((key, chunk)) = result {
+                if chunk.len() == 0 || key.len() != 12 {
+                    continue;
+                }
+                let file_id = u64::from_be_bytes(key[0..8].try_into().unwrap());
+                let index = u32::from_be_bytes(key[8..12].try_into().unwrap());
+
+                match user_files.get_filehistory(file_id) {
+                    Some(file_history) => {
+                        if let Err(e) = Self::write_chunk(user_id, &file_history, index, &chunk) {
+                            log::error!("file chunk migration error {}", e);
+                            continue;
+                        }
+                        if let Err(e) = user_files.file_chunks.insert(key, Vec::new()) {
+                            log::error!("Error saving file chunk to data base: {}", e);
+                        }
+                    }
+                    None => {
+                        if let Err(e) = user_files.file_chunks.remove(key) {
+                            log::error!("Error removing file chunk: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = user_files.file_chunks.flush() {
+            log::error!("Error file chunks flush: {}", e);
+        }
+    }
+
     /// Update file message confirmation in data base
     pub fn update_confirmation(
         account_id: PeerId,
         receiver_id: PeerId,
-        file_id: u64,
+        message_id: &Vec<u8>,
         received_at: u64,
     ) {
         log::trace!("update confirmation");
@@ -355,7 +692,13 @@ impl ChatFile {
         let user_files = ChatFile::get_db_ref(&account_id);
 
         // get file history
-        if let Some(mut file_history) = user_files.get_filehistory(file_id) {
+        if let Some(mut file_history) = user_files.get_filehistory_by_message_id(message_id) {
+            if let FileState::Cancelled = file_history.file_state {
+                return;
+            }
+            let file_id = file_history.file_id;
+            let confirmed = file_history.chunks_confirmed();
+
             // update reception & check if user finished
             if file_history.reception_confirmed(receiver_id) {
                 // update chat message
@@ -367,11 +710,131 @@ impl ChatFile {
                 );
             }
 
+            // inform UI about the progress
+            if file_history.chunks_confirmed() != confirmed {
+                Self::send_progress(&file_history, file_history.chunk_count());
+            }
+
             // save file history
             user_files.save_filehistory(file_id, file_history);
         }
     }
 
+    /// sending of a file failed
+    ///
+    /// The file and its chat message are marked as failed.
+    /// Returns the group id of the file.
+    pub fn update_failed(account_id: PeerId, message_id: &Vec<u8>) -> Option<Vec<u8>> {
+        // get db reference
+        let user_files = ChatFile::get_db_ref(&account_id);
+
+        // get file history
+        if let Some(mut file_history) = user_files.get_filehistory_by_message_id(message_id) {
+            // a cancelled file stays cancelled
+            if let FileState::Cancelled = file_history.file_state {
+                return Some(file_history.group_id);
+            }
+            let file_id = file_history.file_id;
+
+            // update chat message
+            ChatStorage::udate_status(
+                &account_id,
+                &file_history.message_id,
+                super::rpc_proto::MessageStatus::Failed,
+            );
+
+            // save file history
+            let group_id = file_history.group_id.clone();
+            file_history.file_state = FileState::Failed;
+            user_files.save_filehistory(file_id, file_history);
+
+            return Some(group_id);
+        }
+
+        None
+    }
+
+    /// calculate the SHA-256 hash of a file
+    fn hash_file<P: AsRef<Path>>(path_name: P) -> Result<Vec<u8>, String> {
+        let mut file;
+        match File::open(path_name) {
+            Ok(f) => file = f,
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let mut hasher = Sha256::new();
+        let mut buffer: [u8; DEF_PACKAGE_SIZE as usize] = [0; DEF_PACKAGE_SIZE as usize];
+        loop {
+            match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read_size) => hasher.update(&buffer[0..read_size]),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        Ok(hasher.finalize().to_vec())
+    }
+
+    /// calculate the SHA-256 hashes of a file and of its data chunks
+    ///
+    /// Returns the hash of the entire file and the list
+    /// of the chunk hashes.
+    fn hash_file_chunks<P: AsRef<Path>>(
+        path_name: P,
+        chunk_size: u32,
+    ) -> Result<(Vec<u8>, Vec<Vec<u8>>), String> {
+        let file;
+        match File::open(path_name) {
+            Ok(f) => file = f,
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let mut file_hasher = Sha256::new();
+        let mut chunk_hashes: Vec<Vec<u8>> = Vec::new();
+        let mut reader = file.take(0);
+        loop {
+            let mut chunk: Vec<u8> = Vec::new();
+            reader.set_limit(chunk_size as u64);
+            match reader.read_to_end(&mut chunk) {
+                Ok(0) => break,
+                Ok(_) => {
+                    file_hasher.update(&chunk);
+                    chunk_hashes.push(Sha256::digest(&chunk).to_vec());
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        Ok((file_hasher.finalize().to_vec(), chunk_hashes))
+    }
+
+    /// get the MIME type of a file from its extension
+    ///
+    /// Returns a generic binary type for unknown extensions.
+    fn mime_type_from_extension(extension: &str) -> String {
+        let mime_type = match extension.to_lowercase().as_str() {
+            "jpg" | "jpeg" => "image/jpeg",
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "svg" => "image/svg+xml",
+            "mp4" => "video/mp4",
+            "webm" => "video/webm",
+            "mp3" => "audio/mpeg",
+            "ogg" | "oga" => "audio/ogg",
+            "m4a" => "audio/mp4",
+            "wav" => "audio/wav",
+            "pdf" => "application/pdf",
+            "zip" => "application/zip",
+            "txt" => "text/plain",
+            "md" => "text/markdown",
+            "html" | "htm" => "text/html",
+            "json" => "application/json",
+            _ => "application/octet-stream",
+        };
+        mime_type.to_string()
+    }
+
     /// getting file extension from given filename
     fn get_extension_from_filename(filename: &str) -> Option<&str> {
         Path::new(filename).extension().and_then(OsStr::to_str)
@@ -399,12 +862,69 @@ impl ChatFile {
         files_storage_path.join(file_name.clone())
     }
 
+    /// Create and return the path of the partial file
+    ///
+    /// Incoming chunks are written to the partial file
+    /// until the file is complete.
+    fn create_partial_file_path(account_id: PeerId, file_id: u64, file_extension: &str) -> PathBuf {
+        let mut file_path =
+            Self::create_file_path(account_id, file_id, file_extension).into_os_string();
+        file_path.push(".part");
+        PathBuf::from(file_path)
+    }
+
+    /// write a chunk to the partial file
+    ///
+    /// The chunk is written at its position in the file.
+    /// Missing chunks leave holes in the sparse file.
+    /// Chunks outside of the file or of the wrong size are rejected.
+    fn write_chunk(
+        account_id: &PeerId,
+        file_history: &FileHistory,
+        index: u32,
+        data: &Vec<u8>,
+    ) -> Result<(), String> {
+        match file_history.chunk_length(index) {
+            Some(length) => {
+                if data.len() as u64 != length {
+                    return Err(format!("chunk {} has the wrong size", index));
+                }
+            }
+            None => return Err(format!("chunk {} is outside of the file", index)),
+        }
+
+        let file_path = Self::create_partial_file_path(
+            account_id.to_owned(),
+            file_history.file_id,
+            &file_history.file_extension,
+        );
+
+        let mut file;
+        match OpenOptions::new().write(true).create(true).open(file_path) {
+            Ok(f) => file = f,
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let offset = index as u64 * file_history.chunk_size as u64;
+        if let Err(e) = file.seek(SeekFrom::Start(offset)) {
+            return Err(e.to_string());
+        }
+        if let Err(e) = file.write_all(data) {
+            return Err(e.to_string());
+        }
+
+        Ok(())
+    }
+
     /// Getting file histories from table.
     /// This function is called from RPC command (file history [offset limit])
+    ///
+    /// The file histories can be filtered by group and sender.
+    /// Returns the requested page and the total number of matching files.
     pub fn file_history(
         user_account: &UserAccount,
         history_req: &proto_rpc::FileHistoryRequest,
-    ) -> Vec<FileHistory> {
+    ) -> (Vec<FileHistory>, u64) {
         // get DB references
         let db_ref = Self::get_db_ref(&user_account.id);
 
@@ -417,13 +937,19 @@ impl ChatFile {
         for history_result in iterator {
             match history_result {
                 Ok((_id, message)) => {
-                    // stop when we reached our limit
-                    if counter >= history_req.offset + history_req.limit {
-                        break;
+                    // filter by group and sender
+                    if history_req.group_id.len() > 0 && message.group_id != history_req.group_id {
+                        continue;
+                    }
+                    if history_req.sender_id.len() > 0 && message.sender_id != history_req.sender_id
+                    {
+                        continue;
                     }
 
                     // check if we collect the result
-                    if counter >= history_req.offset {
+                    if counter >= history_req.offset
+                        && counter < history_req.offset + history_req.limit
+                    {
                         histories.push(message);
                     }
 
@@ -433,7 +959,149 @@ impl ChatFile {
             }
         }
 
-        histories
+        (histories, counter as u64)
+    }
+
+    /// delete a file
+    ///
+    /// The file is removed from disk together with its
+    /// file history entry and all received chunks.
+    fn delete(account_id: &PeerId, file_id: u64) -> Result<(), String> {
+        let user_files = Self::get_db_ref(account_id);
+        let file_history;
+        match user_files.get_filehistory(file_id) {
+            Some(v) => file_history = v,
+            None => return Err("file not found".to_string()),
+        }
+
+        // stop the retransmission of our own files
+        if file_history.sender_id == account_id.to_bytes() {
+            Messaging::cancel_messages(account_id, &vec![file_history.message_id.clone()]);
+        }
+
+        // remove files from disk
+        let file_path =
+            Self::create_file_path(account_id.to_owned(), file_id, &file_history.file_extension);
+        let partial_path = Self::create_partial_file_path(
+            account_id.to_owned(),
+            file_id,
+            &file_history.file_extension,
+        );
+        for path in [file_path, partial_path] {
+            if path.exists() {
+                if let Err(e) = fs::remove_file(path) {
+                    return Err(e.to_string());
+                }
+            }
+        }
+
+        // remove data base entries
+        user_files.remove_file_chunks(file_id);
+        Self::remove_activity(account_id, file_id);
+        if let Err(e) = user_files.media.remove(file_id.to_be_bytes().to_vec()) {
+            log::error!("{}", e);
+        }
+        if let Err(e) = user_files
+            .chunk_hashes
+            .remove(file_id.to_be_bytes().to_vec())
+        {
+            log::error!("{}", e);
+        }
+        if let Err(e) = user_files.histories.remove(file_id.to_be_bytes().to_vec()) {
+            return Err(e.to_string());
+        }
+        if let Err(e) = user_files.histories.flush() {
+            log::error!("Error file history flush: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// get the file storage usage per group
+    ///
+    /// The size is the size of the stored and partial files on disk.
+    fn storage_usage(account_id: &PeerId) -> Vec<proto_rpc::FileStorageGroup> {
+        let user_files = Self::get_db_ref(account_id);
+        let mut groups: BTreeMap<Vec<u8>, proto_rpc::FileStorageGroup> = BTreeMap::new();
+
+        for entry in user_files.get_filehistory_iterator() {
+            if let Ok((_key, file_history)) = entry {
+                let file_path = Self::create_file_path(
+                    account_id.to_owned(),
+                    file_history.file_id,
+                    &file_history.file_extension,
+                );
+                let partial_path = Self::create_partial_file_path(
+                    account_id.to_owned(),
+                    file_history.file_id,
+                    &file_history.file_extension,
+                );
+                let mut size: u64 = 0;
+                for path in [file_path, partial_path] {
+                    if let Ok(metadata) = fs::metadata(path) {
+                        size = size + metadata.len();
+                    }
+                }
+
+                let group = groups.entry(file_history.group_id.clone()).or_insert(
+                    proto_rpc::FileStorageGroup {
+                        group_id: file_history.group_id.clone(),
+                        file_count: 0,
+                        size: 0,
+                    },
+                );
+                group.file_count = group.file_count + 1;
+                group.size = group.size + size;
+            }
+        }
+
+        groups.into_values().collect()
+    }
+
+    /// get the chunk size for sending a file to a group
+    ///
+    /// The chunk size is chosen from the connection module of the
+    /// best route to every group member. Members without route are
+    /// checked for being a BLE neighbour, as BLE neighbours are not
+    /// contained in the routing table.
+    /// The smallest chunk size of all reachable members is used.
+    /// If no member is reachable, the file is sent via DTN and
+    /// the largest configured size is used.
+    fn get_chunk_size(account_id: &PeerId, group: &Group) -> u32 {
+        let options = Configuration::get().files.clone();
+        let mut chunk_size: Option<u32> = None;
+
+        for user_id in group.members.keys() {
+            if *user_id == account_id.to_bytes() {
+                continue;
+            }
+            if let Ok(receiver) = PeerId::from_bytes(user_id) {
+                let module = match RoutingTable::get_route_to_user(receiver) {
+                    Some(route) => route.module,
+                    None => Neighbours::is_neighbour(&receiver),
+                };
+                let size = match module {
+                    ConnectionModule::Local | ConnectionModule::Lan => options.chunk_size_lan,
+                    ConnectionModule::Internet => options.chunk_size_internet,
+                    ConnectionModule::Ble => options.chunk_size_ble,
+                    ConnectionModule::None => continue,
+                };
+                match chunk_size {
+                    Some(current) if current <= size => {}
+                    _ => chunk_size = Some(size),
+                }
+            }
+        }
+
+        let size = match chunk_size {
+            Some(size) => size,
+            None => options
+                .chunk_size_lan
+                .max(options.chunk_size_internet)
+                .max(options.chunk_size_ble),
+        };
+
+        size.clamp(MIN_PACKAGE_SIZE, DEF_PACKAGE_SIZE)
     }
 
     /// send a file from RPC to users
@@ -442,7 +1110,14 @@ impl ChatFile {
         group_id: &Vec<u8>,
         path_name: String,
         description: String,
+        mime_type: String,
+        preview: Vec<u8>,
     ) -> Result<bool, String> {
+        // check preview size
+        if preview.len() > MAX_PREVIEW_SIZE {
+            return Err("file preview is too big".to_string());
+        }
+
         // get group
         let group;
         match GroupStorage::get_group(user_account.id, group_id.to_owned()) {
@@ -489,7 +1164,7 @@ impl ChatFile {
             }
         };
 
-        let size = file.metadata().unwrap().len() as u32;
+        let size = file.metadata().unwrap().len();
         if size == 0 {
             return Err("file size is zero".to_string());
         }
@@ -506,13 +1181,30 @@ impl ChatFile {
 
         let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
 
+        // choose chunk size for the transports to the group members
+        let chunk_size = Self::get_chunk_size(&user_account.id, &group);
+
+        // get MIME type
+        let mut media = FileMedia {
+            mime_type: match mime_type.len() {
+                0 => Self::mime_type_from_extension(&extension),
+                _ => mime_type,
+            },
+            preview,
+        };
+
+        // the preview is sent within the file info message,
+        // which needs to fit into a chunk
+        if media.preview.len() > chunk_size as usize {
+            log::warn!("file preview is too big for the chunk size, it is not sent");
+            media.preview = Vec::new();
+        }
+
+        // hash file content
+        let (file_hash, chunk_hashes) = Self::hash_file_chunks(&path_name, chunk_size)?;
+
         // create file id
-        let file_id = Self::generate_file_id(
-            group_id,
-            &user_account.id.to_bytes(),
-            file_name.clone(),
-            size,
-        );
+        let file_id = Self::generate_file_id(group_id, &user_account.id.to_bytes(), &file_hash);
 
         // get file path
         let file_path = Self::create_file_path(user_account.id, file_id, extension.as_str());
@@ -525,8 +1217,8 @@ impl ChatFile {
         }
 
         // create messages
-        let mut mesage_count = 1 + size / DEF_PACKAGE_SIZE;
-        if size % DEF_PACKAGE_SIZE > 0 {
+        let mut mesage_count = 1 + (size / chunk_size as u64) as u32;
+        if size % chunk_size as u64 > 0 {
             mesage_count = mesage_count + 1;
         }
 
@@ -542,7 +1234,12 @@ impl ChatFile {
             file_description: description.clone(),
             start_index: 0,
             message_count: mesage_count,
-            data_chunk_size: DEF_PACKAGE_SIZE,
+            data_chunk_size: chunk_size,
+            file_hash: file_hash.clone(),
+            version: FILE_INFO_VERSION,
+            mime_type: media.mime_type.clone(),
+            preview: media.preview.clone(),
+            chunk_hashes: chunk_hashes.clone(),
         };
 
         let info = proto_net::ChatFileContainer {
@@ -558,11 +1255,27 @@ impl ChatFile {
             &message_id,
             timestamp,
             info.encode_to_vec(),
+            None,
         );
 
         // create group ID object
         let groupid = GroupId::from_bytes(group_id).unwrap();
 
+        // track the reception of every member
+        let mut reception_tracking: BTreeMap<Vec<u8>, ReceptionTracking> = BTreeMap::new();
+        for user_id in group.members.keys() {
+            if *user_id == user_account.id.to_bytes() {
+                continue;
+            }
+            reception_tracking.insert(
+                user_id.to_owned(),
+                ReceptionTracking {
+                    received: false,
+                    package_count: 0,
+                },
+            );
+        }
+
         // save file state to data base
         let file_history = FileHistory {
             group_id: group_id.to_owned(),
@@ -571,15 +1284,16 @@ impl ChatFile {
             message_id: message_id.clone(),
             start_index: file_info.start_index,
             message_count: file_info.message_count,
-            chunk_size: DEF_PACKAGE_SIZE,
+            chunk_size,
             file_state: FileState::Sending,
-            reception_tracking: BTreeMap::new(),
+            reception_tracking,
             file_name: file_name.clone(),
             file_description: description.clone(),
             file_extension: extension.clone(),
             file_size: size,
             sent_at: timestamp,
             received_at: 0,
+            file_hash,
         };
 
         let db_ref = Self::get_db_ref(&user_account.id);
@@ -596,54 +1310,74 @@ impl ChatFile {
             }
         }
 
+        // save file media
+        db_ref.save_media(file_id, media.clone());
+        db_ref.save_chunk_hashes(file_id, chunk_hashes);
+
         // save file message to chat conversation
         Self::save_filemsg_in_chat(
             user_account,
             &user_account.id,
             &groupid,
             &file_history,
+            &media,
             super::rpc_proto::MessageStatus::Sending,
         );
 
         // 2. file data message
         // read file contents and create and send FileData messages
-        let mut buffer: [u8; DEF_PACKAGE_SIZE as usize] = [0; DEF_PACKAGE_SIZE as usize];
+        let options = Configuration::get().files.clone();
+        let multipath = options.multipath && size >= options.multipath_size_min;
+        let mut buffer: Vec<u8> = vec![0; chunk_size as usize];
         let mut left_size = size;
         let mut chunk_index: u32 = 0;
 
         while left_size > 0 {
             let mut read_size = left_size;
-            if left_size > DEF_PACKAGE_SIZE {
-                read_size = DEF_PACKAGE_SIZE;
+            if left_size > chunk_size as u64 {
+                read_size = chunk_size as u64;
             };
             left_size = left_size - read_size;
 
-            if let Err(e) = file.read(&mut buffer) {
+            if let Err(e) = file.read_exact(&mut buffer[0..(read_size as usize)]) {
                 return Err(e.to_string());
             }
 
             // pack chat file container
+            let chunk: Vec<u8> = buffer[0..(read_size as usize)].iter().cloned().collect();
             let data = proto_net::ChatFileContainer {
                 message: Some(proto_net::chat_file_container::Message::FileData(
                     proto_net::ChatFileData {
                         file_id,
                         start_index: chunk_index,
                         message_count: mesage_count,
-                        data: buffer[0..(read_size as usize)].iter().cloned().collect(),
+                        data_hash: Sha256::digest(&chunk).to_vec(),
+                        data: chunk,
                     },
                 )),
             };
 
             // send message to all group members
+            // large files are split across the disjoint routes
+            let mut path = None;
+            if multipath {
+                path = Some(chunk_index as usize);
+            }
             Self::send_filecontainer_to_group(
                 user_account,
                 &group,
                 &message_id,
                 timestamp,
                 data.encode_to_vec(),
+                path,
             );
 
             chunk_index = chunk_index + 1;
+
+            // inform UI about the progress
+            if Self::is_progress_step(chunk_index, file_history.chunk_count()) {
+                Self::send_progress(&file_history, chunk_index);
+            }
         }
 
         // set file status to sent
@@ -664,6 +1398,7 @@ impl ChatFile {
         sender_id: &PeerId,
         group_id: &GroupId,
         file_history: &FileHistory,
+        media: &FileMedia,
         status: super::rpc_proto::MessageStatus,
     ) {
         log::trace!("save_filemsg_in_chat");
@@ -675,6 +1410,8 @@ impl ChatFile {
             file_extension: file_history.file_extension.clone(),
             file_size: file_history.file_size,
             file_description: file_history.file_description.clone(),
+            mime_type: media.mime_type.clone(),
+            preview: media.preview.clone(),
         };
         let chat_message = super::rpc_proto::ChatContentMessage {
             message: Some(
@@ -695,12 +1432,17 @@ impl ChatFile {
     }
 
     /// Pack a FileContainer message and send it to all Group members
+    ///
+    /// If a path is given, the message is sent to every member
+    /// on the disjoint route at this position.
+    /// Otherwise it is sent on the best route.
     fn send_filecontainer_to_group(
         user_account: &UserAccount,
         group: &Group,
         message_id: &Vec<u8>,
         timestamp: u64,
         data: Vec<u8>,
+        path: Option<usize>,
     ) {
         // pack file container into common message
         let common_message = messaging::proto::CommonMessage {
@@ -725,13 +1467,19 @@ impl ChatFile {
                 continue;
             }
 
-            if let Err(error) = Messaging::pack_and_send_message(
+            let mut route_index = 0;
+            if let Some(path) = path {
+                route_index = RoutingTable::get_disjoint_route_index(receiver, path);
+            }
+
+            if let Err(error) = Messaging::pack_and_send_message_on_route(
                 user_account,
                 &receiver,
                 message.encode_to_vec(),
                 MessagingServiceType::ChatFile,
                 message_id,
                 true,
+                route_index,
             ) {
                 log::error!("sending file message error {}", error);
             }
@@ -739,18 +1487,17 @@ impl ChatFile {
     }
 
     /// Generate File id
-    fn generate_file_id(group_id: &Vec<u8>, sender: &Vec<u8>, file_name: String, size: u32) -> u64 {
-        let mut name_bytes = file_name.as_bytes().to_vec();
-        let mut size_bytes = size.to_be_bytes().to_vec();
-        let mut time_bytes = timestamp::Timestamp::get_timestamp().to_be_bytes().to_vec();
-        let mut key_bytes = group_id.clone();
-        let mut sender_bytes = sender.clone();
-
-        key_bytes.append(&mut sender_bytes);
-        key_bytes.append(&mut name_bytes);
-        key_bytes.append(&mut size_bytes);
-        key_bytes.append(&mut time_bytes);
-        crc::Crc::<u64>::new(&crc::CRC_64_GO_ISO).checksum(&key_bytes)
+    ///
+    /// The file id is derived from the hash of the file content,
+    /// the group and the sender. The receivers check the file id
+    /// of files with chunk hashes.
+    fn generate_file_id(group_id: &Vec<u8>, sender: &Vec<u8>, file_hash: &Vec<u8>) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(group_id);
+        hasher.update(sender);
+        hasher.update(file_hash);
+        let hash = hasher.finalize();
+        u64::from_be_bytes(hash[0..8].try_into().unwrap())
     }
 
     /// Try to store the file
@@ -763,7 +1510,8 @@ impl ChatFile {
         file_history: FileHistory,
     ) {
         // check how many chunks have been downloaded
-        let count = user_files.count_file_chunks(&file_history.file_id.to_be_bytes().to_vec());
+        let count =
+            Self::received_chunk_count(&user_account.id, &user_files, file_history.file_id, false);
 
         log::trace!(
             "received {} chunks of {}",
@@ -772,54 +1520,786 @@ impl ChatFile {
         );
 
         // if we downloaded all chunks, save it to file if we received the file info
-        if (count + 1) as u32 == file_history.message_count {
+        if count + 1 == file_history.message_count {
             log::trace!("store_file");
 
             Self::store_file(user_account, user_files, file_history);
         }
     }
 
+    /// Mark a received file as corrupted
+    ///
+    /// The file history is set to the verification failed state
+    /// and the chat message to failed.
+    fn on_verification_failed(
+        user_account: &UserAccount,
+        user_files: &UserFiles,
+        mut file_history: FileHistory,
+    ) {
+        log::error!("file {} verification failed", file_history.file_id);
+
+        // set chat message status to failed
+        ChatStorage::udate_status(
+            &user_account.id,
+            &file_history.message_id,
+            super::rpc_proto::MessageStatus::Failed,
+        );
+
+        // save file history
+        file_history.file_state = FileState::VerificationFailed;
+        user_files.save_filehistory(file_history.file_id, file_history.clone());
+
+        // inform UI
+        Self::send_progress(&file_history, 0);
+    }
+
+    /// Verify the hash of a completely downloaded file
+    ///
+    /// Files without hash can not be verified and are accepted.
+    fn verify_file(partial_path: &PathBuf, file_history: &FileHistory) -> bool {
+        if file_history.file_hash.len() == 0 {
+            return true;
+        }
+
+        match Self::hash_file(partial_path) {
+            Ok(file_hash) => file_hash == file_history.file_hash,
+            Err(e) => {
+                log::error!("file hashing error {}", e);
+                false
+            }
+        }
+    }
+
     /// Store a completely downloaded file
-    fn store_file(user_account: &UserAccount, user_files: UserFiles, file_history: FileHistory) {
-        // get all chunks from data base
-        let iterator = user_files.get_file_chunks(&file_history.file_id.to_be_bytes().to_vec());
+    ///
+    /// The partial file is only moved to its final
+    /// location, if the file content matches the file hash.
+    fn store_file(
+        user_account: &UserAccount,
+        user_files: UserFiles,
+        mut file_history: FileHistory,
+    ) {
+        let partial_path = Self::create_partial_file_path(
+            user_account.id,
+            file_history.file_id,
+            &file_history.file_extension,
+        );
 
-        // create file
+        // empty files have no chunks
+        if !partial_path.exists() {
+            if let Err(e) = File::create(&partial_path) {
+                log::error!("file path error: {}", e);
+                return;
+            }
+        }
+
+        // verify file content
+        if !Self::verify_file(&partial_path, &file_history) {
+            Self::on_verification_failed(user_account, &user_files, file_history);
+            return;
+        }
+
+        // move the partial file to its final location
         let file_path = Self::create_file_path(
             user_account.id,
             file_history.file_id,
             &file_history.file_extension,
         );
+        if let Err(e) = fs::rename(&partial_path, &file_path) {
+            log::error!("file storing failed {}", e);
+            return;
+        }
 
-        // open a file in write mode
-        let mut file: File;
-        match File::create(file_path.clone()) {
-            Ok(my_file) => file = my_file,
+        // remove chunk markers
+        user_files.remove_file_chunks(file_history.file_id);
+
+        // set file status to received
+        ChatStorage::udate_status(
+            &user_account.id,
+            &file_history.message_id,
+            super::rpc_proto::MessageStatus::Received,
+        );
+
+        // save file history
+        file_history.file_state = FileState::Received;
+        user_files.save_filehistory(file_history.file_id, file_history.clone());
+        Self::remove_activity(&user_account.id, file_history.file_id);
+
+        // inform UI
+        Self::send_progress(&file_history, file_history.chunk_count());
+    }
+
+    /// get the activity key of a file
+    fn get_activity_key(account_id: &PeerId, file_id: u64) -> Vec<u8> {
+        let mut key = account_id.to_bytes();
+        key.extend(file_id.to_be_bytes());
+        key
+    }
+
+    /// update the reception activity of a file
+    ///
+    /// If `requested` is true, a chunk request was sent.
+    fn update_activity(account_id: &PeerId, file_id: u64, requested: bool) {
+        let key = Self::get_activity_key(account_id, file_id);
+        let now = Timestamp::get_timestamp();
+
+        let mut all_files = ALLFILES.get().write().unwrap();
+        let activity = all_files.activity.entry(key).or_insert(FileActivity {
+            last_at: now,
+            request_count: 0,
+            chunk_count: None,
+        });
+        activity.last_at = now;
+        if requested {
+            activity.request_count = activity.request_count + 1;
+        }
+    }
+
+    /// get the number of received chunks of a file
+    ///
+    /// If `new_chunk` is true, a new chunk was received.
+    /// The chunks are only counted in the data base,
+    /// if the reception activity holds no count yet.
+    fn received_chunk_count(
+        account_id: &PeerId,
+        user_files: &UserFiles,
+        file_id: u64,
+        new_chunk: bool,
+    ) -> u32 {
+        let key = Self::get_activity_key(account_id, file_id);
+
+        // running count
+        {
+            let mut all_files = ALLFILES.get().write().unwrap();
+            if let Some(activity) = all_files.activity.get_mut(&key) {
+                if let Some(count) = activity.chunk_count.as_mut() {
+                    if new_chunk {
+                        *count = *count + 1;
+                    }
+                    return *count;
+                }
+            }
+        }
+
+        // count the chunks in the data base
+        let count = user_files.count_file_chunks(&file_id.to_be_bytes().to_vec()) as u32;
+        let mut all_files = ALLFILES.get().write().unwrap();
+        if let Some(activity) = all_files.activity.get_mut(&key) {
+            activity.chunk_count = Some(count);
+        }
+
+        count
+    }
+
+    /// remove the reception activity of a file
+    fn remove_activity(account_id: &PeerId, file_id: u64) {
+        let key = Self::get_activity_key(account_id, file_id);
+
+        let mut all_files = ALLFILES.get().write().unwrap();
+        all_files.activity.remove(&key);
+    }
+
+    /// Request the missing chunks of all incomplete files
+    ///
+    /// The chunks are requested for files which did not
+    /// receive any chunk during the request timeout.
+    /// This function is called periodically from the main event loop.
+    pub fn request_missing_chunks() {
+        let now = Timestamp::get_timestamp();
+
+        // drop buffered chunks of files without file info
+        {
+            let mut all_files = ALLFILES.get().write().unwrap();
+            all_files.early_chunks.retain(|_key, early| {
+                now.saturating_sub(early.received_at) < CHUNK_REQUEST_TIMEOUT
+            });
+        }
+
+        for account_id in UserAccounts::get_ids() {
+            let user_files = Self::get_db_ref(&account_id);
+
+            // collect idle incomplete files
+            let mut idle: Vec<FileHistory> = Vec::new();
+            {
+                let all_files = ALLFILES.get().read().unwrap();
+                for entry in user_files.histories.iter() {
+                    if let Ok((_key, file_history)) = entry {
+                        match file_history.file_state {
+                            FileState::Receiving => {}
+                            _ => continue,
+                        }
+                        let key = Self::get_activity_key(&account_id, file_history.file_id);
+                        if let Some(activity) = all_files.activity.get(&key) {
+                            if now.saturating_sub(activity.last_at) < CHUNK_REQUEST_TIMEOUT {
+                                continue;
+                            }
+                        }
+                        idle.push(file_history);
+                    }
+                }
+            }
+
+            for file_history in idle {
+                Self::request_chunks(&account_id, &user_files, file_history);
+            }
+        }
+    }
+
+    /// request the missing chunks of a file
+    ///
+    /// The request is sent to the sender of the file or
+    /// one of the other online group members.
+    /// Every request is sent to the next member in turn.
+    fn request_chunks(account_id: &PeerId, user_files: &UserFiles, file_history: FileHistory) {
+        let user_account;
+        match UserAccounts::get_by_id(account_id.to_owned()) {
+            Some(v) => user_account = v,
+            None => return,
+        }
+
+        // check missing chunks
+        let missing = user_files.missing_file_chunks(
+            file_history.file_id,
+            file_history.chunk_count(),
+            CHUNK_REQUEST_MAX,
+        );
+        if missing.len() == 0 {
+            // all chunks are here, the storing was interrupted
+            Self::store_file(&user_account, user_files.to_owned(), file_history);
+            return;
+        }
+
+        // create bitmap
+        let mut bitmap: Vec<u8> = Vec::new();
+        for index in missing {
+            let byte = (index / 8) as usize;
+            if bitmap.len() <= byte {
+                bitmap.resize(byte + 1, 0);
+            }
+            bitmap[byte] = bitmap[byte] | (1 << (index % 8));
+        }
+
+        // get group
+        let group;
+        match GroupStorage::get_group(account_id.to_owned(), file_history.group_id.clone()) {
+            Some(v) => group = v,
+            None => return,
+        }
+
+        // create candidates list, the sender of the file first
+        let mut candidates: Vec<PeerId> = Vec::new();
+        if let Ok(sender_id) = PeerId::from_bytes(&file_history.sender_id) {
+            candidates.push(sender_id);
+        }
+        for user_id in group.members.keys() {
+            if let Ok(member_id) = PeerId::from_bytes(user_id) {
+                if member_id != *account_id && !candidates.contains(&member_id) {
+                    candidates.push(member_id);
+                }
+            }
+        }
+        if candidates.len() == 0 {
+            return;
+        }
+
+        // get the next online candidate
+        let request_count;
+        {
+            let all_files = ALLFILES.get().read().unwrap();
+            let key = Self::get_activity_key(account_id, file_history.file_id);
+            match all_files.activity.get(&key) {
+                Some(activity) => request_count = activity.request_count as usize,
+                None => request_count = 0,
+            }
+        }
+        let mut receiver: Option<PeerId> = None;
+        for i in 0..candidates.len() {
+            let candidate = candidates[(request_count + i) % candidates.len()];
+            if RoutingTable::get_route_to_user(candidate).is_some() {
+                receiver = Some(candidate);
+                break;
+            }
+        }
+
+        // mark as requested, even if nobody is online
+        Self::update_activity(account_id, file_history.file_id, true);
+
+        if let Some(receiver) = receiver {
+            log::trace!(
+                "request chunks of file {} from {}",
+                file_history.file_id,
+                receiver.to_base58()
+            );
+
+            let request = proto_net::ChatFileContainer {
+                message: Some(proto_net::chat_file_container::Message::ChunkRequest(
+                    proto_net::ChatFileChunkRequest {
+                        file_id: file_history.file_id,
+                        group_id: file_history.group_id.clone(),
+                        bitmap,
+                    },
+                )),
+            };
+
+            Self::send_filecontainer_to_user(
+                &user_account,
+                &receiver,
+                &group,
+                request.encode_to_vec(),
+            );
+        }
+    }
+
+    /// process a chunk request of a group member
+    ///
+    /// The requested chunks are sent, if we hold the entire file.
+    fn on_chunk_request(
+        user_account: &UserAccount,
+        sender_id: PeerId,
+        chunk_request: proto_net::ChatFileChunkRequest,
+    ) {
+        let user_files = Self::get_db_ref(&user_account.id);
+
+        let file_history;
+        match user_files.get_filehistory(chunk_request.file_id) {
+            Some(v) => file_history = v,
+            None => return,
+        }
+
+        // check group membership of the requester
+        if file_history.group_id != chunk_request.group_id {
+            return;
+        }
+        let group;
+        match GroupStorage::get_group(user_account.id, file_history.group_id.clone()) {
+            Some(v) => group = v,
+            None => return,
+        }
+        if !group.is_member(&sender_id.to_bytes()) {
+            log::warn!("chunk request from non group member");
+            return;
+        }
+
+        // check if we hold the entire file
+        let is_sender = file_history.sender_id == user_account.id.to_bytes();
+        match file_history.file_state {
+            FileState::Received => {}
+            FileState::Cancelled => return,
+            _ if is_sender => {}
+            _ => return,
+        }
+
+        // open file
+        let file_path = Self::create_file_path(
+            user_account.id,
+            file_history.file_id,
+            &file_history.file_extension,
+        );
+        let mut file;
+        match File::open(file_path) {
+            Ok(f) => file = f,
             Err(e) => {
-                log::error!("file path error: {}", e);
+                log::error!("file open error {}", e);
                 return;
             }
         }
 
-        // loop over all chunks and write them to the data base
-        for result in iterator {
-            match result {
-                Ok((_key, chunk)) => {
-                    // write chunk to file
-                    if let Err(e) = file.write(&chunk) {
-                        log::error!("file storing failed {}", e.to_string());
+        // send requested chunks
+        let mut sent = 0;
+        for index in 0..file_history.chunk_count() {
+            if sent >= CHUNK_REQUEST_MAX {
+                break;
+            }
+            let byte = (index / 8) as usize;
+            if byte >= chunk_request.bitmap.len() {
+                break;
+            }
+            if chunk_request.bitmap[byte] & (1 << (index % 8)) == 0 {
+                continue;
+            }
+
+            // read chunk
+            let offset = index as u64 * file_history.chunk_size as u64;
+            if let Err(e) = file.seek(SeekFrom::Start(offset)) {
+                log::error!("file seek error {}", e);
+                return;
+            }
+            let mut chunk: Vec<u8> = Vec::new();
+            if let Err(e) = (&mut file)
+                .take(file_history.chunk_size as u64)
+                .read_to_end(&mut chunk)
+            {
+                log::error!("file read error {}", e);
+                return;
+            }
+
+            let data = proto_net::ChatFileContainer {
+                message: Some(proto_net::chat_file_container::Message::FileData(
+                    proto_net::ChatFileData {
+                        file_id: file_history.file_id,
+                        start_index: index,
+                        message_count: file_history.message_count,
+                        data_hash: Sha256::digest(&chunk).to_vec(),
+                        data: chunk,
+                    },
+                )),
+            };
+
+            Self::send_filecontainer_to_user(
+                user_account,
+                &sender_id,
+                &group,
+                data.encode_to_vec(),
+            );
+            sent = sent + 1;
+        }
+    }
+
+    /// Pack a FileContainer message and send it to one group member
+    ///
+    /// These messages are not retransmitted and not sent via DTN,
+    /// lost chunks are requested again.
+    fn send_filecontainer_to_user(
+        user_account: &UserAccount,
+        receiver: &PeerId,
+        group: &Group,
+        data: Vec<u8>,
+    ) {
+        // create a new message id
+        let message_id = GroupManage::get_new_message_id(&user_account.id, &group.id);
+        if message_id.len() == 0 {
+            return;
+        }
+
+        // pack file container into common message
+        let common_message = messaging::proto::CommonMessage {
+            message_id: message_id.clone(),
+            group_id: group.id.clone(),
+            sent_at: Timestamp::get_timestamp(),
+            payload: Some(messaging::proto::common_message::Payload::FileMessage(
+                messaging::proto::FileMessage { content: data },
+            )),
+        };
+
+        let message = messaging::proto::Messaging {
+            message: Some(messaging::proto::messaging::Message::CommonMessage(
+                common_message,
+            )),
+        };
+
+        if let Err(error) = Messaging::pack_and_send_message(
+            user_account,
+            receiver,
+            message.encode_to_vec(),
+            MessagingServiceType::ChatFile,
+            &message_id,
+            false,
+        ) {
+            log::error!("sending file message error {}", error);
+        }
+    }
+
+    /// pause the reception of a file
+    fn pause(account_id: &PeerId, file_id: u64) -> Result<(), String> {
+        let user_files = Self::get_db_ref(account_id);
+        match user_files.get_filehistory(file_id) {
+            Some(mut file_history) => match file_history.file_state {
+                FileState::Receiving => {
+                    file_history.file_state = FileState::Paused;
+                    user_files.save_filehistory(file_id, file_history);
+                    Ok(())
+                }
+                _ => Err("file is not receiving".to_string()),
+            },
+            None => Err("file not found".to_string()),
+        }
+    }
+
+    /// resume the reception of a file
+    ///
+    /// The chunks of a file that failed the verification
+    /// are removed and requested again.
+    fn resume(account_id: &PeerId, file_id: u64) -> Result<(), String> {
+        let user_files = Self::get_db_ref(account_id);
+        match user_files.get_filehistory(file_id) {
+            Some(mut file_history) => {
+                match file_history.file_state {
+                    FileState::Paused | FileState::Receiving => {}
+                    FileState::VerificationFailed => {
+                        if file_history.sender_id == account_id.to_bytes() {
+                            return Err("file was sent by us".to_string());
+                        }
+                        Self::remove_activity(account_id, file_id);
+                        let partial_path = Self::create_partial_file_path(
+                            account_id.to_owned(),
+                            file_id,
+                            &file_history.file_extension,
+                        );
+                        let chunk_hashes = user_files.get_chunk_hashes(file_id);
+                        if chunk_hashes.len() > 0 {
+                            // only the corrupted chunks are requested again
+                            Self::remove_corrupted_chunks(
+                                &user_files,
+                                &file_history,
+                                &partial_path,
+                                &chunk_hashes,
+                            );
+                        } else {
+                            user_files.remove_file_chunks(file_id);
+                            if let Err(e) = fs::remove_file(partial_path) {
+                                log::error!("removing partial file failed {}", e);
+                            }
+                        }
+                        ChatStorage::udate_status(
+                            account_id,
+                            &file_history.message_id,
+                            super::rpc_proto::MessageStatus::Receiving,
+                        );
                     }
+                    _ => return Err("file is not receiving".to_string()),
                 }
-                Err(e) => log::error!("{}", e),
+
+                file_history.file_state = FileState::Receiving;
+                user_files.save_filehistory(file_id, file_history.clone());
+
+                // request missing chunks
+                Self::request_chunks(account_id, &user_files, file_history);
+                Ok(())
+            }
+            None => Err("file not found".to_string()),
+        }
+    }
+
+    /// remove the markers of the corrupted chunks of a partial file
+    ///
+    /// Every received chunk is checked against its chunk hash.
+    /// The partial file is truncated to the file size.
+    fn remove_corrupted_chunks(
+        user_files: &UserFiles,
+        file_history: &FileHistory,
+        partial_path: &PathBuf,
+        chunk_hashes: &Vec<Vec<u8>>,
+    ) {
+        let mut file;
+        match OpenOptions::new().read(true).write(true).open(partial_path) {
+            Ok(f) => file = f,
+            Err(e) => {
+                log::error!("file open error {}", e);
+                user_files.remove_file_chunks(file_history.file_id);
+                return;
+            }
+        }
+        if let Err(e) = file.set_len(file_history.file_size) {
+            log::error!("file truncation error {}", e);
+        }
+
+        let file_id_bytes = file_history.file_id.to_be_bytes().to_vec();
+        for index in 0..file_history.chunk_count() {
+            let key = UserFiles::get_chunk_key(&file_id_bytes, index);
+            match user_files.file_chunks.contains_key(key) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    log::error!("{}", e);
+                    continue;
+                }
+            }
+
+            // read chunk
+            let offset = index as u64 * file_history.chunk_size as u64;
+            let mut chunk: Vec<u8> = Vec::new();
+            let mut valid = false;
+            if file.seek(SeekFrom::Start(offset)).is_ok()
+                && (&mut file)
+                    .take(file_history.chunk_size as u64)
+                    .read_to_end(&mut chunk)
+                    .is_ok()
+            {
+                if let Some(chunk_hash) = chunk_hashes.get(index as usize) {
+                    valid = Sha256::digest(&chunk).to_vec() == *chunk_hash;
+                }
+            }
+
+            if !valid {
+                log::warn!(
+                    "chunk {} of file {} is corrupted",
+                    index,
+                    file_history.file_id
+                );
+                user_files.unmark_file_chunk(file_history.file_id, index);
+            }
+        }
+    }
+
+    /// cancel the transfer of a file
+    ///
+    /// The received chunks are removed. If we are the sender
+    /// of the file, the retransmission of the file messages
+    /// is stopped and the group members are notified.
+    fn cancel(account_id: &PeerId, file_id: u64) -> Result<(), String> {
+        let user_files = Self::get_db_ref(account_id);
+        let mut file_history;
+        match user_files.get_filehistory(file_id) {
+            Some(v) => file_history = v,
+            None => return Err("file not found".to_string()),
+        }
+
+        match file_history.file_state {
+            FileState::Received | FileState::ConfirmedByAll | FileState::Cancelled => {
+                return Err("file transfer is already finished".to_string())
+            }
+            _ => {}
+        }
+
+        file_history.file_state = FileState::Cancelled;
+        user_files.save_filehistory(file_id, file_history.clone());
+        Self::purge_reception(account_id, &user_files, &file_history);
+
+        // notify the group members
+        if file_history.sender_id == account_id.to_bytes() {
+            Messaging::cancel_messages(account_id, &vec![file_history.message_id.clone()]);
+
+            let user_account;
+            match UserAccounts::get_by_id(account_id.to_owned()) {
+                Some(v) => user_account = v,
+                None => return Err("user account not found".to_string()),
+            }
+            let group;
+            match GroupStorage::get_group(account_id.to_owned(), file_history.group_id.clone()) {
+                Some(v) => group = v,
+                None => return Err("group not found".to_string()),
+            }
+
+            let data = proto_net::ChatFileContainer {
+                message: Some(proto_net::chat_file_container::Message::FileCancel(
+                    proto_net::ChatFileCancel { file_id },
+                )),
+            };
+
+            Self::send_filecontainer_to_group(
+                &user_account,
+                &group,
+                &file_history.message_id,
+                Timestamp::get_timestamp(),
+                data.encode_to_vec(),
+                None,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// process a cancel message from the sender of a file
+    fn on_cancel(
+        user_account: &UserAccount,
+        sender_id: PeerId,
+        file_cancel: proto_net::ChatFileCancel,
+    ) {
+        let user_files = Self::get_db_ref(&user_account.id);
+        let mut file_history;
+        match user_files.get_filehistory(file_cancel.file_id) {
+            Some(v) => file_history = v,
+            None => return,
+        }
+
+        // only the sender can cancel the transfer
+        if file_history.sender_id != sender_id.to_bytes() {
+            log::warn!("file cancel message not from the file sender");
+            return;
+        }
+        match file_history.file_state {
+            FileState::Received | FileState::Cancelled => return,
+            _ => {}
+        }
+
+        file_history.file_state = FileState::Cancelled;
+        user_files.save_filehistory(file_cancel.file_id, file_history.clone());
+        Self::purge_reception(&user_account.id, &user_files, &file_history);
+    }
+
+    /// remove all reception data of a cancelled file
+    ///
+    /// The chunk markers and the partial file are deleted,
+    /// the chat message is set to failed and the UI is informed.
+    fn purge_reception(account_id: &PeerId, user_files: &UserFiles, file_history: &FileHistory) {
+        user_files.remove_file_chunks(file_history.file_id);
+        Self::remove_activity(account_id, file_history.file_id);
+
+        let partial_path = Self::create_partial_file_path(
+            account_id.to_owned(),
+            file_history.file_id,
+            &file_history.file_extension,
+        );
+        if partial_path.exists() {
+            if let Err(e) = fs::remove_file(partial_path) {
+                log::error!("removing partial file failed {}", e);
             }
         }
 
-        // set file status to received
         ChatStorage::udate_status(
-            &user_account.id,
+            account_id,
             &file_history.message_id,
-            super::rpc_proto::MessageStatus::Received,
+            super::rpc_proto::MessageStatus::Failed,
         );
+
+        Self::send_progress(file_history, 0);
+    }
+
+    /// check if a progress event shall be sent
+    ///
+    /// Progress events are sent for every percent
+    /// of the file and for the last chunk.
+    fn is_progress_step(done: u32, total: u32) -> bool {
+        if total == 0 || done >= total {
+            return true;
+        }
+        if done == 0 {
+            return false;
+        }
+        (done as u64 * 100 / total as u64) != ((done as u64 - 1) * 100 / total as u64)
+    }
+
+    /// send a file progress event to the UI
+    fn send_progress(file_history: &FileHistory, chunks_transferred: u32) {
+        let proto_message = proto_rpc::ChatFile {
+            message: Some(proto_rpc::chat_file::Message::Progress(
+                proto_rpc::FileProgress {
+                    file_id: file_history.file_id,
+                    group_id: file_history.group_id.clone(),
+                    state: file_history.file_state.to_rpc() as i32,
+                    chunk_count: file_history.chunk_count(),
+                    chunks_transferred,
+                    chunks_confirmed: file_history.chunks_confirmed(),
+                },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            crate::rpc::proto::Modules::Chatfile.into(),
+            "".to_string(),
+            Vec::new(),
+        );
+    }
+
+    /// verify the content of a data chunk
+    ///
+    /// The chunk is verified against the chunk hash of the
+    /// file info, which is signed by the file sender.
+    /// Chunks of files without chunk hashes are verified
+    /// against the hash sent with the chunk.
+    fn verify_chunk(user_files: &UserFiles, file_data: &proto_net::ChatFileData) -> bool {
+        let chunk_hashes = user_files.get_chunk_hashes(file_data.file_id);
+        if chunk_hashes.len() > 0 {
+            match chunk_hashes.get(file_data.start_index as usize) {
+                Some(chunk_hash) => return Sha256::digest(&file_data.data).to_vec() == *chunk_hash,
+                None => return false,
+            }
+        }
+
+        file_data.data_hash.len() == 0
+            || Sha256::digest(&file_data.data).to_vec() == file_data.data_hash
     }
 
     /// process chat file data message
@@ -832,42 +2312,80 @@ impl ChatFile {
         // get DB references
         let user_files = Self::get_db_ref(&user_account.id);
 
-        // save file chunk in DB
-        user_files.save_file_chunk(file_data.file_id, file_data.start_index, file_data.data);
-
-        // check if we already received the file info
+        // the file info is needed to write the chunk,
+        // chunks arriving before it are buffered
+        let file_history;
         match user_files.get_filehistory(file_data.file_id) {
-            Some(file_history) => {
-                // create file once everything has been received
-                Self::try_store_file(user_account, user_files, file_history);
-            }
+            Some(v) => file_history = v,
             None => {
-                log::warn!("haven't received file info message yet");
-                // TODO: create stub file history file
-                /*
-                // create a file history stub if nothing was found
-                let file_history = FileHistory {
-                    group_id: todo!(),
-                    sender_id: todo!(),
-                    file_id: todo!(),
-                    message_id: todo!(),
-                    start_index: todo!(),
-                    message_count: todo!(),
-                    chunk_size: todo!(),
-                    file_state: todo!(),
-                    reception_tracking: todo!(),
-                    file_name: todo!(),
-                    file_description: todo!(),
-                    file_extension: todo!(),
-                    file_size: todo!(),
-                    sent_at: todo!(),
-                    received_at: todo!(),
-                };
+                log::trace!("haven't received file info message yet");
+                Self::buffer_early_chunk(&user_account.id, file_data);
+                return;
+            }
+        }
+
+        // drop chunks of paused, cancelled or completed files
+        match file_history.file_state {
+            FileState::Paused | FileState::Cancelled | FileState::Received => return,
+            _ => {}
+        }
 
-                user_files.save_filehistory(file_id, file_history);
-                 */
+        // drop chunks outside of the file or of the wrong size
+        match file_history.chunk_length(file_data.start_index) {
+            Some(length) => {
+                if file_data.data.len() as u64 != length {
+                    log::warn!(
+                        "chunk {} of file {} has the wrong size",
+                        file_data.start_index,
+                        file_data.file_id
+                    );
+                    return;
+                }
+            }
+            None => {
+                log::warn!(
+                    "chunk {} of file {} is outside of the file",
+                    file_data.start_index,
+                    file_data.file_id
+                );
+                return;
             }
         }
+
+        // verify chunk content
+        // corrupted chunks are dropped and requested again
+        // with the missing chunks of the file
+        if !Self::verify_chunk(&user_files, &file_data) {
+            log::warn!(
+                "chunk {} of file {} is corrupted",
+                file_data.start_index,
+                file_data.file_id
+            );
+            return;
+        }
+
+        // write chunk to the partial file
+        if let Err(e) = Self::write_chunk(
+            &user_account.id,
+            &file_history,
+            file_data.start_index,
+            &file_data.data,
+        ) {
+            log::error!("file chunk writing failed {}", e);
+            return;
+        }
+        let new_chunk = user_files.mark_file_chunk(file_data.file_id, file_data.start_index);
+        Self::update_activity(&user_account.id, file_data.file_id, false);
+
+        // inform UI about the progress
+        let count =
+            Self::received_chunk_count(&user_account.id, &user_files, file_data.file_id, new_chunk);
+        if Self::is_progress_step(count, file_history.chunk_count()) {
+            Self::send_progress(&file_history, count);
+        }
+
+        // create file once everything has been received
+        Self::try_store_file(user_account, user_files, file_history);
     }
 
     /// process chat file info message
@@ -879,15 +2397,67 @@ impl ChatFile {
         sent_at: u64,
         file_info: proto_net::ChatFileInfo,
     ) {
+        // check the version of the file info
+        if file_info.version > FILE_INFO_VERSION {
+            log::warn!(
+                "file info version {} of file {} is not supported",
+                file_info.version,
+                file_info.file_id
+            );
+            return;
+        }
+
+        // check the chunk size and the number of chunks
+        if file_info.data_chunk_size == 0 {
+            log::warn!("file {} has no chunk size", file_info.file_id);
+            return;
+        }
+        let mut chunk_count = file_info.file_size / file_info.data_chunk_size as u64;
+        if file_info.file_size % file_info.data_chunk_size as u64 > 0 {
+            chunk_count = chunk_count + 1;
+        }
+        if file_info.message_count as u64 != chunk_count + 1 {
+            log::warn!(
+                "message count of file {} doesn't match its size",
+                file_info.file_id
+            );
+            return;
+        }
+
+        // check the chunk hashes and the content addressed file id
+        if file_info.chunk_hashes.len() > 0 {
+            if file_info.chunk_hashes.len() != file_info.message_count.saturating_sub(1) as usize {
+                log::warn!(
+                    "chunk hash count of file {} doesn't match",
+                    file_info.file_id
+                );
+                return;
+            }
+            if file_info.file_id
+                != Self::generate_file_id(&group_id, &sender_id.to_bytes(), &file_info.file_hash)
+            {
+                log::warn!("file id of file {} doesn't match", file_info.file_id);
+                return;
+            }
+        }
+
         // get db
         let user_files = Self::get_db_ref(&user_account.id);
 
         // check if it already exists in DB
-        let file_history;
+        let mut file_history;
         if let Some(my_file_history) = user_files.get_filehistory(file_info.file_id) {
+            // the sender cancelled the transfer
+            if let FileState::Cancelled = my_file_history.file_state {
+                return;
+            }
             file_history = my_file_history;
 
-            // update fields
+            // the same file content was sent again
+            if file_history.message_id != message_id {
+                file_history.message_id = message_id.clone();
+                file_history.sent_at = sent_at;
+            }
         } else {
             file_history = FileHistory {
                 group_id: group_id.clone(),
@@ -905,11 +2475,14 @@ impl ChatFile {
                 file_size: file_info.file_size,
                 sent_at,
                 received_at: Timestamp::get_timestamp(),
+                file_hash: file_info.file_hash.clone(),
             };
+            user_files.save_chunk_hashes(file_info.file_id, file_info.chunk_hashes.clone());
         }
 
         // save to file history
         user_files.save_filehistory(file_info.file_id, file_history.clone());
+        Self::update_activity(&user_account.id, file_info.file_id, false);
 
         // create group id
         let groupid;
@@ -921,19 +2494,76 @@ impl ChatFile {
             }
         }
 
+        // save file media
+        let mut media = FileMedia {
+            mime_type: file_info.mime_type.clone(),
+            preview: file_info.preview.clone(),
+        };
+        if media.preview.len() > MAX_PREVIEW_SIZE {
+            log::warn!("file preview is too big");
+            media.preview = Vec::new();
+        }
+        user_files.save_media(file_info.file_id, media.clone());
+
         // save message to chat
+        let status = match file_history.file_state {
+            FileState::Received => super::rpc_proto::MessageStatus::Received,
+            _ => super::rpc_proto::MessageStatus::Receiving,
+        };
         Self::save_filemsg_in_chat(
             user_account,
             &sender_id,
             &groupid,
             &file_history,
-            super::rpc_proto::MessageStatus::Receiving,
+            &media,
+            status,
         );
 
+        // the file content is already stored
+        if let FileState::Received = file_history.file_state {
+            return;
+        }
+
+        // process the chunks that arrived before the file info
+        let early_chunks = Self::take_early_chunks(&user_account.id, file_info.file_id);
+        if early_chunks.len() > 0 {
+            for file_data in early_chunks {
+                Self::process_data_message(user_account, sender_id, group_id.clone(), file_data);
+            }
+            return;
+        }
+
         // create file once everything has been received
         Self::try_store_file(user_account, user_files, file_history);
     }
 
+    /// buffer a chunk that arrived before the file info message
+    fn buffer_early_chunk(account_id: &PeerId, file_data: proto_net::ChatFileData) {
+        let key = Self::get_activity_key(account_id, file_data.file_id);
+
+        let mut all_files = ALLFILES.get().write().unwrap();
+        let early = all_files.early_chunks.entry(key).or_insert(EarlyChunks {
+            received_at: Timestamp::get_timestamp(),
+            chunks: Vec::new(),
+        });
+        if early.chunks.len() >= EARLY_CHUNKS_MAX {
+            log::warn!("too many chunks before the file info message");
+            return;
+        }
+        early.chunks.push(file_data);
+    }
+
+    /// take the buffered chunks of a file
+    fn take_early_chunks(account_id: &PeerId, file_id: u64) -> Vec<proto_net::ChatFileData> {
+        let key = Self::get_activity_key(account_id, file_id);
+
+        let mut all_files = ALLFILES.get().write().unwrap();
+        match all_files.early_chunks.remove(&key) {
+            Some(early) => early.chunks,
+            None => Vec::new(),
+        }
+    }
+
     /// process chat file container message from network
     pub fn process_net_chatfilecontainer(
         sender_id: PeerId,
@@ -959,6 +2589,12 @@ impl ChatFile {
                 Some(proto_net::chat_file_container::Message::FileData(file_data)) => {
                     Self::process_data_message(&user_account, sender_id, group_id, file_data);
                 }
+                Some(proto_net::chat_file_container::Message::ChunkRequest(chunk_request)) => {
+                    Self::on_chunk_request(&user_account, sender_id, chunk_request);
+                }
+                Some(proto_net::chat_file_container::Message::FileCancel(file_cancel)) => {
+                    Self::on_cancel(&user_account, sender_id, file_cancel);
+                }
                 None => {
                     log::error!(
                         "file share message from {} was empty",
@@ -992,6 +2628,8 @@ impl ChatFile {
                             &send_req.group_id,
                             send_req.path_name,
                             send_req.description,
+                            send_req.mime_type,
+                            send_req.preview,
                         ) {
                             log::error!("file rpc send file failed {}", e.to_string());
                         }
@@ -1009,10 +2647,12 @@ impl ChatFile {
                             }
                         }
 
-                        let list = Self::file_history(&user_account, &history_req);
+                        let (list, total) = Self::file_history(&user_account, &history_req);
 
+                        let user_files = Self::get_db_ref(&account_id);
                         let mut histories: Vec<proto_rpc::FileHistoryEntry> = vec![];
                         for entry in list {
+                            let media = user_files.get_media(entry.file_id);
                             let file_entry = proto_rpc::FileHistoryEntry {
                                 file_id: entry.file_id,
                                 file_name: entry.file_name.clone(),
@@ -1025,6 +2665,10 @@ impl ChatFile {
                                     entry.group_id.try_into().unwrap(),
                                 )
                                 .to_string(),
+                                state: entry.file_state.to_rpc() as i32,
+                                file_hash: entry.file_hash.clone(),
+                                mime_type: media.mime_type,
+                                preview: media.preview,
                             };
                             histories.push(file_entry);
                         }
@@ -1035,7 +2679,7 @@ impl ChatFile {
                                 proto_rpc::FileHistoryResponse {
                                     offset: history_req.offset,
                                     limit: history_req.limit,
-                                    total: histories.len() as u64,
+                                    total,
                                     histories,
                                 },
                             )),
@@ -1055,6 +2699,72 @@ impl ChatFile {
                             Vec::new(),
                         );
                     }
+                    Some(proto_rpc::chat_file::Message::PauseRequest(pause_request)) => {
+                        if let Err(e) = Self::pause(&account_id, pause_request.file_id) {
+                            log::error!("file rpc pause failed {}", e);
+                        }
+                    }
+                    Some(proto_rpc::chat_file::Message::ResumeRequest(resume_request)) => {
+                        if let Err(e) = Self::resume(&account_id, resume_request.file_id) {
+                            log::error!("file rpc resume failed {}", e);
+                        }
+                    }
+                    Some(proto_rpc::chat_file::Message::CancelRequest(cancel_request)) => {
+                        if let Err(e) = Self::cancel(&account_id, cancel_request.file_id) {
+                            log::error!("file rpc cancel failed {}", e);
+                        }
+                    }
+                    Some(proto_rpc::chat_file::Message::DeleteRequest(delete_request)) => {
+                        let response;
+                        match Self::delete(&account_id, delete_request.file_id) {
+                            Ok(()) => {
+                                response = proto_rpc::FileDeleteResponse {
+                                    file_id: delete_request.file_id,
+                                    success: true,
+                                    error: "".to_string(),
+                                }
+                            }
+                            Err(e) => {
+                                log::error!("file rpc delete failed {}", e);
+                                response = proto_rpc::FileDeleteResponse {
+                                    file_id: delete_request.file_id,
+                                    success: false,
+                                    error: e,
+                                }
+                            }
+                        }
+
+                        // pack message
+                        let proto_message = proto_rpc::ChatFile {
+                            message: Some(proto_rpc::chat_file::Message::DeleteResponse(response)),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chatfile.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(proto_rpc::chat_file::Message::StorageRequest(_)) => {
+                        // pack message
+                        let proto_message = proto_rpc::ChatFile {
+                            message: Some(proto_rpc::chat_file::Message::StorageResponse(
+                                proto_rpc::FileStorageResponse {
+                                    groups: Self::storage_usage(&account_id),
+                                },
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chatfile.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
                     _ => {
                         log::error!("Unhandled Protobuf File Message");
                     }
@@ -1066,3 +2776,173 @@ impl ChatFile {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    static INIT: Once = Once::new();
+
+    /// initialize the storage in a temporary folder
+    fn init() {
+        INIT.call_once(|| {
+            let path = std::env::temp_dir().join(format!("qaul-test-{}", std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            crate::storage::Storage::init(path.to_str().unwrap().to_string());
+            Rpc::init();
+            Messaging::init();
+            ChatStorage::init();
+            ChatFile::init();
+        });
+    }
+
+    /// save the file history of a file sent to a receiver
+    ///
+    /// The file consists of the file info message and one chunk.
+    fn save_sent_file(account_id: &PeerId, receiver_id: &PeerId, file_id: u64) -> Vec<u8> {
+        let message_id = Chat::generate_message_id(&receiver_id.to_bytes(), account_id, 1);
+
+        let mut reception_tracking = BTreeMap::new();
+        reception_tracking.insert(
+            receiver_id.to_bytes(),
+            ReceptionTracking {
+                received: false,
+                package_count: 0,
+            },
+        );
+
+        let user_files = ChatFile::get_db_ref(account_id);
+        user_files.save_filehistory(
+            file_id,
+            FileHistory {
+                group_id: receiver_id.to_bytes(),
+                sender_id: account_id.to_bytes(),
+                file_id,
+                message_id: message_id.clone(),
+                start_index: 0,
+                message_count: 2,
+                chunk_size: DEF_PACKAGE_SIZE,
+                file_state: FileState::Sending,
+                reception_tracking,
+                file_name: "test".to_string(),
+                file_description: "".to_string(),
+                file_extension: "txt".to_string(),
+                file_size: 4,
+                sent_at: Timestamp::get_timestamp(),
+                received_at: 0,
+                file_hash: Vec::new(),
+            },
+        );
+
+        message_id
+    }
+
+    /// save a file message as unconfirmed message
+    fn save_unconfirmed(
+        account_id: &PeerId,
+        receiver_id: &PeerId,
+        message_id: &Vec<u8>,
+        signature: Vec<u8>,
+    ) {
+        let container = messaging::proto::Container {
+            signature,
+            envelope: Some(messaging::proto::Envelope {
+                sender_id: account_id.to_bytes(),
+                receiver_id: receiver_id.to_bytes(),
+                payload: Vec::new(),
+            }),
+        };
+        Messaging::save_unconfirmed_message(
+            MessagingServiceType::ChatFile,
+            message_id,
+            receiver_id,
+            &container,
+            false,
+        );
+    }
+
+    /// a file message that is given up by the messaging service
+    /// ends in the failed state
+    #[test]
+    fn failed_file_message() {
+        init();
+        let account_id = PeerId::random();
+        let receiver_id = PeerId::random();
+        let message_id = save_sent_file(&account_id, &receiver_id, 1);
+        let signature = vec![1, 1, 1, 1];
+        save_unconfirmed(&account_id, &receiver_id, &message_id, signature.clone());
+
+        // give up the message
+        let unconfirmed = Messaging::get_unconfirmed_message(&signature).unwrap();
+        Messaging::remove_unconfirmed_message(&signature);
+        Messaging::on_failed_message(&signature, unconfirmed, "test");
+
+        let user_files = ChatFile::get_db_ref(&account_id);
+        let file_history = user_files
+            .get_filehistory_by_message_id(&message_id)
+            .unwrap();
+        assert!(matches!(file_history.file_state, FileState::Failed));
+    }
+
+    /// a file whose messages are all confirmed
+    /// ends in the confirmed by all state
+    #[test]
+    fn confirmed_file_message() {
+        init();
+        let account_id = PeerId::random();
+        let receiver_id = PeerId::random();
+        let message_id = save_sent_file(&account_id, &receiver_id, 2);
+        let user_account = UserAccount {
+            id: account_id,
+            keys: libp2p::identity::Keypair::generate_ed25519(),
+            name: "test".to_string(),
+        };
+
+        // confirm the file info message and the chunk
+        for signature in [vec![2, 2, 2, 1], vec![2, 2, 2, 2]] {
+            save_unconfirmed(&account_id, &receiver_id, &message_id, signature.clone());
+            Messaging::on_confirmed_message(
+                &signature,
+                receiver_id,
+                user_account.clone(),
+                messaging::proto::Confirmation {
+                    signature: signature.clone(),
+                    received_at: Timestamp::get_timestamp(),
+                },
+            );
+        }
+
+        let user_files = ChatFile::get_db_ref(&account_id);
+        let file_history = user_files
+            .get_filehistory_by_message_id(&message_id)
+            .unwrap();
+        assert_eq!(file_history.chunks_confirmed(), 1);
+        assert!(matches!(file_history.file_state, FileState::ConfirmedByAll));
+    }
+
+    /// chunks outside of the file and chunks
+    /// of the wrong size are rejected
+    #[test]
+    fn chunk_length() {
+        init();
+        let account_id = PeerId::random();
+        let receiver_id = PeerId::random();
+        save_sent_file(&account_id, &receiver_id, 3);
+
+        // the file consists of the chunks 512, 512 and 100 bytes
+        let user_files = ChatFile::get_db_ref(&account_id);
+        let mut file_history = user_files.get_filehistory(3).unwrap();
+        file_history.chunk_size = 512;
+        file_history.file_size = 1124;
+        file_history.message_count = 4;
+
+        assert_eq!(file_history.chunk_length(0), Some(512));
+        assert_eq!(file_history.chunk_length(2), Some(100));
+        assert_eq!(file_history.chunk_length(3), None);
+        assert!(ChatFile::write_chunk(&account_id, &file_history, 1, &vec![0; 100]).is_err());
+        assert!(ChatFile::write_chunk(&account_id, &file_history, 2, &vec![0; 512]).is_err());
+        assert!(ChatFile::write_chunk(&account_id, &file_history, 3, &vec![0; 100]).is_err());
+        assert!(ChatFile::write_chunk(&account_id, &file_history, 2, &vec![0; 100]).is_ok());
+    }
+}
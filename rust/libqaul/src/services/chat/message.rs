@@ -43,11 +43,115 @@ impl ChatMessage {
         )
     }
 
-    /// send message
+    /// send a chat message to a group
+    ///
+    /// If `reply_to` contains a message id, the message
+    /// is sent as a reply to this message.
     pub fn send_chat_message(
         account_id: &PeerId,
         group_id: &Vec<u8>,
         message: String,
+        reply_to: Vec<u8>,
+    ) -> Result<bool, String> {
+        let payload;
+        if reply_to.len() > 0 {
+            payload = proto::common_message::Payload::ChatReply(proto::ChatReply {
+                parent_id: reply_to.clone(),
+                content: message.clone(),
+            });
+        } else {
+            payload = proto::common_message::Payload::ChatMessage(proto::ChatMessage {
+                content: message.clone(),
+            });
+        }
+
+        let message_content = super::rpc_proto::ChatContentMessage {
+            message: Some(
+                super::rpc_proto::chat_content_message::Message::ChatContent(
+                    super::rpc_proto::ChatContent {
+                        text: message,
+                        reply_to,
+                    },
+                ),
+            ),
+        };
+
+        Self::send_to_group(account_id, group_id, payload, Some(message_content))
+    }
+
+    /// send a reaction to a chat message
+    ///
+    /// The reaction is applied to the local message
+    /// and sent to all group members.
+    pub fn send_chat_reaction(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        message_id: Vec<u8>,
+        reaction: String,
+        remove: bool,
+    ) -> Result<bool, String> {
+        if !ChatStorage::update_reaction(
+            account_id,
+            group_id,
+            &message_id,
+            account_id,
+            &reaction,
+            remove,
+            Timestamp::get_timestamp(),
+        ) {
+            return Err("chat message not found".to_string());
+        }
+
+        let payload = proto::common_message::Payload::ChatReaction(proto::ChatReaction {
+            parent_id: message_id,
+            reaction,
+            remove,
+        });
+
+        Self::send_to_group(account_id, group_id, payload, None)
+    }
+
+    /// send an edit or redaction of an own chat message
+    ///
+    /// The edit is applied to the local message
+    /// and sent to all group members.
+    pub fn send_chat_edit(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        message_id: Vec<u8>,
+        content: String,
+        redact: bool,
+    ) -> Result<bool, String> {
+        if !ChatStorage::update_edit(
+            account_id,
+            group_id,
+            &message_id,
+            account_id,
+            &content,
+            redact,
+            Timestamp::get_timestamp(),
+        ) {
+            return Err("chat message can not be edited".to_string());
+        }
+
+        let payload = proto::common_message::Payload::ChatEdit(proto::ChatEdit {
+            parent_id: message_id,
+            content,
+            redact,
+        });
+
+        Self::send_to_group(account_id, group_id, payload, None)
+    }
+
+    /// send a common message payload to all group members
+    ///
+    /// If a message content is given, the message
+    /// is saved in the chat conversation.
+    pub fn send_to_group(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        payload: proto::common_message::Payload,
+        message_content: Option<super::rpc_proto::ChatContentMessage>,
     ) -> Result<bool, String> {
         let groupid = GroupId::from_bytes(&group_id).unwrap();
         let group;
@@ -92,31 +196,21 @@ impl ChatMessage {
             message_id: message_id.clone(),
             group_id: groupid.to_bytes(),
             sent_at: timestamp,
-            payload: Some(proto::common_message::Payload::ChatMessage(
-                proto::ChatMessage {
-                    content: message.clone(),
-                },
-            )),
-        };
-
-        let message_content = super::rpc_proto::ChatContentMessage {
-            message: Some(
-                super::rpc_proto::chat_content_message::Message::ChatContent(
-                    super::rpc_proto::ChatContent { text: message },
-                ),
-            ),
+            payload: Some(payload),
         };
 
         // save outgoing message
-        ChatStorage::save_message(
-            account_id,
-            &groupid,
-            account_id,
-            &message_id,
-            timestamp,
-            message_content.clone(),
-            rpc_proto::MessageStatus::Sending,
-        );
+        if let Some(content) = message_content {
+            ChatStorage::save_message(
+                account_id,
+                &groupid,
+                account_id,
+                &message_id,
+                timestamp,
+                content,
+                rpc_proto::MessageStatus::Sending,
+            );
+        }
 
         // send to all group members
         if let Some(user_account) = UserAccounts::get_by_id(account_id.clone()) {
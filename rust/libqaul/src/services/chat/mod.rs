@@ -10,13 +10,20 @@ use prost::Message;
 
 pub mod file;
 pub mod message;
+pub mod receipt;
+pub mod search;
 pub mod storage;
 
 use crate::connections::{internet::Internet, lan::Lan};
 use crate::node::user_accounts::UserAccounts;
 use crate::rpc::Rpc;
+use crate::services::group::GroupStorage;
+use crate::services::messaging::Messaging;
+use crate::utilities::timestamp::Timestamp;
 pub use file::ChatFile;
 pub use message::ChatMessage;
+pub use receipt::ChatReceipt;
+pub use search::ChatSearch;
 pub use storage::ChatStorage;
 
 /// Import protobuf message definition generated by
@@ -36,6 +43,9 @@ impl Chat {
 
         // initialize the chat file management
         ChatFile::init();
+
+        // initialize the typing notifications
+        ChatReceipt::init();
     }
 
     /// Generate a Chat Message ID
@@ -59,6 +69,93 @@ impl Chat {
         buff0
     }
 
+    /// Apply the retention settings of all groups
+    ///
+    /// This function is called periodically from the main event loop.
+    pub fn retention_sweep() {
+        let now = Timestamp::get_timestamp();
+
+        for account_id in UserAccounts::get_ids() {
+            let db_ref = GroupStorage::get_db_ref(account_id);
+            for entry in db_ref.groups.iter() {
+                if let Ok((_, group)) = entry {
+                    if !group.retention.is_active() {
+                        continue;
+                    }
+                    let deleted =
+                        ChatStorage::apply_retention(&account_id, &group.id, &group.retention, now);
+                    if deleted > 0 {
+                        log::trace!("retention deleted {} chat messages", deleted);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Create and send the delivery status of a chat message
+    /// to the RPC client
+    fn send_status_response(account_id: &PeerId, message_id: &Vec<u8>, changed: u32) {
+        let message = ChatStorage::get_message(account_id, message_id);
+        let message_ids = vec![message_id.clone()];
+        let mut deliveries: Vec<rpc_proto::ChatMessageDelivery> = Vec::new();
+
+        // unconfirmed deliveries
+        for unconfirmed in Messaging::get_unconfirmed_by_message_ids(account_id, &message_ids) {
+            let state;
+            if unconfirmed.custody {
+                state = rpc_proto::DeliveryState::DeliveryCustody;
+            } else if unconfirmed.scheduled_dtn {
+                state = rpc_proto::DeliveryState::DeliveryDtn;
+            } else if unconfirmed.scheduled {
+                state = rpc_proto::DeliveryState::DeliverySent;
+            } else {
+                state = rpc_proto::DeliveryState::DeliveryPending;
+            }
+
+            deliveries.push(rpc_proto::ChatMessageDelivery {
+                receiver_id: unconfirmed.receiver_id,
+                state: state as i32,
+                try_count: unconfirmed.retry,
+                created_at: unconfirmed.created_at,
+                last_try: unconfirmed.last_sent,
+                failure: "".to_string(),
+            });
+        }
+
+        // failed deliveries
+        for failed in Messaging::get_failed_by_message_ids(account_id, &message_ids) {
+            deliveries.push(rpc_proto::ChatMessageDelivery {
+                receiver_id: failed.receiver_id,
+                state: rpc_proto::DeliveryState::DeliveryFailed as i32,
+                try_count: failed.try_count,
+                created_at: failed.created_at,
+                last_try: failed.last_try,
+                failure: failed.message,
+            });
+        }
+
+        // pack message
+        let proto_message = rpc_proto::Chat {
+            message: Some(rpc_proto::chat::Message::StatusResponse(
+                rpc_proto::ChatMessageStatusResponse {
+                    message_id: message_id.clone(),
+                    found: message.is_some() || deliveries.len() > 0,
+                    message,
+                    deliveries,
+                    changed,
+                },
+            )),
+        };
+
+        // send message
+        Rpc::send_message(
+            proto_message.encode_to_vec(),
+            crate::rpc::proto::Modules::Chat.into(),
+            "".to_string(),
+            Vec::new(),
+        );
+    }
+
     /// Process incoming RPC request messages for chat module
     pub fn rpc(
         data: Vec<u8>,
@@ -126,10 +223,184 @@ impl Chat {
                             &user_account.id,
                             &message.group_id,
                             message.content,
+                            message.reply_to,
                         ) {
                             log::error!("Outgoing chat message error: {}", error)
                         }
                     }
+                    Some(rpc_proto::chat::Message::StatusRequest(status_request)) => {
+                        // send delivery status
+                        Self::send_status_response(&account_id, &status_request.message_id, 0);
+                    }
+                    Some(rpc_proto::chat::Message::ResendRequest(resend_request)) => {
+                        let message_id = resend_request.message_id;
+                        let message_ids = vec![message_id.clone()];
+
+                        // schedule message again
+                        let changed = Messaging::resend_messages(&account_id, &message_ids);
+                        if changed > 0 {
+                            ChatStorage::udate_status(
+                                &account_id,
+                                &message_id,
+                                rpc_proto::MessageStatus::Sending,
+                            );
+                        }
+
+                        // send delivery status
+                        Self::send_status_response(&account_id, &message_id, changed);
+                    }
+                    Some(rpc_proto::chat::Message::CancelRequest(cancel_request)) => {
+                        let message_id = cancel_request.message_id;
+                        let message_ids = vec![message_id.clone()];
+
+                        // remove message from the sending tables
+                        let changed = Messaging::cancel_messages(&account_id, &message_ids);
+                        if changed > 0 {
+                            if let Some(message) =
+                                ChatStorage::get_message(&account_id, &message_id)
+                            {
+                                if message.status == rpc_proto::MessageStatus::Sending as i32
+                                    || message.status == rpc_proto::MessageStatus::Sent as i32
+                                {
+                                    ChatStorage::udate_status(
+                                        &account_id,
+                                        &message_id,
+                                        rpc_proto::MessageStatus::Failed,
+                                    );
+                                }
+                            }
+                        }
+
+                        // send delivery status
+                        Self::send_status_response(&account_id, &message_id, changed);
+                    }
+                    Some(rpc_proto::chat::Message::DeleteRequest(delete_request)) => {
+                        // delete messages
+                        let deleted = ChatStorage::delete_messages(
+                            &account_id,
+                            &delete_request.group_id,
+                            &delete_request.message_ids,
+                            delete_request.first_index,
+                            delete_request.last_index,
+                        );
+
+                        // pack message
+                        let proto_message = rpc_proto::Chat {
+                            message: Some(rpc_proto::chat::Message::DeleteResponse(
+                                rpc_proto::ChatDeleteResponse {
+                                    group_id: delete_request.group_id,
+                                    deleted,
+                                },
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chat.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(rpc_proto::chat::Message::SearchRequest(search_request)) => {
+                        // search messages
+                        let search_response = ChatSearch::search(&account_id, &search_request);
+
+                        // pack message
+                        let proto_message = rpc_proto::Chat {
+                            message: Some(rpc_proto::chat::Message::SearchResponse(
+                                search_response,
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chat.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(rpc_proto::chat::Message::ReactionSend(reaction)) => {
+                        // send reaction
+                        if let Err(error) = ChatMessage::send_chat_reaction(
+                            &account_id,
+                            &reaction.group_id,
+                            reaction.message_id,
+                            reaction.reaction,
+                            reaction.remove,
+                        ) {
+                            log::error!("Outgoing chat reaction error: {}", error)
+                        }
+                    }
+                    Some(rpc_proto::chat::Message::EditSend(edit)) => {
+                        // send edit
+                        if let Err(error) = ChatMessage::send_chat_edit(
+                            &account_id,
+                            &edit.group_id,
+                            edit.message_id,
+                            edit.content,
+                            edit.redact,
+                        ) {
+                            log::error!("Outgoing chat edit error: {}", error)
+                        }
+                    }
+                    Some(rpc_proto::chat::Message::ReadReceiptsSet(read_receipts_set)) => {
+                        // enable or disable read receipts
+                        ChatReceipt::set_enabled(&account_id, read_receipts_set.enabled);
+                    }
+                    Some(rpc_proto::chat::Message::ReadReceiptsRequest(read_receipts_request)) => {
+                        // get read receipts of the group
+                        let read_receipts_response = ChatReceipt::get_read_receipts(
+                            &account_id,
+                            &read_receipts_request.group_id,
+                        );
+
+                        // pack message
+                        let proto_message = rpc_proto::Chat {
+                            message: Some(rpc_proto::chat::Message::ReadReceiptsResponse(
+                                read_receipts_response,
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chat.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(rpc_proto::chat::Message::TypingSend(typing_send)) => {
+                        // send typing notification
+                        if let Err(error) = ChatReceipt::send_typing(
+                            &account_id,
+                            &typing_send.group_id,
+                            typing_send.typing,
+                        ) {
+                            log::error!("Outgoing typing notification error: {}", error)
+                        }
+                    }
+                    Some(rpc_proto::chat::Message::TypingRequest(typing_request)) => {
+                        // get typing users of the group
+                        let typing_response =
+                            ChatReceipt::get_typing(&account_id, &typing_request.group_id);
+
+                        // pack message
+                        let proto_message = rpc_proto::Chat {
+                            message: Some(rpc_proto::chat::Message::TypingResponse(
+                                typing_response,
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Chat.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
                     _ => {
                         log::error!("Unhandled Protobuf Chat Message");
                     }
@@ -1,7 +1,7 @@
 /// Chat file sending container
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChatFileContainer {
-    #[prost(oneof="chat_file_container::Message", tags="1, 2")]
+    #[prost(oneof="chat_file_container::Message", tags="1, 2, 3, 4")]
     pub message: ::core::option::Option<chat_file_container::Message>,
 }
 /// Nested message and enum types in `ChatFileContainer`.
@@ -14,6 +14,12 @@ pub mod chat_file_container {
         /// Chat File Data Message
         #[prost(message, tag="2")]
         FileData(super::ChatFileData),
+        /// Request missing chunks of a file
+        #[prost(message, tag="3")]
+        ChunkRequest(super::ChatFileChunkRequest),
+        /// The sender cancelled the transfer of a file
+        #[prost(message, tag="4")]
+        FileCancel(super::ChatFileCancel),
     }
 }
 /// Chat File Info Message
@@ -28,9 +34,13 @@ pub struct ChatFileInfo {
     /// file extension
     #[prost(string, tag="3")]
     pub file_extension: ::prost::alloc::string::String,
-    /// file size
-    #[prost(uint32, tag="4")]
-    pub file_size: u32,
+    /// file size in bytes
+    ///
+    /// This field was a uint32 before version 1.
+    /// The wire format of both types is compatible
+    /// for files smaller than 4 GiB.
+    #[prost(uint64, tag="4")]
+    pub file_size: u64,
     /// file description
     #[prost(string, tag="5")]
     pub file_description: ::prost::alloc::string::String,
@@ -44,6 +54,67 @@ pub struct ChatFileInfo {
     /// file data chunk size
     #[prost(uint32, tag="8")]
     pub data_chunk_size: u32,
+    /// SHA-256 hash of the entire file content
+    ///
+    /// The receiver verifies the assembled file
+    /// against this hash, before it is stored.
+    #[prost(bytes="vec", tag="9")]
+    pub file_hash: ::prost::alloc::vec::Vec<u8>,
+    /// version of the file info message
+    ///
+    /// 0 = legacy file info with 32 bit file size
+    /// 1 = 64 bit file size
+    ///
+    /// File infos of a newer version are ignored by the receiver.
+    #[prost(uint32, tag="10")]
+    pub version: u32,
+    /// MIME type of the file
+    #[prost(string, tag="11")]
+    pub mime_type: ::prost::alloc::string::String,
+    /// optional small preview of the file
+    ///
+    /// The preview is delivered with the file info
+    /// before any data chunk.
+    #[prost(bytes="vec", tag="12")]
+    pub preview: ::prost::alloc::vec::Vec<u8>,
+    /// SHA-256 hashes of the data chunks
+    ///
+    /// One hash per data chunk, ordered by the start index.
+    /// The file info is signed by the sender of the file,
+    /// every data chunk is verified against its hash,
+    /// no matter which group member relayed it.
+    ///
+    /// Empty for senders not providing chunk hashes.
+    #[prost(bytes="vec", repeated, tag="13")]
+    pub chunk_hashes: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// Request missing chunks of a file
+///
+/// The request can be sent to the sender of the file
+/// or to any other group member holding the file.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatFileChunkRequest {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+    /// group id of the file
+    #[prost(bytes="vec", tag="2")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// bitmap of the requested chunks
+    ///
+    /// bit n (byte n / 8, bit n % 8, starting at the lowest bit)
+    /// is set, if the data chunk with the start index n is requested.
+    #[prost(bytes="vec", tag="3")]
+    pub bitmap: ::prost::alloc::vec::Vec<u8>,
+}
+/// Cancel the transfer of a file
+///
+/// Sent by the sender of the file to all group members.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChatFileCancel {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
 }
 /// Chat File Data Message
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -60,4 +131,11 @@ pub struct ChatFileData {
     /// package data
     #[prost(bytes="vec", tag="4")]
     pub data: ::prost::alloc::vec::Vec<u8>,
+    /// SHA-256 hash of the package data
+    ///
+    /// The hash is calculated by the member sending the package.
+    /// It is only used for files without chunk hashes in the
+    /// file info, corrupted packages are dropped and requested again.
+    #[prost(bytes="vec", tag="5")]
+    pub data_hash: ::prost::alloc::vec::Vec<u8>,
 }
@@ -2,7 +2,7 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChatFile {
     /// message type
-    #[prost(oneof="chat_file::Message", tags="1, 2, 3, 4")]
+    #[prost(oneof="chat_file::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12")]
     pub message: ::core::option::Option<chat_file::Message>,
 }
 /// Nested message and enum types in `ChatFile`.
@@ -31,6 +31,34 @@ pub mod chat_file {
         /// delivers the requested list of
         #[prost(message, tag="4")]
         FileHistoryResponse(super::FileHistoryResponse),
+        /// pause the reception of a file
+        #[prost(message, tag="5")]
+        PauseRequest(super::FilePauseRequest),
+        /// resume the reception of a paused
+        /// or incomplete file
+        #[prost(message, tag="6")]
+        ResumeRequest(super::FileResumeRequest),
+        /// file transfer progress
+        ///
+        /// this message is sent from libqaul to the UI
+        /// while a file is sent, confirmed or received
+        #[prost(message, tag="7")]
+        Progress(super::FileProgress),
+        /// cancel the transfer of a file
+        #[prost(message, tag="8")]
+        CancelRequest(super::FileCancelRequest),
+        /// delete a file
+        #[prost(message, tag="9")]
+        DeleteRequest(super::FileDeleteRequest),
+        /// result of the file deletion
+        #[prost(message, tag="10")]
+        DeleteResponse(super::FileDeleteResponse),
+        /// request the file storage usage per group
+        #[prost(message, tag="11")]
+        StorageRequest(super::FileStorageRequest),
+        /// file storage usage per group
+        #[prost(message, tag="12")]
+        StorageResponse(super::FileStorageResponse),
     }
 }
 /// Send File Request
@@ -47,6 +75,19 @@ pub struct SendFileRequest {
     /// file description text to be sent in the message
     #[prost(string, tag="3")]
     pub description: ::prost::alloc::string::String,
+    /// MIME type of the file
+    ///
+    /// if empty, the MIME type is derived from the file extension
+    #[prost(string, tag="4")]
+    pub mime_type: ::prost::alloc::string::String,
+    /// optional small preview of the file
+    ///
+    /// e.g. a thumbnail of an image or video,
+    /// which is sent together with the file info.
+    /// The preview is not sent, if it is bigger than
+    /// the chunk size of the transports to the group members.
+    #[prost(bytes="vec", tag="5")]
+    pub preview: ::prost::alloc::vec::Vec<u8>,
 }
 /// Send File Response
 ///
@@ -66,6 +107,107 @@ pub struct SendFileResponse {
     #[prost(uint64, tag="3")]
     pub file_id: u64,
 }
+/// Pause the reception of a file
+///
+/// Incoming chunks of a paused file are dropped
+/// and no missing chunks are requested.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FilePauseRequest {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+}
+/// Resume the reception of a file
+///
+/// The missing chunks are requested from the group members.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileResumeRequest {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+}
+/// Cancel the transfer of a file
+///
+/// A cancelled file is not sent or received anymore.
+/// If we are the sender of the file, the group members
+/// are notified to cancel the reception.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileCancelRequest {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+}
+/// File transfer progress
+///
+/// Progress event sent from libqaul to the UI.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileProgress {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+    /// group id
+    #[prost(bytes="vec", tag="2")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// transfer state of the file
+    #[prost(enumeration="FileHistoryState", tag="3")]
+    pub state: i32,
+    /// number of data chunks of the file
+    #[prost(uint32, tag="4")]
+    pub chunk_count: u32,
+    /// number of chunks sent or received
+    #[prost(uint32, tag="5")]
+    pub chunks_transferred: u32,
+    /// number of chunks confirmed by all group members
+    #[prost(uint32, tag="6")]
+    pub chunks_confirmed: u32,
+}
+/// Delete a file
+///
+/// The file is removed from disk together with
+/// its file history entry and all received chunks.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileDeleteRequest {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+}
+/// Result of the file deletion
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileDeleteResponse {
+    /// file id
+    #[prost(uint64, tag="1")]
+    pub file_id: u64,
+    /// was the file deleted
+    #[prost(bool, tag="2")]
+    pub success: bool,
+    /// error reason
+    #[prost(string, tag="3")]
+    pub error: ::prost::alloc::string::String,
+}
+/// Request the file storage usage
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileStorageRequest {
+}
+/// File storage usage per group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileStorageResponse {
+    /// storage usage of all groups with files
+    #[prost(message, repeated, tag="1")]
+    pub groups: ::prost::alloc::vec::Vec<FileStorageGroup>,
+}
+/// File storage usage of a group
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileStorageGroup {
+    /// group id
+    #[prost(bytes="vec", tag="1")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// number of files
+    #[prost(uint32, tag="2")]
+    pub file_count: u32,
+    /// bytes used on disk
+    #[prost(uint64, tag="3")]
+    pub size: u64,
+}
 /// File History Request
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FileHistoryRequest {
@@ -75,6 +217,16 @@ pub struct FileHistoryRequest {
     /// limit
     #[prost(uint32, tag="2")]
     pub limit: u32,
+    /// only files of this group
+    ///
+    /// all groups if empty
+    #[prost(bytes="vec", tag="3")]
+    pub group_id: ::prost::alloc::vec::Vec<u8>,
+    /// only files of this sender
+    ///
+    /// all senders if empty
+    #[prost(bytes="vec", tag="4")]
+    pub sender_id: ::prost::alloc::vec::Vec<u8>,
 }
 /// File History Entry
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -88,9 +240,9 @@ pub struct FileHistoryEntry {
     /// file extension
     #[prost(string, tag="3")]
     pub file_extension: ::prost::alloc::string::String,
-    /// file size
-    #[prost(uint32, tag="4")]
-    pub file_size: u32,
+    /// file size in bytes
+    #[prost(uint64, tag="4")]
+    pub file_size: u64,
     /// file description
     #[prost(string, tag="5")]
     pub file_description: ::prost::alloc::string::String,
@@ -103,6 +255,18 @@ pub struct FileHistoryEntry {
     /// group id
     #[prost(string, tag="8")]
     pub group_id: ::prost::alloc::string::String,
+    /// transfer state of the file
+    #[prost(enumeration="FileHistoryState", tag="9")]
+    pub state: i32,
+    /// SHA-256 hash of the file content
+    #[prost(bytes="vec", tag="10")]
+    pub file_hash: ::prost::alloc::vec::Vec<u8>,
+    /// MIME type of the file
+    #[prost(string, tag="11")]
+    pub mime_type: ::prost::alloc::string::String,
+    /// small preview of the file, empty if none
+    #[prost(bytes="vec", tag="12")]
+    pub preview: ::prost::alloc::vec::Vec<u8>,
 }
 /// File History Response
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -113,10 +277,55 @@ pub struct FileHistoryResponse {
     /// limit
     #[prost(uint32, tag="2")]
     pub limit: u32,
-    /// limit
+    /// total number of matching files
     #[prost(uint64, tag="3")]
     pub total: u64,
     /// histories
     #[prost(message, repeated, tag="4")]
     pub histories: ::prost::alloc::vec::Vec<FileHistoryEntry>,
 }
+/// File transfer state
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FileHistoryState {
+    /// file is being sent
+    FileSending = 0,
+    /// file has been sent
+    FileSent = 1,
+    /// reception was confirmed by a member
+    FileConfirmed = 2,
+    /// reception was confirmed by all members
+    FileConfirmedByAll = 3,
+    /// file is being received
+    FileReceiving = 4,
+    /// file was received and verified
+    FileReceived = 5,
+    /// sending failed
+    FileFailed = 6,
+    /// the received file content did not match its hash
+    FileVerificationFailed = 7,
+    /// reception of the file is paused
+    FilePaused = 8,
+    /// transfer of the file was cancelled
+    FileCancelled = 9,
+}
+impl FileHistoryState {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FileHistoryState::FileSending => "FILE_SENDING",
+            FileHistoryState::FileSent => "FILE_SENT",
+            FileHistoryState::FileConfirmed => "FILE_CONFIRMED",
+            FileHistoryState::FileConfirmedByAll => "FILE_CONFIRMED_BY_ALL",
+            FileHistoryState::FileReceiving => "FILE_RECEIVING",
+            FileHistoryState::FileReceived => "FILE_RECEIVED",
+            FileHistoryState::FileFailed => "FILE_FAILED",
+            FileHistoryState::FileVerificationFailed => "FILE_VERIFICATION_FAILED",
+            FileHistoryState::FilePaused => "FILE_PAUSED",
+            FileHistoryState::FileCancelled => "FILE_CANCELLED",
+        }
+    }
+}
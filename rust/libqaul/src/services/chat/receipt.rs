@@ -0,0 +1,333 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Chat Read Receipts and Typing Notifications
+//!
+//! Read receipts are opt-in per user account. When the unread
+//! messages of a group are cleared, a read receipt is sent to
+//! all group members. It contains for every member the message id
+//! of the last chat message we received from this member.
+//! Read receipts are sent directly to the online group members,
+//! they are not confirmed, not retransmitted and never stored in DTN.
+//!
+//! The received read receipts are saved in the data base.
+//! Every receiving user only saves its own read marker.
+//!
+//! Typing notifications are ephemeral. They are sent directly
+//! to the online group members, are not confirmed, not
+//! retransmitted and never stored in DTN.
+//! The typing state is only kept in memory and expires
+//! after a timeout.
+
+use libp2p::PeerId;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use state::Storage;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use super::rpc_proto;
+use super::ChatStorage;
+use crate::node::user_accounts::UserAccounts;
+use crate::services::group::{Group, GroupStorage};
+use crate::services::messaging::{proto, Messaging, MessagingServiceType};
+use crate::storage::configuration::Configuration;
+use crate::utilities::timestamp::Timestamp;
+
+/// time in milli seconds after which a typing notification expires
+const TYPING_TIMEOUT: u64 = 6000;
+
+/// mutable state of the typing notifications
+static TYPING: Storage<RwLock<ChatTyping>> = Storage::new();
+
+/// read receipt of a group member
+///
+/// The group member has read all our messages
+/// up to the message with the message id.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReadReceipt {
+    /// message id of our last message the member has read
+    pub last_message_id: Vec<u8>,
+    /// data base index of this message in our chat conversation
+    pub last_message_index: u64,
+    /// time the receipt was received
+    pub read_at: u64,
+}
+
+/// typing users
+struct ChatTyping {
+    /// account id & group id => user id => expires at
+    typing: BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, u64>>,
+}
+
+/// Chat read receipts and typing notifications
+pub struct ChatReceipt {}
+
+impl ChatReceipt {
+    /// initialize the typing state
+    pub fn init() {
+        let typing = ChatTyping {
+            typing: BTreeMap::new(),
+        };
+        TYPING.set(RwLock::new(typing));
+    }
+
+    /// check if read receipts are enabled for a user account
+    pub fn is_enabled(account_id: &PeerId) -> bool {
+        match Configuration::get_user(account_id.to_string()) {
+            Some(user_profile) => user_profile.chat.read_receipts,
+            None => false,
+        }
+    }
+
+    /// enable or disable the sending of read receipts
+    pub fn set_enabled(account_id: &PeerId, enabled: bool) {
+        Configuration::update_user_read_receipts(account_id.to_string(), enabled);
+        Configuration::save();
+    }
+
+    /// send a read receipt to all group members
+    ///
+    /// The receipt is only sent if read receipts are
+    /// enabled for this user account.
+    pub fn send_read_receipt(account_id: &PeerId, group: &Group) {
+        if !Self::is_enabled(account_id) {
+            return;
+        }
+
+        let user_account;
+        match UserAccounts::get_by_id(account_id.to_owned()) {
+            Some(v) => user_account = v,
+            None => return,
+        }
+
+        // create read marker for every member
+        let mut member_ids: Vec<Vec<u8>> = Vec::new();
+        for user_id in group.members.keys() {
+            if *user_id != account_id.to_bytes() {
+                member_ids.push(user_id.to_owned());
+            }
+        }
+        let mut read_markers: Vec<proto::ChatReadMarker> = Vec::new();
+        for (user_id, message_id) in
+            ChatStorage::get_last_message_ids(account_id, &group.id, &member_ids)
+        {
+            read_markers.push(proto::ChatReadMarker {
+                user_id,
+                message_id,
+            });
+        }
+        if read_markers.len() == 0 {
+            return;
+        }
+
+        let send_message = proto::Messaging {
+            message: Some(proto::messaging::Message::ChatRead(proto::ChatRead {
+                group_id: group.id.to_owned(),
+                read_markers,
+            })),
+        };
+        let data = send_message.encode_to_vec();
+
+        for user_id in member_ids {
+            if let Ok(receiver) = PeerId::from_bytes(&user_id) {
+                // not a common message: it is neither saved
+                // as unconfirmed message nor sent via DTN
+                if let Err(error) = Messaging::pack_and_send_message(
+                    &user_account,
+                    &receiver,
+                    data.clone(),
+                    MessagingServiceType::Chat,
+                    &Vec::new(),
+                    false,
+                ) {
+                    log::error!("read receipt send error {}", error);
+                }
+            }
+        }
+    }
+
+    /// process a received read receipt
+    ///
+    /// Only the read marker of our own messages is saved.
+    pub fn on_read_receipt(account_id: &PeerId, sender_id: &PeerId, chat_read: &proto::ChatRead) {
+        // check membership
+        match GroupStorage::get_group(account_id.to_owned(), chat_read.group_id.clone()) {
+            Some(group) => {
+                if !group.are_members(&account_id.to_bytes(), &sender_id.to_bytes()) {
+                    return;
+                }
+            }
+            None => return,
+        }
+
+        let my_id = account_id.to_bytes();
+        for read_marker in &chat_read.read_markers {
+            if read_marker.user_id != my_id {
+                continue;
+            }
+
+            // the read message must be our message in this group
+            let chat_msg;
+            match ChatStorage::get_message(account_id, &read_marker.message_id) {
+                Some(v) => chat_msg = v,
+                None => return,
+            }
+            if chat_msg.group_id != chat_read.group_id || chat_msg.sender_id != my_id {
+                return;
+            }
+
+            let db_ref = ChatStorage::get_db_ref(account_id.to_owned());
+            let mut key = chat_read.group_id.clone();
+            key.extend(sender_id.to_bytes());
+
+            // never move the read marker backwards
+            if let Ok(Some(receipt)) = db_ref.receipts.get(&key) {
+                if receipt.last_message_index >= chat_msg.index {
+                    return;
+                }
+            }
+
+            let receipt = ReadReceipt {
+                last_message_id: read_marker.message_id.clone(),
+                last_message_index: chat_msg.index,
+                read_at: Timestamp::get_timestamp(),
+            };
+            if let Err(e) = db_ref.receipts.insert(key, receipt) {
+                log::error!("Error saving read receipt to data base: {}", e);
+            }
+            if let Err(e) = db_ref.receipts.flush() {
+                log::error!("Error read receipts flush: {}", e);
+            }
+            return;
+        }
+    }
+
+    /// get the read receipts of a group
+    pub fn get_read_receipts(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+    ) -> rpc_proto::ChatReadReceiptsResponse {
+        let db_ref = ChatStorage::get_db_ref(account_id.to_owned());
+        let mut receipts: Vec<rpc_proto::ChatReadReceipt> = Vec::new();
+
+        for res in db_ref.receipts.scan_prefix(group_id) {
+            if let Ok((key, receipt)) = res {
+                receipts.push(rpc_proto::ChatReadReceipt {
+                    user_id: key[group_id.len()..].to_vec(),
+                    last_message_id: receipt.last_message_id,
+                    read_at: receipt.read_at,
+                });
+            }
+        }
+
+        rpc_proto::ChatReadReceiptsResponse {
+            group_id: group_id.to_owned(),
+            enabled: Self::is_enabled(account_id),
+            receipts,
+        }
+    }
+
+    /// send a typing notification to all group members
+    pub fn send_typing(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        typing: bool,
+    ) -> Result<(), String> {
+        let group;
+        match GroupStorage::get_group(account_id.to_owned(), group_id.to_owned()) {
+            Some(v) => group = v,
+            None => return Err("Group not found".to_string()),
+        }
+        if !group.is_member(&account_id.to_bytes()) {
+            return Err("you are not member in this group".to_string());
+        }
+
+        let user_account;
+        match UserAccounts::get_by_id(account_id.to_owned()) {
+            Some(v) => user_account = v,
+            None => return Err("user account not found".to_string()),
+        }
+
+        let send_message = proto::Messaging {
+            message: Some(proto::messaging::Message::ChatTyping(proto::ChatTyping {
+                group_id: group_id.to_owned(),
+                typing,
+            })),
+        };
+        let data = send_message.encode_to_vec();
+
+        for user_id in group.members.keys() {
+            if *user_id == account_id.to_bytes() {
+                continue;
+            }
+            if let Ok(receiver) = PeerId::from_bytes(user_id) {
+                // not a common message: it is neither saved
+                // as unconfirmed message nor sent via DTN
+                if let Err(error) = Messaging::pack_and_send_message(
+                    &user_account,
+                    &receiver,
+                    data.clone(),
+                    MessagingServiceType::Chat,
+                    &Vec::new(),
+                    false,
+                ) {
+                    log::error!("typing notification send error {}", error);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// process a received typing notification
+    pub fn on_typing(account_id: &PeerId, sender_id: &PeerId, typing: &proto::ChatTyping) {
+        // check membership
+        match GroupStorage::get_group(account_id.to_owned(), typing.group_id.clone()) {
+            Some(group) => {
+                if !group.are_members(&account_id.to_bytes(), &sender_id.to_bytes()) {
+                    return;
+                }
+            }
+            None => return,
+        }
+
+        let mut key = account_id.to_bytes();
+        key.extend(typing.group_id.iter());
+
+        let mut state = TYPING.get().write().unwrap();
+        let users = state.typing.entry(key).or_insert(BTreeMap::new());
+        if typing.typing {
+            users.insert(
+                sender_id.to_bytes(),
+                Timestamp::get_timestamp() + TYPING_TIMEOUT,
+            );
+        } else {
+            users.remove(&sender_id.to_bytes());
+        }
+    }
+
+    /// get the users currently typing in a group
+    ///
+    /// Expired typing notifications are removed.
+    pub fn get_typing(account_id: &PeerId, group_id: &Vec<u8>) -> rpc_proto::ChatTypingResponse {
+        let mut key = account_id.to_bytes();
+        key.extend(group_id.iter());
+
+        let now = Timestamp::get_timestamp();
+        let mut user_ids: Vec<Vec<u8>> = Vec::new();
+
+        let mut state = TYPING.get().write().unwrap();
+        if let Some(users) = state.typing.get_mut(&key) {
+            users.retain(|_user_id, expires_at| *expires_at > now);
+            for user_id in users.keys() {
+                user_ids.push(user_id.to_owned());
+            }
+        }
+
+        rpc_proto::ChatTypingResponse {
+            group_id: group_id.to_owned(),
+            user_ids,
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Chat Full-Text Search
+//!
+//! Every user account has an inverted index over its chat messages.
+//! The index contains the words of the chat texts and
+//! the names and descriptions of the files.
+//!
+//! The index keys are built as follows:
+//!
+//! word + 0x00 + chat message db key
+//!
+//! The value of an index entry is the time the message was sent.
+//!
+//! Messages saved before the index was introduced are indexed
+//! once, which is recorded in the search state tree.
+
+use libp2p::PeerId;
+use prost::Message;
+use sled_extensions::{bincode::Tree, DbExt};
+use std::collections::BTreeSet;
+
+use super::rpc_proto;
+use super::storage::{ChatAccountDb, ChatStorage};
+
+/// minimal length of an indexed word in characters
+const MIN_WORD_LENGTH: usize = 2;
+/// default number of search results
+const DEFAULT_LIMIT: u32 = 50;
+/// search state key, set once all messages are indexed
+const INDEXED_KEY: &[u8] = b"indexed";
+
+/// Chat full-text search
+pub struct ChatSearch {}
+
+impl ChatSearch {
+    /// split a text into lower case words
+    ///
+    /// Every word is only returned once.
+    pub fn tokenize(text: &str) -> Vec<String> {
+        let mut words: Vec<String> = Vec::new();
+
+        for word in text.split(|c: char| !c.is_alphanumeric()) {
+            if word.chars().count() < MIN_WORD_LENGTH {
+                continue;
+            }
+            let word = word.to_lowercase();
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+
+        words
+    }
+
+    /// get the searchable words of a chat message content
+    ///
+    /// The content is an encoded `ChatContentMessage`.
+    fn words_from_content(content: &Vec<u8>) -> Vec<String> {
+        if let Ok(content_message) = rpc_proto::ChatContentMessage::decode(&content[..]) {
+            match content_message.message {
+                Some(rpc_proto::chat_content_message::Message::ChatContent(chat_content)) => {
+                    return Self::tokenize(&chat_content.text);
+                }
+                Some(rpc_proto::chat_content_message::Message::FileContent(file_content)) => {
+                    let text = format!(
+                        "{} {}",
+                        file_content.file_name, file_content.file_description
+                    );
+                    return Self::tokenize(&text);
+                }
+                _ => {}
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// create the index key of a word
+    fn index_key(word: &str, db_key: &[u8]) -> Vec<u8> {
+        let mut key = word.as_bytes().to_vec();
+        key.push(0);
+        key.extend_from_slice(db_key);
+        key
+    }
+
+    /// add a chat message to the search index
+    pub fn index_message(db_ref: &ChatAccountDb, db_key: &[u8], message: &rpc_proto::ChatMessage) {
+        for word in Self::words_from_content(&message.content) {
+            if let Err(e) = db_ref
+                .search
+                .insert(Self::index_key(&word, db_key), message.sent_at)
+            {
+                log::error!("Error saving chat search index: {}", e);
+            }
+        }
+    }
+
+    /// remove a chat message from the search index
+    pub fn remove_message(db_ref: &ChatAccountDb, db_key: &[u8], message: &rpc_proto::ChatMessage) {
+        for word in Self::words_from_content(&message.content) {
+            if let Err(e) = db_ref.search.remove(Self::index_key(&word, db_key)) {
+                log::error!("Error removing chat search index: {}", e);
+            }
+        }
+    }
+
+    /// Index the messages of a user account saved without index
+    ///
+    /// The index is only built once per user account.
+    /// Afterwards every message is indexed when it is saved.
+    pub fn init_index(db: &sled_extensions::Db, db_ref: &ChatAccountDb) {
+        let search_state: Tree<bool>;
+        match db.open_bincode_tree("chat_search_state") {
+            Ok(tree) => search_state = tree,
+            Err(e) => {
+                log::error!("Error opening chat search state: {}", e);
+                return;
+            }
+        }
+
+        match search_state.get(INDEXED_KEY) {
+            Ok(Some(_)) => return,
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Error reading chat search state: {}", e);
+                return;
+            }
+        }
+
+        if db_ref.messages.len() > 0 {
+            Self::rebuild(db_ref);
+        }
+
+        if let Err(e) = search_state.insert(INDEXED_KEY, true) {
+            log::error!("Error saving chat search state: {}", e);
+        }
+        if let Err(e) = search_state.flush() {
+            log::error!("Error chat search state flush: {}", e);
+        }
+    }
+
+    /// Rebuild the search index of a user account
+    ///
+    /// The index is cleared and all chat messages
+    /// are indexed again.
+    pub fn rebuild(db_ref: &ChatAccountDb) {
+        log::info!("rebuild chat search index");
+
+        // clear index
+        for res in db_ref.search.iter() {
+            if let Ok((key, _sent_at)) = res {
+                if let Err(e) = db_ref.search.remove(key) {
+                    log::error!("Error removing chat search index: {}", e);
+                }
+            }
+        }
+
+        // index all messages
+        for res in db_ref.messages.iter() {
+            if let Ok((db_key, message)) = res {
+                Self::index_message(db_ref, &db_key, &message);
+            }
+        }
+
+        if let Err(e) = db_ref.search.flush() {
+            log::error!("Error chat search index flush: {}", e);
+        }
+    }
+
+    /// find the db keys of all messages containing a word
+    ///
+    /// The word is matched as prefix of the indexed words.
+    fn find_word(db_ref: &ChatAccountDb, word: &str) -> BTreeSet<Vec<u8>> {
+        let mut db_keys: BTreeSet<Vec<u8>> = BTreeSet::new();
+
+        for res in db_ref.search.scan_prefix(word.as_bytes()) {
+            if let Ok((key, _sent_at)) = res {
+                if let Some(position) = key.iter().position(|byte| *byte == 0) {
+                    db_keys.insert(key[position + 1..].to_vec());
+                }
+            }
+        }
+
+        db_keys
+    }
+
+    /// Search the chat messages of a user account
+    ///
+    /// All words of the query need to be found in a message.
+    /// The results are filtered by group, sender and time
+    /// and sorted from the newest to the oldest message.
+    pub fn search(
+        account_id: &PeerId,
+        request: &rpc_proto::ChatSearchRequest,
+    ) -> rpc_proto::ChatSearchResponse {
+        let db_ref = ChatStorage::get_db_ref(account_id.to_owned());
+        let mut message_list: Vec<rpc_proto::ChatMessage> = Vec::new();
+
+        // find messages containing all words
+        let mut result: Option<BTreeSet<Vec<u8>>> = None;
+        for word in Self::tokenize(&request.query) {
+            let db_keys = Self::find_word(&db_ref, &word);
+            result = match result {
+                Some(keys) => Some(keys.intersection(&db_keys).cloned().collect()),
+                None => Some(db_keys),
+            };
+        }
+
+        // filter messages
+        if let Some(db_keys) = result {
+            for db_key in db_keys {
+                if request.group_id.len() > 0 && !db_key.starts_with(&request.group_id) {
+                    continue;
+                }
+
+                if let Ok(Some(message)) = db_ref.messages.get(&db_key) {
+                    if request.sender_id.len() > 0 && message.sender_id != request.sender_id {
+                        continue;
+                    }
+                    if request.from > 0 && message.sent_at < request.from {
+                        continue;
+                    }
+                    if request.to > 0 && message.sent_at > request.to {
+                        continue;
+                    }
+                    message_list.push(message);
+                }
+            }
+        }
+
+        // sort newest first and limit the results
+        message_list.sort_by(|a, b| b.sent_at.cmp(&a.sent_at));
+        let mut limit = request.limit;
+        if limit == 0 {
+            limit = DEFAULT_LIMIT;
+        }
+        message_list.truncate(limit as usize);
+
+        rpc_proto::ChatSearchResponse {
+            query: request.query.clone(),
+            message_list,
+        }
+    }
+}
@@ -12,8 +12,10 @@ use state::Storage;
 use std::collections::BTreeMap;
 use std::sync::RwLock;
 
+use super::receipt::ReadReceipt;
 use super::rpc_proto;
-use crate::services::group::{group_id::GroupId, GroupStorage};
+use super::search::ChatSearch;
+use crate::services::group::{group_id::GroupId, GroupRetention, GroupStorage};
 use crate::storage::database::DataBase;
 use crate::utilities::timestamp::Timestamp;
 use prost::Message;
@@ -28,6 +30,10 @@ pub struct ChatAccountDb {
     pub messages: Tree<rpc_proto::ChatMessage>,
     // message id => db key
     pub message_ids: Tree<Vec<u8>>,
+    // search index: word & db key => sent at
+    pub search: Tree<u64>,
+    // read receipts: group id & user id => read receipt
+    pub receipts: Tree<ReadReceipt>,
 }
 
 /// qaul Chat Conversation Storage
@@ -94,8 +100,14 @@ impl ChatStorage {
             match db_ref.message_ids.get(id) {
                 Ok(opt_key) => {
                     if let Some(db_key) = opt_key {
-                        if let Err(_e) = db_ref.messages.remove(&db_key) {
-                            log::error!("remove message error!");
+                        match db_ref.messages.remove(&db_key) {
+                            Ok(Some(message)) => {
+                                ChatSearch::remove_message(&db_ref, &db_key, &message);
+                            }
+                            Ok(None) => {}
+                            Err(_e) => {
+                                log::error!("remove message error!");
+                            }
                         }
                     }
                     if let Err(_e) = db_ref.message_ids.remove(id) {
@@ -112,6 +124,9 @@ impl ChatStorage {
         if let Err(_e) = db_ref.message_ids.flush() {
             log::error!("message ids storing error!");
         }
+        if let Err(_e) = db_ref.search.flush() {
+            log::error!("search index storing error!");
+        }
     }
 
     /// Save a Chat Message
@@ -174,8 +189,17 @@ impl ChatStorage {
             sent_at,
             received_at,
             content: content.encode_to_vec(),
+            reactions: Vec::new(),
+            edited_at: 0,
+            redacted: false,
         };
 
+        // add message to search index
+        ChatSearch::index_message(&db_ref, &db_key, &chat_message);
+        if let Err(e) = db_ref.search.flush() {
+            log::error!("Error chat search index flush: {}", e);
+        }
+
         // save message in data base
         if let Err(e) = db_ref.messages.insert(db_key.clone(), chat_message) {
             log::error!("Error saving chat message to data base: {}", e);
@@ -262,6 +286,331 @@ impl ChatStorage {
         }
     }
 
+    /// add or remove a reaction of a user to a chat message
+    ///
+    /// Every user can react once with each reaction.
+    /// Returns false if the message was not found in the group.
+    pub fn update_reaction(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        message_id: &Vec<u8>,
+        user_id: &PeerId,
+        reaction: &String,
+        remove: bool,
+        reacted_at: u64,
+    ) -> bool {
+        // get data base of user account
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        if let Ok(Some(key)) = db_ref.message_ids.get(message_id) {
+            // the message needs to be in the group
+            if !key.starts_with(group_id) {
+                log::warn!("reaction to chat message of another group");
+                return false;
+            }
+
+            if let Ok(Some(mut chat_msg)) = db_ref.messages.get(&key) {
+                let user_id = user_id.to_bytes();
+                chat_msg
+                    .reactions
+                    .retain(|entry| entry.user_id != user_id || entry.reaction != *reaction);
+
+                if !remove {
+                    chat_msg.reactions.push(rpc_proto::MessageReaction {
+                        user_id,
+                        reaction: reaction.to_owned(),
+                        reacted_at,
+                    });
+                }
+
+                // save message in data base
+                if let Err(e) = db_ref.messages.insert(key.clone(), chat_msg) {
+                    log::error!("Error saving chat message to data base: {}", e);
+                }
+                // flush trees to disk
+                if let Err(e) = db_ref.messages.flush() {
+                    log::error!("Error chat messages flush: {}", e);
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// edit or redact a chat message
+    ///
+    /// Only the sender of a message can edit it.
+    /// A redacted message has an empty text and can not be edited anymore.
+    /// Returns false if the message was not found or the edit was rejected.
+    pub fn update_edit(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        message_id: &Vec<u8>,
+        sender_id: &PeerId,
+        text: &String,
+        redact: bool,
+        edited_at: u64,
+    ) -> bool {
+        // get data base of user account
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        if let Ok(Some(key)) = db_ref.message_ids.get(message_id) {
+            // the message needs to be in the group
+            if !key.starts_with(group_id) {
+                log::warn!("edit of chat message of another group");
+                return false;
+            }
+
+            if let Ok(Some(mut chat_msg)) = db_ref.messages.get(&key) {
+                // only the sender can edit a message
+                if chat_msg.sender_id != sender_id.to_bytes() {
+                    log::warn!("edit of chat message by another user rejected");
+                    return false;
+                }
+                if chat_msg.redacted {
+                    log::warn!("edit of redacted chat message rejected");
+                    return false;
+                }
+
+                // only text messages can be edited
+                let mut chat_content;
+                match rpc_proto::ChatContentMessage::decode(&chat_msg.content[..]) {
+                    Ok(rpc_proto::ChatContentMessage {
+                        message: Some(rpc_proto::chat_content_message::Message::ChatContent(v)),
+                    }) => chat_content = v,
+                    _ => {
+                        log::warn!("edit of non text chat message rejected");
+                        return false;
+                    }
+                }
+
+                // remove old text from search index
+                ChatSearch::remove_message(&db_ref, &key, &chat_msg);
+
+                if redact {
+                    chat_content.text = String::new();
+                    chat_msg.redacted = true;
+                } else {
+                    chat_content.text = text.to_owned();
+                }
+                chat_msg.content = rpc_proto::ChatContentMessage {
+                    message: Some(rpc_proto::chat_content_message::Message::ChatContent(
+                        chat_content,
+                    )),
+                }
+                .encode_to_vec();
+                chat_msg.edited_at = edited_at;
+
+                // add new text to search index
+                ChatSearch::index_message(&db_ref, &key, &chat_msg);
+                if let Err(e) = db_ref.search.flush() {
+                    log::error!("Error chat search index flush: {}", e);
+                }
+
+                // save message in data base
+                if let Err(e) = db_ref.messages.insert(key.clone(), chat_msg) {
+                    log::error!("Error saving chat message to data base: {}", e);
+                }
+                // flush trees to disk
+                if let Err(e) = db_ref.messages.flush() {
+                    log::error!("Error chat messages flush: {}", e);
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// get a chat message by its message id
+    pub fn get_message(
+        account_id: &PeerId,
+        message_id: &Vec<u8>,
+    ) -> Option<rpc_proto::ChatMessage> {
+        // get data base of user account
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        if let Ok(Some(key)) = db_ref.message_ids.get(message_id) {
+            if let Ok(Some(chat_msg)) = db_ref.messages.get(&key) {
+                return Some(chat_msg);
+            }
+        }
+        None
+    }
+
+    /// get the message ids of the last messages of the senders in a group
+    ///
+    /// Returns a map of sender id => message id.
+    pub fn get_last_message_ids(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        sender_ids: &Vec<Vec<u8>>,
+    ) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        let mut message_ids: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+
+        // get data base of user account
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+
+        // loop from the newest to the oldest message
+        for res in db_ref.messages.scan_prefix(group_id).rev() {
+            if let Ok((_key, chat_msg)) = res {
+                if chat_msg.message_id.len() == 0
+                    || !sender_ids.contains(&chat_msg.sender_id)
+                    || message_ids.contains_key(&chat_msg.sender_id)
+                {
+                    continue;
+                }
+                message_ids.insert(chat_msg.sender_id, chat_msg.message_id);
+
+                // all senders found
+                if message_ids.len() == sender_ids.len() {
+                    break;
+                }
+            }
+        }
+
+        message_ids
+    }
+
+    /// get the group id of a chat message
+    pub fn get_group_id(account_id: &PeerId, message_id: &Vec<u8>) -> Option<Vec<u8>> {
+        // get data base of user account
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        if let Ok(Some(key)) = db_ref.message_ids.get(message_id) {
+            if let Ok(Some(chat_msg)) = db_ref.messages.get(&key) {
+                return Some(chat_msg.group_id);
+            }
+        }
+        None
+    }
+
+    /// Delete chat messages of a conversation
+    ///
+    /// The messages are addressed by their message ids and by an
+    /// index range. The range is only deleted if `last_index` is bigger
+    /// than 0.
+    ///
+    /// Returns the number of deleted messages.
+    pub fn delete_messages(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        message_ids: &Vec<Vec<u8>>,
+        first_index: u64,
+        last_index: u64,
+    ) -> u32 {
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        let mut db_keys: Vec<Vec<u8>> = Vec::new();
+
+        // get messages by id
+        for id in message_ids {
+            if let Ok(Some(db_key)) = db_ref.message_ids.get(id) {
+                if db_key.starts_with(group_id) {
+                    db_keys.push(db_key);
+                }
+            }
+        }
+
+        // get messages in index range
+        if last_index > 0 && first_index <= last_index {
+            let first_key = Self::get_db_key_from_vec(group_id, first_index);
+            let last_key = Self::get_db_key_from_vec(group_id, last_index);
+            for res in db_ref
+                .messages
+                .range(first_key.as_slice()..=last_key.as_slice())
+            {
+                if let Ok((db_key, _message)) = res {
+                    if !db_keys.contains(&db_key.to_vec()) {
+                        db_keys.push(db_key.to_vec());
+                    }
+                }
+            }
+        }
+
+        Self::remove_db_entries(&db_ref, &db_keys)
+    }
+
+    /// Apply the retention settings of a group to its conversation
+    ///
+    /// Messages older than the maximal age, disappeared messages,
+    /// and the oldest messages exceeding the maximal count are deleted.
+    ///
+    /// Returns the number of deleted messages.
+    pub fn apply_retention(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        retention: &GroupRetention,
+        now: u64,
+    ) -> u32 {
+        let db_ref = Self::get_db_ref(account_id.to_owned());
+        let mut db_keys: Vec<Vec<u8>> = Vec::new();
+        let mut remaining: Vec<Vec<u8>> = Vec::new();
+
+        // check age of messages
+        let (first_key, last_key) = Self::get_db_key_range(group_id);
+        for res in db_ref
+            .messages
+            .range(first_key.as_slice()..last_key.as_slice())
+        {
+            if let Ok((db_key, message)) = res {
+                if retention.max_age > 0 && message.sent_at + retention.max_age * 1000 < now {
+                    db_keys.push(db_key.to_vec());
+                } else if retention.disappear_after > 0
+                    && message.received_at + retention.disappear_after * 1000 < now
+                {
+                    db_keys.push(db_key.to_vec());
+                } else {
+                    remaining.push(db_key.to_vec());
+                }
+            }
+        }
+
+        // remove oldest messages exceeding the maximal count
+        if retention.max_count > 0 && remaining.len() > retention.max_count as usize {
+            let count = remaining.len() - retention.max_count as usize;
+            db_keys.extend(remaining.drain(..count));
+        }
+
+        Self::remove_db_entries(&db_ref, &db_keys)
+    }
+
+    /// remove messages and their message ids from the data base
+    ///
+    /// Returns the number of removed messages.
+    fn remove_db_entries(db_ref: &ChatAccountDb, db_keys: &Vec<Vec<u8>>) -> u32 {
+        let mut count: u32 = 0;
+
+        for db_key in db_keys {
+            match db_ref.messages.remove(db_key.clone()) {
+                Ok(Some(message)) => {
+                    count = count + 1;
+                    ChatSearch::remove_message(db_ref, db_key, &message);
+                    if message.message_id.len() > 0 {
+                        if let Err(e) = db_ref.message_ids.remove(message.message_id) {
+                            log::error!("Error removing chat message id: {}", e);
+                        }
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => log::error!("Error removing chat message: {}", e),
+            }
+        }
+
+        if count > 0 {
+            // flush trees to disk
+            if let Err(e) = db_ref.messages.flush() {
+                log::error!("Error chat messages flush: {}", e);
+            }
+            if let Err(e) = db_ref.message_ids.flush() {
+                log::error!("Error chat message_ids flush: {}", e);
+            }
+            if let Err(e) = db_ref.search.flush() {
+                log::error!("Error chat search index flush: {}", e);
+            }
+        }
+
+        count
+    }
+
     /// Get chat messages of a specific conversation from data base
     pub fn get_messages(account_id: PeerId, group_id: Vec<u8>) -> rpc_proto::ChatConversationList {
         // create empty messages list
@@ -332,7 +681,7 @@ impl ChatStorage {
     }
 
     /// get user account data base tree references
-    fn get_db_ref(account_id: PeerId) -> ChatAccountDb {
+    pub fn get_db_ref(account_id: PeerId) -> ChatAccountDb {
         // check if user account data exists
         {
             // get chat state
@@ -343,6 +692,8 @@ impl ChatStorage {
                 return ChatAccountDb {
                     messages: chat_user.messages.clone(),
                     message_ids: chat_user.message_ids.clone(),
+                    search: chat_user.search.clone(),
+                    receipts: chat_user.receipts.clone(),
                 };
             }
         }
@@ -354,6 +705,8 @@ impl ChatStorage {
         ChatAccountDb {
             messages: chat_user.messages.clone(),
             message_ids: chat_user.message_ids.clone(),
+            search: chat_user.search.clone(),
+            receipts: chat_user.receipts.clone(),
         }
     }
 
@@ -365,12 +718,19 @@ impl ChatStorage {
         // open trees
         let messages: Tree<rpc_proto::ChatMessage> = db.open_bincode_tree("chat_messages").unwrap();
         let message_ids: Tree<Vec<u8>> = db.open_bincode_tree("chat_message_ids").unwrap();
+        let search: Tree<u64> = db.open_bincode_tree("chat_search").unwrap();
+        let receipts: Tree<ReadReceipt> = db.open_bincode_tree("chat_read_markers").unwrap();
 
         let chat_user = ChatAccountDb {
             messages,
             message_ids,
+            search,
+            receipts,
         };
 
+        // build search index for messages saved without index
+        ChatSearch::init_index(&db, &chat_user);
+
         // get chat state for writing
         let mut chat = CHAT.get().write().unwrap();
 
@@ -10,28 +10,80 @@ use libp2p::PeerId;
 use prost::Message;
 use sled_extensions::{bincode::Tree, DbExt};
 use state::Storage;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::{convert::TryInto, sync::RwLock};
 
-use super::messaging::{proto, MessagingServiceType};
+use super::messaging::{proto, Messaging, MessagingServiceType, UnConfirmedMessage};
 use crate::node::user_accounts::{UserAccount, UserAccounts};
+use crate::router::table::RoutingTable;
 use crate::rpc::Rpc;
-use crate::storage::configuration::Configuration;
+use crate::storage::configuration::{Configuration, StorageOptions};
 use crate::storage::database::DataBase;
+use crate::utilities::qaul_id::QaulId;
+use crate::utilities::timestamp::Timestamp;
 
 /// Import protobuf message definition generated by
 /// the rust module prost-build.
 pub mod proto_rpc {
     include!("qaul.rpc.dtn.rs");
 }
-/// DTN message entry new_sig => {org_sig, size}
+
+/// DTN message entry new_sig => {org_sig, origin, size, received_at, ttl}
 /// This structure is used to update stroage node state(used size and message count)
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct DtnMessageEntry {
     // orignal DTN message signature
     pub org_sig: Vec<u8>,
+    // user id of the original sender
+    pub origin: Vec<u8>,
     // DTN payload size (bytes)
     pub size: u32,
+    // time stamp when the message was received
+    pub received_at: u64,
+    // time to live of the message in seconds
+    pub ttl: u64,
+}
+
+impl DtnMessageEntry {
+    /// check if this entry has expired at the time stamp `now`
+    ///
+    /// Entries with a time to live of 0 never expire.
+    pub fn is_expired(&self, now: u64) -> bool {
+        if self.ttl == 0 {
+            return false;
+        }
+        self.expires_at() < now
+    }
+
+    /// time stamp when this entry expires
+    ///
+    /// The time to live is received from the network,
+    /// the calculation saturates instead of overflowing.
+    pub fn expires_at(&self) -> u64 {
+        self.received_at
+            .saturating_add(self.ttl.saturating_mul(1000))
+    }
+}
+
+/// DTN storage usage of an origin user
+///
+/// origin user_id => {size, message_count}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct DtnUserEntry {
+    // used size of all stored messages (bytes)
+    pub size: u64,
+    // stored message count
+    pub message_count: u32,
+}
+
+/// delivery rate of stored messages to a receiver
+#[derive(Clone)]
+pub struct DtnDelivery {
+    // start of the current delivery interval
+    pub window_start: u64,
+    // messages delivered in the current interval
+    pub count: u32,
 }
 
 /// dtn storage state
@@ -46,6 +98,16 @@ pub struct DtnStorageState {
     // DTN message id table ref (org_dtn_sig => new_dtn_sig)
     // This is used to prevent dup DTN message incoming
     pub db_ref_id: Tree<Vec<u8>>,
+    // DTN usage per origin user table ref (origin user_id => DtnUserEntry)
+    pub db_ref_users: Tree<DtnUserEntry>,
+    // number of messages that expired since startup
+    pub expired_count: u32,
+    // time stamp of the last expiry sweep
+    pub last_sweep_at: u64,
+    // delivery rate per receiver q8id
+    pub delivery: BTreeMap<Vec<u8>, DtnDelivery>,
+    // signatures of the stored messages per receiver q8id
+    pub receivers: BTreeMap<Vec<u8>, BTreeSet<Vec<u8>>>,
 }
 
 /// mutable state of storge
@@ -64,12 +126,22 @@ impl Dtn {
         // open trees
         let dtn_messages: Tree<DtnMessageEntry> = db.open_bincode_tree("dtn-messages").unwrap();
         let db_ref_id: Tree<Vec<u8>> = db.open_bincode_tree("dtn-messages-ids").unwrap();
+        let db_ref_users: Tree<DtnUserEntry> = db.open_bincode_tree("dtn-users").unwrap();
 
         //calc current used size
+        //and index the stored messages by their receiver
         let mut used_size: u64 = 0;
+        let mut receivers: BTreeMap<Vec<u8>, BTreeSet<Vec<u8>>> = BTreeMap::new();
         for entry in dtn_messages.iter() {
-            if let Ok((_, ent)) = entry {
+            if let Ok((signature, ent)) = entry {
                 used_size = used_size + (ent.size as u64);
+
+                if let Some(unconfirmed) = Messaging::get_unconfirmed_message(&signature.to_vec()) {
+                    receivers
+                        .entry(QaulId::bytes_to_q8id(unconfirmed.receiver_id))
+                        .or_insert(BTreeSet::new())
+                        .insert(signature.to_vec());
+                }
             }
         }
         let storage_state = DtnStorageState {
@@ -77,6 +149,11 @@ impl Dtn {
             used_size,
             db_ref: dtn_messages,
             db_ref_id: db_ref_id,
+            db_ref_users,
+            expired_count: 0,
+            last_sweep_at: 0,
+            delivery: BTreeMap::new(),
+            receivers,
         };
 
         STORAGESTATE.set(RwLock::new(storage_state));
@@ -99,8 +176,10 @@ impl Dtn {
         }
     }
 
-    /// Get storage node user id
-    pub fn get_storage_user(user_id: &PeerId) -> Option<PeerId> {
+    /// Get all configured storage node user ids
+    fn get_storage_user_ids(user_id: &PeerId) -> Vec<PeerId> {
+        let mut storage_users: Vec<PeerId> = Vec::new();
+
         let user_profile;
         match Configuration::get_user(user_id.to_string()) {
             Some(user_prof) => {
@@ -108,7 +187,7 @@ impl Dtn {
             }
             None => {
                 log::error!("dtn module: user profile no exists");
-                return None;
+                return storage_users;
             }
         }
 
@@ -116,22 +195,53 @@ impl Dtn {
             match Self::id_string_to_bin(user.clone()) {
                 Ok(v) => match PeerId::from_bytes(&v) {
                     Ok(id) => {
-                        return Some(id.clone());
+                        storage_users.push(id);
                     }
                     _ => {}
                 },
                 _ => {}
             }
         }
-        None
+        storage_users
+    }
+
+    /// Get the storage node user ids a message is handed to
+    ///
+    /// Storage nodes that are currently reachable are preferred.
+    /// The number of storage nodes is limited by the configured
+    /// replication factor.
+    pub fn get_storage_users(user_id: &PeerId) -> Vec<PeerId> {
+        let mut replication: usize = 1;
+        if let Some(user_profile) = Configuration::get_user(user_id.to_string()) {
+            if user_profile.storage.replication > 1 {
+                replication = user_profile.storage.replication as usize;
+            }
+        }
+
+        // sort reachable storage nodes first
+        let mut online: Vec<PeerId> = Vec::new();
+        let mut offline: Vec<PeerId> = Vec::new();
+        for storage_id in Self::get_storage_user_ids(user_id) {
+            if RoutingTable::get_route_to_user(storage_id).is_some() {
+                online.push(storage_id);
+            } else {
+                offline.push(storage_id);
+            }
+        }
+        online.append(&mut offline);
+        online.truncate(replication);
+
+        online
     }
 
     /// process DTN message by role as stroage node
     fn process_storage_node_message(
         user_account: &UserAccount,
+        origin_id: &PeerId,
         receiver_id: &PeerId,
         org_sig: &Vec<u8>,
         dtn_payload: &Vec<u8>,
+        dtn_ttl: u64,
     ) -> (i32, i32) {
         let mut storage_state = STORAGESTATE.get().write().unwrap();
 
@@ -179,8 +289,33 @@ impl Dtn {
             );
         }
 
+        // check quota of the origin user
+        let mut user_usage = Self::get_user_usage(&storage_state, &origin_id.to_bytes());
+        let user_size_limit = (user_profile.storage.user_size_max as u64) * 1024 * 1024;
+        let user_message_limit = user_profile.storage.user_message_max;
+        if (user_size_limit > 0 && user_usage.size + (dtn_payload.len() as u64) > user_size_limit)
+            || (user_message_limit > 0 && user_usage.message_count + 1 > user_message_limit)
+        {
+            return (
+                super::messaging::proto::dtn_response::ResponseType::Rejected
+                    .try_into()
+                    .unwrap(),
+                super::messaging::proto::dtn_response::Reason::UserQuota
+                    .try_into()
+                    .unwrap(),
+            );
+        }
+
+        // time to live requested by the sender,
+        // limited by our own maximum (0 = unlimited)
+        let mut ttl = user_profile.storage.ttl_max;
+        if dtn_ttl > 0 && (ttl == 0 || dtn_ttl < ttl) {
+            ttl = dtn_ttl;
+        }
+
         // repack message and send
         let payload = super::messaging::proto::EnvelopPayload {
+            dtn_ttl: 0,
             payload: Some(super::messaging::proto::envelop_payload::Payload::Dtn(
                 dtn_payload.clone(),
             )),
@@ -201,7 +336,10 @@ impl Dtn {
                 signature.clone(),
                 DtnMessageEntry {
                     org_sig: org_sig.clone(),
+                    origin: origin_id.to_bytes(),
                     size: dtn_payload.len() as u32,
+                    received_at: Timestamp::get_timestamp(),
+                    ttl,
                 },
             ) {
                 log::error!("dnt entry storing error!");
@@ -211,6 +349,18 @@ impl Dtn {
                 }
             }
 
+            // update usage of origin user
+            user_usage.size = user_usage.size + (dtn_payload.len() as u64);
+            user_usage.message_count = user_usage.message_count + 1;
+            Self::save_user_usage(&storage_state, &origin_id.to_bytes(), user_usage);
+
+            // index message by receiver
+            storage_state
+                .receivers
+                .entry(QaulId::bytes_to_q8id(receiver_id.to_bytes()))
+                .or_insert(BTreeSet::new())
+                .insert(signature.clone());
+
             // save message id
             if let Err(_e) = storage_state
                 .db_ref_id
@@ -247,48 +397,529 @@ impl Dtn {
         // update storage state
     }
 
+    /// Remove a stored DTN message entry and update the storage state
+    ///
+    /// Returns true if the entry existed.
+    /// The trees are not flushed by this function.
+    fn remove_entry(state: &mut DtnStorageState, signature: &Vec<u8>) -> bool {
+        let entry;
+        match state.db_ref.remove(signature) {
+            Ok(Some(v)) => entry = v,
+            Ok(None) => return false,
+            Err(_) => {
+                log::error!("remove storage node entry error!");
+                return false;
+            }
+        }
+
+        // update storage node state
+        if state.used_size > entry.size as u64 {
+            state.used_size = state.used_size - (entry.size as u64);
+        } else {
+            state.used_size = 0;
+        }
+        if state.message_counts > 0 {
+            state.message_counts = state.message_counts - 1;
+        }
+
+        if let Err(_) = state.db_ref_id.remove(&entry.org_sig) {
+            log::error!("remove storage node id entry error!");
+        }
+
+        // remove message from the receiver index
+        state.receivers.retain(|_q8id, signatures| {
+            signatures.remove(signature);
+            signatures.len() > 0
+        });
+
+        // update usage of origin user
+        if entry.origin.len() > 0 {
+            let mut user_usage = Self::get_user_usage(state, &entry.origin);
+            if user_usage.size > entry.size as u64 {
+                user_usage.size = user_usage.size - (entry.size as u64);
+            } else {
+                user_usage.size = 0;
+            }
+            if user_usage.message_count > 0 {
+                user_usage.message_count = user_usage.message_count - 1;
+            }
+            Self::save_user_usage(state, &entry.origin, user_usage);
+        }
+
+        true
+    }
+
+    /// get the storage usage of an origin user
+    fn get_user_usage(state: &DtnStorageState, user_id: &Vec<u8>) -> DtnUserEntry {
+        match state.db_ref_users.get(user_id) {
+            Ok(Some(usage)) => usage,
+            Ok(None) => DtnUserEntry::default(),
+            Err(e) => {
+                log::error!("dtn user usage error: {}", e);
+                DtnUserEntry::default()
+            }
+        }
+    }
+
+    /// save the storage usage of an origin user
+    ///
+    /// Users without stored messages are removed from the table.
+    fn save_user_usage(state: &DtnStorageState, user_id: &Vec<u8>, usage: DtnUserEntry) {
+        if usage.message_count == 0 {
+            if let Err(e) = state.db_ref_users.remove(user_id) {
+                log::error!("dtn user usage removing error: {}", e);
+            }
+        } else {
+            if let Err(e) = state.db_ref_users.insert(user_id.clone(), usage) {
+                log::error!("dtn user usage storing error: {}", e);
+            }
+        }
+        if let Err(e) = state.db_ref_users.flush() {
+            log::error!("dtn user usage flushing error: {}", e);
+        }
+    }
+
+    /// flush the storage node trees to disk
+    fn flush_state(state: &DtnStorageState) {
+        if let Err(_) = state.db_ref.flush() {
+            log::error!("remove storage node entry flush error!");
+        }
+        if let Err(_) = state.db_ref_id.flush() {
+            log::error!("remove storage node id entry flush error!");
+        }
+    }
+
     /// this function is called when receive DTN response
     pub fn on_dtn_response(dtn_response: &super::messaging::proto::DtnResponse) {
         // check if storage node case
+        {
+            let mut state = STORAGESTATE.get().write().unwrap();
+            if Self::remove_entry(&mut state, &dtn_response.signature) {
+                Self::flush_state(&state);
+            }
+        }
+
+        // check if we handed over a message to a storage node
+        if let Some(unconfirmed) = Messaging::get_unconfirmed_message(&dtn_response.signature) {
+            if let MessagingServiceType::DtnOrigin = unconfirmed.message_type {
+                if dtn_response.response_type
+                    == super::messaging::proto::dtn_response::ResponseType::Accepted as i32
+                {
+                    if dtn_response.custody {
+                        Self::on_custody_transferred(&unconfirmed.message_id);
+                    }
+                } else {
+                    Self::on_dtn_rejected(&unconfirmed);
+                }
+            }
+        }
+    }
+
+    /// a storage node took over the custody of a message
+    ///
+    /// If it is a message we stored ourselves, it is removed
+    /// from our storage.
+    /// If we are the sender of the message, it is not
+    /// retransmitted anymore.
+    fn on_custody_transferred(signature: &Vec<u8>) {
         let mut state = STORAGESTATE.get().write().unwrap();
-        if state.db_ref.contains_key(&dtn_response.signature).unwrap() {
-            // update storage node state
-            let entry = state.db_ref.get(&dtn_response.signature).unwrap().unwrap();
-            if state.used_size > entry.size as u64 {
-                state.used_size = state.used_size + (entry.size as u64);
-            } else {
-                state.used_size = 0;
+        if Self::remove_entry(&mut state, signature) {
+            Self::flush_state(&state);
+            Messaging::remove_unconfirmed_message(signature);
+        } else {
+            Messaging::on_custody_transferred(signature);
+        }
+    }
+
+    /// a storage node rejected a DTN copy of a message
+    ///
+    /// The rejecting storage node is replaced by another storage node,
+    /// that doesn't hold a copy of the message yet.
+    /// Only storage nodes configured after the rejecting one are
+    /// considered, that a message is not passed around in circles.
+    /// If there is no storage node left and no other copy
+    /// is pending, the message is retransmitted again.
+    fn on_dtn_rejected(rejected: &UnConfirmedMessage) {
+        // get the original message
+        let unconfirmed;
+        match Messaging::get_unconfirmed_message(&rejected.message_id) {
+            Some(v) => unconfirmed = v,
+            None => return,
+        }
+        if unconfirmed.custody {
+            return;
+        }
+
+        // custody transfers of stored messages are retried
+        // by the delivery of the stored messages
+        if let MessagingServiceType::DtnStored = unconfirmed.message_type {
+            Messaging::on_dtn_rejected(&rejected.message_id);
+            return;
+        }
+
+        // storage nodes which haven't answered yet
+        let mut pending = Messaging::get_dtn_storage_nodes(&rejected.message_id);
+        pending.retain(|storage_node| *storage_node != rejected.receiver_id);
+
+        // get original container and user account
+        let container;
+        match proto::Container::decode(&unconfirmed.container[..]) {
+            Ok(v) => container = v,
+            Err(_) => return,
+        }
+        let user_account;
+        match container.envelope.as_ref() {
+            Some(envelope) => match PeerId::from_bytes(&envelope.sender_id) {
+                Ok(id) => match UserAccounts::get_by_id(id) {
+                    Some(v) => user_account = v,
+                    None => return,
+                },
+                Err(_) => return,
+            },
+            None => return,
+        }
+
+        // find a replacement storage node, reachable ones first
+        let mut replacement: Option<PeerId> = None;
+        let mut offline: Vec<PeerId> = Vec::new();
+        let mut after_rejected = false;
+        for storage_id in Self::get_storage_user_ids(&user_account.id) {
+            let storage_id_bytes = storage_id.to_bytes();
+            if storage_id_bytes == rejected.receiver_id {
+                after_rejected = true;
+                continue;
+            }
+            if !after_rejected
+                || storage_id_bytes == unconfirmed.receiver_id
+                || pending.contains(&storage_id_bytes)
+            {
+                continue;
             }
-            if state.message_counts > 0 {
-                state.message_counts = state.message_counts - 1;
+            if RoutingTable::get_route_to_user(storage_id).is_some() {
+                replacement = Some(storage_id);
+                break;
             }
+            offline.push(storage_id);
+        }
+        if replacement.is_none() && offline.len() > 0 {
+            replacement = Some(offline[0]);
+        }
 
-            // remove entry
-            if let Err(_) = state.db_ref.remove(&dtn_response.signature) {
-                log::error!("remove storage node entry error!");
-            } else {
-                if let Err(_) = state.db_ref.flush() {
-                    log::error!("remove storage node entry flush error!");
+        if let Some(storage_id) = replacement {
+            let mut dtn_ttl: u64 = 0;
+            if let Some(user_profile) = Configuration::get_user(user_account.id.to_string()) {
+                dtn_ttl = user_profile.storage.ttl_request;
+            }
+
+            log::info!(
+                "hand DTN message to replacement storage node {}",
+                storage_id.to_base58()
+            );
+            match Messaging::send_dtn_message(
+                &user_account,
+                &storage_id,
+                &container,
+                &rejected.message_id,
+                dtn_ttl,
+            ) {
+                Ok(_) => return,
+                Err(e) => log::error!("DTN scheduling error: {}", e),
+            }
+        }
+
+        // retransmit the message if no copy is pending anymore
+        if pending.len() == 0 {
+            Messaging::on_dtn_rejected(&rejected.message_id);
+        }
+    }
+
+    /// Deliver stored messages to their receivers
+    ///
+    /// This function is called periodically from the main event loop.
+    /// As soon as a route to the receiver of a stored message appears
+    /// in the routing table, the message is pushed to the receiver.
+    /// Only the messages of the online receivers are visited,
+    /// using the receiver index of the storage state.
+    /// Before that, we check whether the custody of the message
+    /// can be passed on to a better placed storage node.
+    ///
+    /// The deliveries are rate limited per receiver,
+    /// according to the storage options of the storage user.
+    /// A stored message is deleted once the receiver
+    /// confirmed it via a DTN response.
+    pub fn deliver_stored_messages() {
+        // get online users
+        let online_users = RoutingTable::get_online_users();
+        if online_users.len() == 0 {
+            let mut state = STORAGESTATE.get().write().unwrap();
+            state.delivery.clear();
+            return;
+        }
+
+        // get signatures of the stored messages of the online receivers
+        let mut signatures: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+        {
+            let state = STORAGESTATE.get().read().unwrap();
+            for (q8id, receiver_signatures) in &state.receivers {
+                if !online_users.contains_key(q8id) {
+                    continue;
+                }
+                for signature in receiver_signatures {
+                    signatures.push((q8id.clone(), signature.clone()));
                 }
             }
+        }
 
-            if let Err(_) = state.db_ref_id.remove(&entry.org_sig) {
-                log::error!("remove storage node id entry error!");
-            } else {
-                if let Err(_) = state.db_ref_id.flush() {
-                    log::error!("remove storage node id entry flush error!");
+        let now = Timestamp::get_timestamp();
+        for (q8id, signature) in signatures {
+            // get the repacked message
+            let unconfirmed;
+            match Messaging::get_unconfirmed_message(&signature) {
+                Some(v) => unconfirmed = v,
+                None => continue,
+            }
+
+            // custody transfer is in progress
+            if unconfirmed.scheduled_dtn {
+                continue;
+            }
+
+            // get the repacked container
+            let container;
+            match proto::Container::decode(&unconfirmed.container[..]) {
+                Ok(v) => container = v,
+                Err(_) => continue,
+            }
+
+            // get the storage options of the storage user
+            let mut options = StorageOptions::default();
+            if let Some(envelope) = container.envelope.as_ref() {
+                if let Ok(storage_id) = PeerId::from_bytes(&envelope.sender_id) {
+                    if let Some(user_profile) = Configuration::get_user(storage_id.to_string()) {
+                        options = user_profile.storage;
+                    }
+                }
+            }
+
+            // wait for the confirmation of the last delivery
+            if unconfirmed.scheduled && now < unconfirmed.last_sent + options.delivery_retry * 1000
+            {
+                continue;
+            }
+
+            let receiver_id;
+            match PeerId::from_bytes(&unconfirmed.receiver_id) {
+                Ok(v) => receiver_id = v,
+                Err(_) => continue,
+            }
+
+            // check delivery rate of the receiver
+            {
+                let mut state = STORAGESTATE.get().write().unwrap();
+                let delivery = state.delivery.entry(q8id).or_insert(DtnDelivery {
+                    window_start: now,
+                    count: 0,
+                });
+                if now > delivery.window_start + options.delivery_interval * 1000 {
+                    delivery.window_start = now;
+                    delivery.count = 0;
+                }
+                if delivery.count >= options.delivery_max_messages {
+                    continue;
+                }
+                delivery.count = delivery.count + 1;
+            }
+
+            // try to pass on the custody
+            if Self::transfer_custody(&signature, &receiver_id, &container, now) {
+                continue;
+            }
+
+            // push message to receiver
+            log::trace!("deliver stored DTN message to {}", receiver_id.to_base58());
+            Messaging::schedule_message(
+                receiver_id,
+                container,
+                true,
+                false,
+                false,
+                unconfirmed.is_dtn,
+            );
+            Messaging::update_last_sent(&signature, now);
+        }
+
+        // remove the delivery rates of receivers that
+        // went offline or got all their messages
+        let mut state = STORAGESTATE.get().write().unwrap();
+        let DtnStorageState {
+            delivery,
+            receivers,
+            ..
+        } = &mut *state;
+        delivery.retain(|q8id, _| online_users.contains_key(q8id) && receivers.contains_key(q8id));
+    }
+
+    /// Pass on the custody of a stored message to a better placed storage node
+    ///
+    /// We check whether one of our storage nodes is reachable via the same
+    /// neighbour as the receiver and closer to the receiver than we are.
+    /// If so, the message is handed over to this storage node.
+    /// Our stored message is removed once the storage node
+    /// confirmed the custody.
+    ///
+    /// Returns true if the message was handed over.
+    fn transfer_custody(
+        signature: &Vec<u8>,
+        receiver_id: &PeerId,
+        container: &proto::Container,
+        now: u64,
+    ) -> bool {
+        // check the route to the receiver
+        let receiver_route;
+        match RoutingTable::get_route_to_user(receiver_id.to_owned()) {
+            Some(v) => receiver_route = v,
+            None => return false,
+        }
+        if receiver_route.hc <= 1 {
+            return false;
+        }
+
+        // get original container and storage user account
+        let envelope;
+        match &container.envelope {
+            Some(v) => envelope = v,
+            None => return false,
+        }
+        let org_container;
+        match proto::EnvelopPayload::decode(&envelope.payload[..]) {
+            Ok(proto::EnvelopPayload {
+                payload: Some(proto::envelop_payload::Payload::Dtn(dtn_payload)),
+                ..
+            }) => match proto::Container::decode(&dtn_payload[..]) {
+                Ok(v) => org_container = v,
+                Err(_) => return false,
+            },
+            _ => return false,
+        }
+        let user_account;
+        match PeerId::from_bytes(&envelope.sender_id) {
+            Ok(id) => match UserAccounts::get_by_id(id) {
+                Some(v) => user_account = v,
+                None => return false,
+            },
+            Err(_) => return false,
+        }
+
+        // find a better placed storage node
+        for storage_id in Self::get_storage_user_ids(&user_account.id) {
+            if storage_id == *receiver_id || storage_id == user_account.id {
+                continue;
+            }
+            if let Some(storage_route) = RoutingTable::get_route_to_user(storage_id) {
+                if storage_route.node == receiver_route.node && storage_route.hc < receiver_route.hc
+                {
+                    // remaining time to live
+                    let mut dtn_ttl: u64 = 1;
+                    {
+                        let state = STORAGESTATE.get().read().unwrap();
+                        if let Ok(Some(entry)) = state.db_ref.get(signature) {
+                            if entry.ttl == 0 {
+                                // unlimited time to live
+                                dtn_ttl = 0;
+                            } else {
+                                let expires_at = entry.expires_at();
+                                if expires_at > now + 1000 {
+                                    dtn_ttl = (expires_at - now) / 1000;
+                                }
+                            }
+                        }
+                    }
+
+                    log::info!(
+                        "pass custody of DTN message on to storage node {}",
+                        storage_id.to_base58()
+                    );
+                    match Messaging::send_dtn_message(
+                        &user_account,
+                        &storage_id,
+                        &org_container,
+                        signature,
+                        dtn_ttl,
+                    ) {
+                        Ok(_) => {
+                            Messaging::set_scheduled_dtn(signature, true);
+                            return true;
+                        }
+                        Err(e) => {
+                            log::error!("custody transfer error: {}", e);
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Remove all expired DTN messages from the storage
+    ///
+    /// This function is called periodically from the main event loop.
+    /// Expired messages are removed from the storage node trees
+    /// and from the unconfirmed table, so that they are not
+    /// retransmitted anymore.
+    pub fn sweep_expired() {
+        let now = Timestamp::get_timestamp();
+
+        // collect and remove expired entries
+        let mut expired: Vec<Vec<u8>> = Vec::new();
+        {
+            let mut state = STORAGESTATE.get().write().unwrap();
+            state.last_sweep_at = now;
+
+            for entry in state.db_ref.iter() {
+                if let Ok((signature, dtn_entry)) = entry {
+                    if dtn_entry.is_expired(now) {
+                        expired.push(signature.to_vec());
+                    }
+                }
+            }
+
+            if expired.len() == 0 {
+                return;
+            }
+
+            for signature in &expired {
+                if Self::remove_entry(&mut state, signature) {
+                    state.expired_count = state.expired_count + 1;
                 }
             }
+            Self::flush_state(&state);
         }
+
+        // remove the repacked messages from the unconfirmed table
+        // after the storage state lock is released
+        for signature in &expired {
+            super::messaging::Messaging::remove_unconfirmed_message(signature);
+        }
+
+        log::info!("{} stored DTN messages expired", expired.len());
     }
 
     /// prtocess DTN messages from network
-    pub fn net(user_id: &PeerId, sender_id: &PeerId, signature: &Vec<u8>, dtn_payload: &Vec<u8>) {
+    pub fn net(
+        user_id: &PeerId,
+        sender_id: &PeerId,
+        signature: &Vec<u8>,
+        dtn_payload: &Vec<u8>,
+        dtn_ttl: u64,
+    ) {
         if let Some(user_account) = UserAccounts::get_by_id(*user_id) {
             match proto::Container::decode(&dtn_payload[..]) {
                 Ok(container) => {
                     let envelope = container.envelope.as_ref().unwrap();
 
+                    let mut custody = false;
                     let mut res: (i32, i32) = (
                         super::messaging::proto::dtn_response::ResponseType::Accepted
                             .try_into()
@@ -307,12 +938,38 @@ impl Dtn {
                                 container,
                             );
                         } else {
-                            res = Self::process_storage_node_message(
-                                &user_account,
-                                &receiver_id,
-                                signature,
-                                dtn_payload,
-                            );
+                            // the original sender of the message
+                            match PeerId::from_bytes(&envelope.sender_id) {
+                                Ok(origin_id) => {
+                                    res = Self::process_storage_node_message(
+                                        &user_account,
+                                        &origin_id,
+                                        &receiver_id,
+                                        signature,
+                                        dtn_payload,
+                                        dtn_ttl,
+                                    );
+                                }
+                                Err(e) => {
+                                    log::error!("invalid dtn origin id: {}", e);
+                                    res = (
+                                        super::messaging::proto::dtn_response::ResponseType::Rejected
+                                            .try_into()
+                                            .unwrap(),
+                                        super::messaging::proto::dtn_response::Reason::UserNotAccepted
+                                            .try_into()
+                                            .unwrap(),
+                                    );
+                                }
+                            }
+
+                            // we take over the custody of accepted messages
+                            if res.0
+                                == super::messaging::proto::dtn_response::ResponseType::Accepted
+                                    as i32
+                            {
+                                custody = true;
+                            }
                         }
 
                         // we send DTN response message
@@ -320,6 +977,7 @@ impl Dtn {
                             response_type: res.0,
                             reason: res.1,
                             signature: signature.clone(),
+                            custody,
                         };
                         let send_message = proto::Messaging {
                             message: Some(proto::messaging::Message::DtnResponse(dnt_response)),
@@ -366,6 +1024,8 @@ impl Dtn {
                                 used_size: state.used_size,
                                 dtn_message_count: state.message_counts,
                                 unconfirmed_count: unconfrimed_len as u32,
+                                expired_count: state.expired_count,
+                                last_sweep_at: state.last_sweep_at,
                             },
                         )),
                     };
@@ -400,6 +1060,8 @@ impl Dtn {
                                     proto_rpc::DtnConfigResponse {
                                         total_size: user_profile.storage.size_total,
                                         users: users,
+                                        user_size_max: user_profile.storage.user_size_max,
+                                        user_message_max: user_profile.storage.user_message_max,
                                     },
                                 )),
                             };
@@ -558,6 +1220,75 @@ impl Dtn {
                         }
                     }
                 }
+                Some(proto_rpc::dtn::Message::DtnUsageRequest(req)) => {
+                    let state = STORAGESTATE.get().read().unwrap();
+                    let mut users: Vec<proto_rpc::DtnUserUsage> = Vec::new();
+
+                    if req.user_id.len() > 0 {
+                        let usage = Self::get_user_usage(&state, &req.user_id);
+                        users.push(proto_rpc::DtnUserUsage {
+                            user_id: req.user_id,
+                            used_size: usage.size,
+                            message_count: usage.message_count,
+                        });
+                    } else {
+                        for entry in state.db_ref_users.iter() {
+                            if let Ok((user_id, usage)) = entry {
+                                users.push(proto_rpc::DtnUserUsage {
+                                    user_id: user_id.to_vec(),
+                                    used_size: usage.size,
+                                    message_count: usage.message_count,
+                                });
+                            }
+                        }
+                    }
+
+                    let proto_message = proto_rpc::Dtn {
+                        message: Some(proto_rpc::dtn::Message::DtnUsageResponse(
+                            proto_rpc::DtnUsageResponse { users },
+                        )),
+                    };
+
+                    // send message
+                    Rpc::send_message(
+                        proto_message.encode_to_vec(),
+                        crate::rpc::proto::Modules::Dtn.into(),
+                        "".to_string(),
+                        Vec::new(),
+                    );
+                }
+                Some(proto_rpc::dtn::Message::DtnSetUserQuotaRequest(req)) => {
+                    match Configuration::get_user(my_user_id.to_string()) {
+                        // CHANGE: save it in user profile, not to configuration directly.
+                        Some(_user_profile) => {
+                            Configuration::update_user_quota(
+                                my_user_id.to_string(),
+                                req.user_size_max,
+                                req.user_message_max,
+                            );
+                            Configuration::save();
+
+                            let proto_message = proto_rpc::Dtn {
+                                message: Some(proto_rpc::dtn::Message::DtnSetUserQuotaResponse(
+                                    proto_rpc::DtnSetUserQuotaResponse {
+                                        status: true,
+                                        message: "".to_string(),
+                                    },
+                                )),
+                            };
+                            // send message
+                            Rpc::send_message(
+                                proto_message.encode_to_vec(),
+                                crate::rpc::proto::Modules::Dtn.into(),
+                                "".to_string(),
+                                Vec::new(),
+                            );
+                        }
+                        None => {
+                            log::error!("user profile does not exists");
+                        }
+                    }
+                }
                 _ => {
                     log::error!("Unhandled Protobuf DTN RPC message");
                 }
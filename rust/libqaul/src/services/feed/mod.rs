@@ -6,6 +6,18 @@
 //! The feed service sends and receives feed messages into the network.
 //! Feed messages are not encrypted and for everybody to read.
 //! They should reach everyone in the network.
+//!
+//! Feed messages are only kept for the configured retention.
+//! Messages exceeding the maximal age or storage size are
+//! pruned periodically. The ids of pruned messages are kept
+//! as tombstones, so that they are not synchronized again
+//! from the neighbours.
+//!
+//! Messages of blocked users are dropped and not forwarded.
+//! The number of messages received per sender is rate limited,
+//! messages exceeding the flood rate are neither stored nor forwarded.
+//! Muted senders are only hidden locally, their messages
+//! are still stored and forwarded.
 
 //use bs58::decode;
 use libp2p::{
@@ -28,6 +40,7 @@ use crate::connections::{internet::Internet, lan::Lan, ConnectionModule};
 use crate::router;
 use crate::router::flooder::Flooder;
 use crate::rpc::Rpc;
+use crate::storage::configuration::Configuration;
 use crate::storage::database::DataBase;
 use crate::utilities::timestamp;
 
@@ -40,6 +53,9 @@ pub mod proto_net {
     include!("qaul.net.feed.rs");
 }
 
+/// default number of messages in a feed page
+const FEED_PAGE_LIMIT: u32 = 50;
+
 /// mutable state of feed messages
 static FEED: Storage<RwLock<Feed>> = Storage::new();
 
@@ -102,8 +118,28 @@ pub struct Feed {
 
     // sled data base tree
     pub tree: Tree<FeedMessageData>,
+    // sled data base tree of pruned message ids
+    //
+    // message_id => time pruned
+    pub tree_pruned: Tree<u64>,
+    // sled data base tree of muted senders
+    //
+    // sender_id => time muted
+    pub tree_muted: Tree<u64>,
     // last recent message
     pub last_message: u64,
+    // storage size of all messages in bytes
+    pub size: u64,
+    // forwarded messages per sender
+    pub flood_rate: BTreeMap<Vec<u8>, FloodRate>,
+}
+
+/// Forwarded messages of a sender within the flood rate period
+pub struct FloodRate {
+    // start of the period in milli seconds
+    pub period_start: u64,
+    // number of forwarded messages
+    pub count: u32,
 }
 
 impl Feed {
@@ -113,6 +149,8 @@ impl Feed {
         let db = DataBase::get_node_db();
         let tree: Tree<FeedMessageData> = db.open_bincode_tree("feed").unwrap();
         let tree_ids: Tree<u64> = db.open_bincode_tree("feed_id").unwrap();
+        let tree_pruned: Tree<u64> = db.open_bincode_tree("feed_pruned").unwrap();
+        let tree_muted: Tree<u64> = db.open_bincode_tree("feed_muted").unwrap();
 
         // get last key
         let last_message: u64;
@@ -138,12 +176,24 @@ impl Feed {
             }
         }
 
+        // calculate storage size
+        let mut size: u64 = 0;
+        for res in tree.iter() {
+            if let Ok((_id, message)) = res {
+                size = size + Self::message_size(&message);
+            }
+        }
+
         // create feed messages state
         let feed = Feed {
             messages: BTreeMap::new(),
             tree,
             tree_ids,
+            tree_pruned,
+            tree_muted,
             last_message,
+            size,
+            flood_rate: BTreeMap::new(),
         };
         FEED.set(RwLock::new(feed));
     }
@@ -235,19 +285,47 @@ impl Feed {
                             return;
                         }
 
+                        // drop messages of blocked users
+                        if router::users::Users::is_blocked(&user_id_decoded) {
+                            log::trace!("feed message of blocked user {} dropped", user_id_decoded);
+                            Self::save_dropped(&feed_container.signature);
+                            return;
+                        }
+
+                        // drop messages exceeding the retention
+                        if Self::is_expired(message.time) {
+                            log::trace!(
+                                "feed message {:?} exceeds retention",
+                                feed_container.signature
+                            );
+                            return;
+                        }
+
                         // check if message exists is in feed store
                         let mut new_message = true;
 
                         {
                             let feed = FEED.get().read().unwrap();
 
-                            if feed.messages.contains_key(&feed_container.signature) {
+                            if feed.messages.contains_key(&feed_container.signature)
+                                || Self::is_known(&feed, &feed_container.signature)
+                            {
                                 new_message = false;
                             }
                         }
 
                         // check if message exists
                         if new_message {
+                            // drop the message, if the sender
+                            // exceeded its flood rate
+                            if !Self::check_flood_rate(&message.sender) {
+                                log::trace!(
+                                    "flood rate of {} exceeded, message dropped",
+                                    user_id_decoded
+                                );
+                                return;
+                            }
+
                             // write message to store
                             Self::save_message(feed_container.signature.clone(), feed_content);
 
@@ -293,8 +371,21 @@ impl Feed {
         content: String,
         time: u64,
     ) {
+        // drop messages exceeding the retention
+        if Self::is_expired(time) {
+            return;
+        }
+
+        // drop messages of blocked users
+        if let Ok(user_id) = PeerId::from_bytes(sender_id) {
+            if router::users::Users::is_blocked(&user_id) {
+                Self::save_dropped(message_id);
+                return;
+            }
+        }
+
         let mut feed = FEED.get().write().unwrap();
-        if let Some(_index) = feed.tree_ids.get(&message_id[..]).unwrap() {
+        if Self::is_known(&feed, message_id) {
             return;
         }
 
@@ -324,6 +415,7 @@ impl Feed {
         };
 
         // save to data base
+        feed.size = feed.size + Self::message_size(&message_data);
         if let Err(e) = feed.tree.insert(&last_message.to_be_bytes(), message_data) {
             log::error!("Error saving feed message to data base: {}", e);
         } else {
@@ -371,6 +463,7 @@ impl Feed {
         };
 
         // save to data base
+        feed.size = feed.size + Self::message_size(&message_data);
         if let Err(e) = feed.tree.insert(&last_message.to_be_bytes(), message_data) {
             log::error!("Error saving feed message to data base: {}", e);
         } else {
@@ -417,21 +510,244 @@ impl Feed {
     }
 
     //return missing feed ids to request to the neighbour
+    //
+    //Pruned messages are not requested again.
     pub fn process_received_feed_ids(ids: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
         let mut missing_ids: Vec<Vec<u8>> = vec![];
 
         let feed = FEED.get().read().unwrap();
         for id in ids {
-            match feed.tree_ids.get(&id[..]).unwrap() {
-                Some(_index) => {}
-                _ => {
-                    missing_ids.push(id.clone());
-                }
+            if !Self::is_known(&feed, id) {
+                missing_ids.push(id.clone());
             }
         }
         missing_ids
     }
 
+    /// check if a message is stored or was pruned
+    fn is_known(feed: &Feed, message_id: &Vec<u8>) -> bool {
+        match feed.tree_ids.contains_key(&message_id[..]) {
+            Ok(true) => return true,
+            Ok(false) => {}
+            Err(e) => log::error!("{}", e),
+        }
+        match feed.tree_pruned.contains_key(&message_id[..]) {
+            Ok(true) => return true,
+            Ok(false) => {}
+            Err(e) => log::error!("{}", e),
+        }
+        false
+    }
+
+    /// remember the id of a dropped message
+    ///
+    /// The id is kept as a tombstone, so that the message
+    /// is not requested again from the neighbours.
+    fn save_dropped(message_id: &Vec<u8>) {
+        let feed = FEED.get().read().unwrap();
+
+        if let Err(e) = feed
+            .tree_pruned
+            .insert(&message_id[..], timestamp::Timestamp::get_timestamp())
+        {
+            log::error!("Error saving dropped feed id: {}", e);
+        }
+        if let Err(e) = feed.tree_pruned.flush() {
+            log::error!("Error when flushing data base to disk: {}", e);
+        }
+    }
+
+    /// check if a message sent at this time exceeds the retention
+    fn is_expired(timestamp_sent: u64) -> bool {
+        let age_max = Configuration::get().feed.message_age_max;
+        if age_max == 0 {
+            return false;
+        }
+        let now = timestamp::Timestamp::get_timestamp();
+        now.saturating_sub(timestamp_sent) > age_max * 1000
+    }
+
+    /// check and count a message for the flood rate of its sender
+    ///
+    /// Returns false, if the sender exceeded the maximal number
+    /// of received messages within the flood rate period.
+    fn check_flood_rate(sender_id: &Vec<u8>) -> bool {
+        let (rate_max, period) = {
+            let config = Configuration::get();
+            (
+                config.feed.flood_rate_max,
+                config.feed.flood_rate_period * 1000,
+            )
+        };
+        if rate_max == 0 {
+            return true;
+        }
+        let now = timestamp::Timestamp::get_timestamp();
+
+        let mut feed = FEED.get().write().unwrap();
+        let rate = feed
+            .flood_rate
+            .entry(sender_id.to_owned())
+            .or_insert(FloodRate {
+                period_start: now,
+                count: 0,
+            });
+
+        // start a new period
+        if now.saturating_sub(rate.period_start) >= period {
+            rate.period_start = now;
+            rate.count = 0;
+        }
+
+        if rate.count >= rate_max {
+            return false;
+        }
+        rate.count = rate.count + 1;
+        true
+    }
+
+    /// check if a sender is muted
+    fn is_muted(feed: &Feed, sender_id: &Vec<u8>) -> bool {
+        match feed.tree_muted.contains_key(&sender_id[..]) {
+            Ok(muted) => muted,
+            Err(e) => {
+                log::error!("{}", e);
+                false
+            }
+        }
+    }
+
+    /// mute or unmute a sender
+    fn set_muted(sender_id: &Vec<u8>, mute: bool) {
+        let feed = FEED.get().read().unwrap();
+
+        if mute {
+            if let Err(e) = feed
+                .tree_muted
+                .insert(&sender_id[..], timestamp::Timestamp::get_timestamp())
+            {
+                log::error!("Error saving muted sender: {}", e);
+            }
+        } else {
+            if let Err(e) = feed.tree_muted.remove(&sender_id[..]) {
+                log::error!("Error removing muted sender: {}", e);
+            }
+        }
+
+        if let Err(e) = feed.tree_muted.flush() {
+            log::error!("Error when flushing data base to disk: {}", e);
+        }
+    }
+
+    /// get the list of muted senders
+    fn get_muted() -> proto::FeedMuteList {
+        let feed = FEED.get().read().unwrap();
+        let mut sender_ids: Vec<Vec<u8>> = Vec::new();
+
+        for res in feed.tree_muted.iter() {
+            match res {
+                Ok((sender_id, _muted_at)) => sender_ids.push(sender_id.to_vec()),
+                Err(e) => log::error!("{}", e),
+            }
+        }
+
+        proto::FeedMuteList { sender_ids }
+    }
+
+    /// storage size of a message in bytes
+    fn message_size(message: &FeedMessageData) -> u64 {
+        (message.content.len() + message.message_id.len() + message.sender_id.len()) as u64
+    }
+
+    /// Prune feed messages
+    ///
+    /// Removes all messages exceeding the maximal age and
+    /// the oldest messages exceeding the maximal storage size.
+    /// A tombstone is kept for every pruned message and
+    /// removed after the maximal tombstone age.
+    /// The newest message is always kept, as the message
+    /// index is continued from it after a restart.
+    /// This function is called periodically from the main event loop.
+    pub fn prune() {
+        let options = Configuration::get().feed.clone();
+        let size_max = options.size_max as u64 * 1024 * 1024;
+        let now = timestamp::Timestamp::get_timestamp();
+
+        let mut feed = FEED.get().write().unwrap();
+
+        // collect messages to prune, oldest first
+        let mut size = feed.size;
+        let mut pruned: Vec<FeedMessageData> = Vec::new();
+        for res in feed.tree.iter() {
+            match res {
+                Ok((_id, message)) => {
+                    if message.index == feed.last_message {
+                        continue;
+                    }
+                    let expired = options.message_age_max > 0
+                        && now.saturating_sub(message.timestamp_sent)
+                            > options.message_age_max * 1000;
+                    let oversize = size_max > 0 && size > size_max;
+                    if expired || oversize {
+                        size = size.saturating_sub(Self::message_size(&message));
+                        pruned.push(message);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Error retrieving feed message from data base: {}", e);
+                }
+            }
+        }
+
+        // remove messages
+        for message in &pruned {
+            feed.messages.remove(&message.message_id);
+            if let Err(e) = feed.tree.remove(&message.index.to_be_bytes()) {
+                log::error!("Error removing feed message: {}", e);
+            }
+            if let Err(e) = feed.tree_ids.remove(&message.message_id[..]) {
+                log::error!("Error removing feed id: {}", e);
+            }
+            if let Err(e) = feed.tree_pruned.insert(&message.message_id[..], now) {
+                log::error!("Error saving pruned feed id: {}", e);
+            }
+        }
+        feed.size = size;
+
+        // remove expired flood rates
+        let period = options.flood_rate_period * 1000;
+        feed.flood_rate
+            .retain(|_sender_id, rate| now.saturating_sub(rate.period_start) < period);
+
+        // remove old tombstones
+        if options.tombstone_age_max > 0 {
+            for res in feed.tree_pruned.iter() {
+                if let Ok((id, timestamp_pruned)) = res {
+                    if now.saturating_sub(timestamp_pruned) > options.tombstone_age_max * 1000 {
+                        if let Err(e) = feed.tree_pruned.remove(id) {
+                            log::error!("Error removing pruned feed id: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if pruned.len() > 0 {
+            log::trace!("{} feed messages pruned", pruned.len());
+        }
+
+        // flush trees to disk
+        if let Err(e) = feed.tree.flush() {
+            log::error!("Error when flushing data base to disk: {}", e);
+        }
+        if let Err(e) = feed.tree_ids.flush() {
+            log::error!("Error when flushing data base to disk: {}", e);
+        }
+        if let Err(e) = feed.tree_pruned.flush() {
+            log::error!("Error when flushing data base to disk: {}", e);
+        }
+    }
+
     pub fn get_messges_by_ids(ids: &Vec<Vec<u8>>) -> Vec<(Vec<u8>, Vec<u8>, String, u64)> {
         let mut res: Vec<(Vec<u8>, Vec<u8>, String, u64)> = vec![];
         let feed = FEED.get().read().unwrap();
@@ -472,39 +788,19 @@ impl Feed {
             for res in feed.tree.range(first_message_bytes.as_slice()..) {
                 match res {
                     Ok((_id, message)) => {
+                        // skip muted senders
+                        if Self::is_muted(&feed, &message.sender_id) {
+                            continue;
+                        }
+
                         if feed.messages.contains_key(&message.message_id) {
                             log::info!("key exist");
                         } else {
                             log::info!("key find error");
                         }
 
-                        let sender_id_base58 =
-                            bs58::encode(message.sender_id.clone()).into_string();
-
-                        //create timestamp
-                        let time_sent = timestamp::Timestamp::create_time();
-
-                        // create message
-                        let feed_message = proto::FeedMessage {
-                            sender_id: message.sender_id.clone(),
-                            // DEPRECATED
-                            sender_id_base58,
-                            message_id: message.message_id.clone(),
-                            // DEPRECATED
-                            message_id_base58: bs58::encode(message.message_id).into_string(),
-                            // DEPRECATED
-                            time_sent: humantime::format_rfc3339(time_sent.clone()).to_string(),
-                            timestamp_sent: message.timestamp_sent,
-                            // DEPRECATED
-                            time_received: humantime::format_rfc3339(time_sent).to_string(),
-                            timestamp_received: message.timestamp_received,
-                            content: message.content.clone(),
-                            // data base index
-                            index: message.index,
-                        };
-
                         // add message to feed list
-                        feed_list.feed_message.push(feed_message);
+                        feed_list.feed_message.push(Self::to_rpc_message(message));
                     }
                     Err(e) => {
                         log::error!("Error retrieving feed message from data base: {}", e);
@@ -516,6 +812,122 @@ impl Feed {
         feed_list
     }
 
+    /// Get a page of messages from data base
+    ///
+    /// The messages are read from the data base in the
+    /// requested direction, starting after the start index.
+    /// Messages not matching the sender and time filters are skipped.
+    fn get_page(request: &proto::FeedPageRequest) -> proto::FeedPage {
+        let mut limit = request.limit as usize;
+        if limit == 0 {
+            limit = FEED_PAGE_LIMIT as usize;
+        }
+
+        // get feed message store
+        let feed = FEED.get().read().unwrap();
+
+        let iterator: Box<dyn Iterator<Item = _>> =
+            match proto::FeedPageDirection::from_i32(request.direction) {
+                Some(proto::FeedPageDirection::FeedNewer) => {
+                    let first_message = request.start_index + 1;
+                    Box::new(feed.tree.range(first_message.to_be_bytes().to_vec()..))
+                }
+                _ => {
+                    if request.start_index == 0 {
+                        Box::new(feed.tree.iter().rev())
+                    } else {
+                        Box::new(
+                            feed.tree
+                                .range(..request.start_index.to_be_bytes().to_vec())
+                                .rev(),
+                        )
+                    }
+                }
+            };
+
+        let mut page = proto::FeedPage {
+            feed_message: Vec::new(),
+            has_more: false,
+        };
+
+        for res in iterator {
+            match res {
+                Ok((_id, message)) => {
+                    // filter messages
+                    if request.sender_id.len() > 0 && message.sender_id != request.sender_id {
+                        continue;
+                    }
+                    if Self::is_muted(&feed, &message.sender_id) {
+                        continue;
+                    }
+                    if message.timestamp_sent < request.time_from {
+                        continue;
+                    }
+                    if request.time_to > 0 && message.timestamp_sent >= request.time_to {
+                        continue;
+                    }
+
+                    // check if page is full
+                    if page.feed_message.len() >= limit {
+                        page.has_more = true;
+                        break;
+                    }
+
+                    page.feed_message.push(Self::to_rpc_message(message));
+                }
+                Err(e) => {
+                    log::error!("Error retrieving feed message from data base: {}", e);
+                }
+            }
+        }
+
+        page
+    }
+
+    /// Get a single message by its message id
+    fn get_message(message_id: &Vec<u8>) -> Option<proto::FeedMessage> {
+        let feed = FEED.get().read().unwrap();
+
+        match feed.tree_ids.get(&message_id[..]) {
+            Ok(Some(index)) => match feed.tree.get(index.to_be_bytes()) {
+                Ok(Some(message)) => return Some(Self::to_rpc_message(message)),
+                Ok(None) => {}
+                Err(e) => log::error!("Error retrieving feed message from data base: {}", e),
+            },
+            Ok(None) => {}
+            Err(e) => log::error!("Error retrieving feed id from data base: {}", e),
+        }
+
+        None
+    }
+
+    /// Convert a stored message to a RPC feed message
+    fn to_rpc_message(message: FeedMessageData) -> proto::FeedMessage {
+        let sender_id_base58 = bs58::encode(message.sender_id.clone()).into_string();
+
+        //create timestamp
+        let time_sent = timestamp::Timestamp::create_time();
+
+        // create message
+        proto::FeedMessage {
+            sender_id: message.sender_id.clone(),
+            // DEPRECATED
+            sender_id_base58,
+            message_id: message.message_id.clone(),
+            // DEPRECATED
+            message_id_base58: bs58::encode(message.message_id).into_string(),
+            // DEPRECATED
+            time_sent: humantime::format_rfc3339(time_sent.clone()).to_string(),
+            timestamp_sent: message.timestamp_sent,
+            // DEPRECATED
+            time_received: humantime::format_rfc3339(time_sent).to_string(),
+            timestamp_received: message.timestamp_received,
+            content: message.content.clone(),
+            // data base index
+            index: message.index,
+        }
+    }
+
     /// Sign a message with the private key
     /// The signature can be validated with the corresponding public key.
     pub fn sign_message(buf: &Vec<u8>, keys: Keypair) -> Vec<u8> {
@@ -560,6 +972,62 @@ impl Feed {
                             Vec::new(),
                         );
                     }
+                    Some(proto::feed::Message::PageRequest(page_request)) => {
+                        // get feed page from data base
+                        let page = Self::get_page(&page_request);
+
+                        // pack message
+                        let proto_message = proto::Feed {
+                            message: Some(proto::feed::Message::Page(page)),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Feed.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(proto::feed::Message::LookupRequest(lookup_request)) => {
+                        let feed_message = Self::get_message(&lookup_request.message_id);
+
+                        // pack message
+                        let proto_message = proto::Feed {
+                            message: Some(proto::feed::Message::LookupResponse(
+                                proto::FeedLookupResponse {
+                                    message_id: lookup_request.message_id,
+                                    found: feed_message.is_some(),
+                                    feed_message,
+                                },
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Feed.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
+                    Some(proto::feed::Message::MuteRequest(mute_request)) => {
+                        Self::set_muted(&mute_request.sender_id, mute_request.mute);
+                    }
+                    Some(proto::feed::Message::MuteListRequest(_)) => {
+                        // pack message
+                        let proto_message = proto::Feed {
+                            message: Some(proto::feed::Message::MuteList(Self::get_muted())),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Feed.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+                    }
                     Some(proto::feed::Message::Send(send_feed)) => {
                         // print message
                         log::trace!("feed message received: {}", send_feed.content.clone());
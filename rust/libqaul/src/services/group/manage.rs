@@ -170,6 +170,44 @@ impl GroupManage {
         Err("can not find group".to_string())
     }
 
+    /// set the message retention settings of a group from RPC command
+    ///
+    /// `account_id` the user account ID
+    pub fn set_retention(
+        account_id: &PeerId,
+        group_id: &Vec<u8>,
+        retention: super::GroupRetention,
+    ) -> Result<(), String> {
+        if let Some(mut group) = GroupStorage::get_group(account_id.to_owned(), group_id.to_owned())
+        {
+            // check if administrator
+            if let Some(member) = group.get_member(&account_id.to_bytes()) {
+                // check permission
+                if member.role != 255 {
+                    return Err(
+                        "you don't have the permissions to change the retention of this group"
+                            .to_string(),
+                    );
+                }
+            } else {
+                return Err("you are not a member for this group".to_string());
+            }
+
+            // set retention
+            group.retention = retention;
+
+            // update revision
+            group.revision = group.revision + 1;
+
+            // save group
+            GroupStorage::save_group(account_id.to_owned(), group);
+
+            return Ok(());
+        }
+
+        Err("can not find group".to_string())
+    }
+
     /// get a new message ID
     pub fn get_new_message_id(account_id: &PeerId, group_id: &Vec<u8>) -> Vec<u8> {
         if let Some(mut group) = GroupStorage::get_group(account_id.to_owned(), group_id.to_owned())
@@ -229,6 +267,7 @@ impl GroupManage {
             last_message_at: group.last_message_at,
             last_message: group.last_message_data,
             last_message_sender_id: group.last_message_sender_id,
+            retention: Some(group.retention.to_rpc()),
         };
         Ok(res)
     }
@@ -268,6 +307,7 @@ impl GroupManage {
                         last_message_at: group.last_message_at,
                         last_message: group.last_message_data,
                         last_message_sender_id: group.last_message_sender_id,
+                        retention: Some(group.retention.to_rpc()),
                     };
                     res.groups.push(grp);
                 }
@@ -312,6 +352,7 @@ impl GroupManage {
                             last_message_at: 0,
                             last_message: Vec::new(),
                             last_message_sender_id: Vec::new(),
+                            retention: Some(invite.group.retention.to_rpc()),
                         }),
                     };
 
@@ -409,6 +450,9 @@ impl GroupManage {
         group.created_at = notify.created_at;
         group.revision = notify.revision;
         group.members = members;
+        if let Some(retention) = &notify.retention {
+            group.retention = super::GroupRetention::from_net(retention);
+        }
 
         // activate group after invite accept
         if group.status == super::proto_rpc::GroupStatus::InviteAccepted as i32 {
@@ -76,6 +76,7 @@ impl Member {
                         created_at: group.created_at,
                         revision: group.revision,
                         members,
+                        retention: Some(group.retention.to_net()),
                     }),
                 },
             )),
@@ -289,6 +290,9 @@ impl Member {
         group.created_at = group_info.created_at;
         group.status = super::proto_rpc::GroupStatus::InviteAccepted as i32;
         group.revision = group_info.revision;
+        if let Some(retention) = &group_info.retention {
+            group.retention = super::GroupRetention::from_net(retention);
+        }
 
         let invited = super::GroupInvited {
             sender_id: sender_id.to_bytes(),
@@ -56,6 +56,63 @@ pub struct GroupMember {
     pub last_message_index: u32,
 }
 
+/// Message retention settings of a group
+///
+/// A value of 0 disables the setting.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GroupRetention {
+    /// maximal age of a message in seconds
+    pub max_age: u64,
+    /// maximal number of messages in the conversation
+    pub max_count: u32,
+    /// time in seconds after which a message disappears,
+    /// once it arrived on this node
+    pub disappear_after: u64,
+}
+
+impl GroupRetention {
+    /// check if any retention setting is active
+    pub fn is_active(&self) -> bool {
+        self.max_age > 0 || self.max_count > 0 || self.disappear_after > 0
+    }
+
+    /// create retention settings from a network message
+    pub fn from_net(retention: &proto_net::GroupRetention) -> GroupRetention {
+        GroupRetention {
+            max_age: retention.max_age,
+            max_count: retention.max_count,
+            disappear_after: retention.disappear_after,
+        }
+    }
+
+    /// create network message of the retention settings
+    pub fn to_net(&self) -> proto_net::GroupRetention {
+        proto_net::GroupRetention {
+            max_age: self.max_age,
+            max_count: self.max_count,
+            disappear_after: self.disappear_after,
+        }
+    }
+
+    /// create retention settings from a RPC message
+    pub fn from_rpc(retention: &proto_rpc::GroupRetention) -> GroupRetention {
+        GroupRetention {
+            max_age: retention.max_age,
+            max_count: retention.max_count,
+            disappear_after: retention.disappear_after,
+        }
+    }
+
+    /// create RPC message of the retention settings
+    pub fn to_rpc(&self) -> proto_rpc::GroupRetention {
+        proto_rpc::GroupRetention {
+            max_age: self.max_age,
+            max_count: self.max_count,
+            disappear_after: self.disappear_after,
+        }
+    }
+}
+
 /// Structure of Group
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GroupInvited {
@@ -98,6 +155,8 @@ pub struct Group {
     pub last_message_data: Vec<u8>,
     /// last message sender id
     pub last_message_sender_id: Vec<u8>,
+    /// message retention settings
+    pub retention: GroupRetention,
 }
 
 /// Group module to process transfer, receive and RPC commands
@@ -124,6 +183,7 @@ impl Group {
             last_message_at: 0,
             last_message_data: Vec::new(),
             last_message_sender_id: Vec::new(),
+            retention: GroupRetention::default(),
         }
     }
 
@@ -292,6 +352,7 @@ impl Group {
             created_at: group.created_at,
             revision: group.revision,
             members,
+            retention: Some(group.retention.to_net()),
         };
 
         let container = proto_net::GroupContainer {
@@ -599,6 +660,49 @@ impl Group {
                             Self::post_group_update(&my_user_id, &remove_req.group_id);
                         }
                     }
+                    Some(proto_rpc::group::Message::GroupRetentionRequest(retention_req)) => {
+                        let mut status = true;
+                        let mut message: String = "".to_string();
+
+                        match retention_req.retention {
+                            Some(retention) => {
+                                if let Err(err) = GroupManage::set_retention(
+                                    &my_user_id,
+                                    &retention_req.group_id,
+                                    GroupRetention::from_rpc(&retention),
+                                ) {
+                                    status = false;
+                                    message = err.clone();
+                                }
+                            }
+                            None => {
+                                status = false;
+                                message = "no retention settings".to_string();
+                            }
+                        }
+
+                        let proto_message = proto_rpc::Group {
+                            message: Some(proto_rpc::group::Message::GroupRetentionResponse(
+                                proto_rpc::GroupRetentionResponse {
+                                    group_id: retention_req.group_id.clone(),
+                                    result: Some(proto_rpc::GroupResult { status, message }),
+                                },
+                            )),
+                        };
+
+                        // send message
+                        Rpc::send_message(
+                            proto_message.encode_to_vec(),
+                            crate::rpc::proto::Modules::Group.into(),
+                            "".to_string(),
+                            Vec::new(),
+                        );
+
+                        // post updates
+                        if status {
+                            Self::post_group_update(&my_user_id, &retention_req.group_id);
+                        }
+                    }
                     _ => {
                         log::error!("Unhandled Protobuf Group chat message");
                     }
@@ -175,11 +175,18 @@ impl GroupStorage {
         log::debug!("group_clear_unread");
 
         if let Some(mut group) = Self::get_group(account_id, group_id) {
+            let had_unread = group.unread_messages > 0;
+
             // clear unread value
             group.unread_messages = 0;
 
             // save group
-            Self::save_group(account_id, group);
+            Self::save_group(account_id, group.clone());
+
+            // inform the group members
+            if had_unread {
+                crate::services::chat::ChatReceipt::send_read_receipt(&account_id, &group);
+            }
         } else {
             log::error!("group_clear_unread group not found");
         }
@@ -8,6 +8,7 @@
 
 use libp2p::PeerId;
 use prost::Message;
+use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use sled_extensions::{bincode::Tree, DbExt};
 use state::Storage;
@@ -24,7 +25,8 @@ use super::chat::{ChatFile, ChatStorage};
 use super::crypto::Crypto;
 use crate::connections::ConnectionModule;
 use crate::node::user_accounts::{UserAccount, UserAccounts};
-use crate::router::table::RoutingTable;
+use crate::router::{loops::LoopDetector, neighbours::Neighbours, table::RoutingTable};
+use crate::storage::configuration::Configuration;
 use crate::storage::database::DataBase;
 use crate::utilities::timestamp::Timestamp;
 use process::MessagingProcess;
@@ -47,6 +49,11 @@ pub struct ScheduledMessage {
     is_forward: bool,
     scheduled_dtn: bool,
     is_dtn: bool,
+    /// index of the ranked route to send the message on
+    route_index: usize,
+    /// neighbour and connection module of the previous
+    /// send attempt, which shall be avoided
+    avoid_route: Option<(PeerId, ConnectionModule)>,
 }
 
 /// mutable state of messages, scheduled for sending
@@ -75,6 +82,20 @@ pub struct UnConfirmedMessage {
     pub scheduled_dtn: bool,
     // flag that indicate DTN message
     pub is_dtn: bool,
+    // flag that a storage node took over the custody
+    pub custody: bool,
+    // creation time
+    pub created_at: u64,
+    // random jitter of the next send attempt, 0 to 255
+    pub jitter: u8,
+    // node id of the neighbour via which the message was last sent,
+    // empty if the message was not sent yet
+    pub route_node: Vec<u8>,
+    // connection module via which the message was last sent
+    pub route_module: ConnectionModule,
+    // hop count of the route via which the message was last sent,
+    // 1 if the receiver is the neighbour itself
+    pub route_hc: u8,
 }
 
 /// Unconfirmed Message Type
@@ -103,6 +124,8 @@ pub enum MessagingServiceType {
 pub struct UnConfirmedMessages {
     /// signature => UnConfirmedMessage
     pub unconfirmed: Tree<UnConfirmedMessage>,
+    /// signature => FailedMessage
+    pub failed: Tree<FailedMessage>,
 }
 
 /// Qaul Messaging Structure
@@ -112,13 +135,32 @@ pub struct Messaging {
 }
 
 /// Qaul Failed Message Structure
+///
+/// Unconfirmed messages that were given up
+/// are moved to the failed messages table.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FailedMessage {
+    // sending user account
     pub user_id: Vec<u8>,
+    // receiver id
+    pub receiver_id: Vec<u8>,
+    // group id, if known
     pub group_id: Vec<u8>,
+    // message type
+    pub message_type: MessagingServiceType,
+    // message id
+    pub message_id: Vec<u8>,
+    // encoded container
+    pub container: Vec<u8>,
+    // flag that indicate DTN message
+    pub is_dtn: bool,
+    // creation time
     pub created_at: u64,
+    // time of the last send attempt
     pub last_try: u64,
+    // number of send attempts
     pub try_count: u32,
+    // reason of the failure
     pub message: String,
 }
 
@@ -138,7 +180,11 @@ impl Messaging {
 
         // open trees
         let unconfirmed: Tree<UnConfirmedMessage> = db.open_bincode_tree("unconfirmed").unwrap();
-        let unconfirmed_messages = UnConfirmedMessages { unconfirmed };
+        let failed: Tree<FailedMessage> = db.open_bincode_tree("failed").unwrap();
+        let unconfirmed_messages = UnConfirmedMessages {
+            unconfirmed,
+            failed,
+        };
         UNCONFIRMED.set(RwLock::new(unconfirmed_messages));
     }
 
@@ -150,16 +196,23 @@ impl Messaging {
         container: &proto::Container,
         is_dtn: bool,
     ) {
+        let timestamp = Timestamp::get_timestamp();
         let new_entry = UnConfirmedMessage {
             receiver_id: receiver.to_bytes(),
             container: container.encode_to_vec(),
-            last_sent: Timestamp::get_timestamp(),
+            last_sent: timestamp,
             message_type,
             message_id: message_id.to_owned(),
             retry: 1,
             scheduled: false,
             scheduled_dtn: false,
             is_dtn,
+            custody: false,
+            created_at: timestamp,
+            jitter: thread_rng().gen(),
+            route_node: Vec::new(),
+            route_module: ConnectionModule::None,
+            route_hc: 0,
         };
         let unconfirmed = UNCONFIRMED.get().write().unwrap();
 
@@ -176,6 +229,446 @@ impl Messaging {
         }
     }
 
+    /// get a message from the unconfirmed table
+    pub fn get_unconfirmed_message(signature: &Vec<u8>) -> Option<UnConfirmedMessage> {
+        let unconfirmed = UNCONFIRMED.get().read().unwrap();
+
+        match unconfirmed.unconfirmed.get(signature) {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("{}", e);
+                None
+            }
+        }
+    }
+
+    /// set the DTN scheduling flag of an unconfirmed message
+    ///
+    /// Messages scheduled via DTN are not retransmitted.
+    pub fn set_scheduled_dtn(signature: &Vec<u8>, scheduled_dtn: bool) {
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        if let Ok(Some(mut unconfirmed_message)) = unconfirmed.unconfirmed.get(signature) {
+            unconfirmed_message.scheduled_dtn = scheduled_dtn;
+            if let Err(_e) = unconfirmed
+                .unconfirmed
+                .insert(signature.clone(), unconfirmed_message)
+            {
+                log::error!("error updating unconfirmed table");
+            } else {
+                if let Err(_e) = unconfirmed.unconfirmed.flush() {
+                    log::error!("error updating unconfirmed table");
+                }
+            }
+        }
+    }
+
+    /// update the last sent time stamp of an unconfirmed message
+    ///
+    /// The message is marked as scheduled.
+    pub fn update_last_sent(signature: &Vec<u8>, last_sent: u64) {
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        if let Ok(Some(mut unconfirmed_message)) = unconfirmed.unconfirmed.get(signature) {
+            unconfirmed_message.scheduled = true;
+            unconfirmed_message.last_sent = last_sent;
+            if let Err(_e) = unconfirmed
+                .unconfirmed
+                .insert(signature.clone(), unconfirmed_message)
+            {
+                log::error!("error updating unconfirmed table");
+            } else {
+                if let Err(_e) = unconfirmed.unconfirmed.flush() {
+                    log::error!("error updating unconfirmed table");
+                }
+            }
+        }
+    }
+
+    /// a storage node took over the custody of a message
+    ///
+    /// The message is not retransmitted anymore,
+    /// but kept until the reception is confirmed.
+    pub fn on_custody_transferred(signature: &Vec<u8>) {
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        if let Ok(Some(mut unconfirmed_message)) = unconfirmed.unconfirmed.get(signature) {
+            unconfirmed_message.custody = true;
+            unconfirmed_message.scheduled_dtn = true;
+            if let Err(_e) = unconfirmed
+                .unconfirmed
+                .insert(signature.clone(), unconfirmed_message)
+            {
+                log::error!("error updating unconfirmed table");
+            } else {
+                if let Err(_e) = unconfirmed.unconfirmed.flush() {
+                    log::error!("error updating unconfirmed table");
+                }
+            }
+        }
+    }
+
+    /// get the storage nodes a message was handed to
+    ///
+    /// Returns the user ids of all storage nodes, that
+    /// haven't answered yet to a DTN copy of the message.
+    pub fn get_dtn_storage_nodes(message_id: &Vec<u8>) -> Vec<Vec<u8>> {
+        let mut storage_nodes: Vec<Vec<u8>> = Vec::new();
+        let unconfirmed = UNCONFIRMED.get().read().unwrap();
+
+        for entry in unconfirmed.unconfirmed.iter() {
+            if let Ok((_signature, unconfirmed_message)) = entry {
+                if let MessagingServiceType::DtnOrigin = unconfirmed_message.message_type {
+                    if unconfirmed_message.message_id == *message_id {
+                        storage_nodes.push(unconfirmed_message.receiver_id);
+                    }
+                }
+            }
+        }
+
+        storage_nodes
+    }
+
+    /// no storage node took over the custody of a message
+    ///
+    /// If no other storage node took over the custody of the
+    /// message, it is retransmitted again.
+    pub fn on_dtn_rejected(signature: &Vec<u8>) {
+        if let Some(unconfirmed_message) = Self::get_unconfirmed_message(signature) {
+            if !unconfirmed_message.custody {
+                Self::set_scheduled_dtn(signature, false);
+            }
+        }
+    }
+
+    /// remove a message from the unconfirmed table
+    ///
+    /// The message will not be retransmitted anymore.
+    pub fn remove_unconfirmed_message(signature: &Vec<u8>) {
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        if let Err(e) = unconfirmed.unconfirmed.remove(signature) {
+            log::error!("{}", e);
+        }
+        // flush
+        if let Err(e) = unconfirmed.unconfirmed.flush() {
+            log::error!("Error unconfirmed table flush: {}", e);
+        }
+    }
+
+    /// an unconfirmed message was given up
+    ///
+    /// The message has already been removed from the unconfirmed table.
+    /// It is saved to the failed messages table and the failure is
+    /// reported to the service that sent it.
+    pub fn on_failed_message(signature: &Vec<u8>, unconfirmed: UnConfirmedMessage, reason: &str) {
+        log::warn!(
+            "message failed: {}, signature: {}",
+            reason,
+            bs58::encode(signature.clone()).into_string()
+        );
+
+        // get sending user account
+        let mut user_id: Vec<u8> = Vec::new();
+        if let Ok(container) = proto::Container::decode(&unconfirmed.container[..]) {
+            if let Some(envelope) = container.envelope {
+                user_id = envelope.sender_id;
+            }
+        }
+        let user_account_id = PeerId::from_bytes(&user_id).ok();
+
+        // report the failure to the service
+        let mut group_id: Vec<u8> = Vec::new();
+        match unconfirmed.message_type {
+            MessagingServiceType::DtnOrigin => {
+                // the storage node could not be reached,
+                // the original message is scheduled again
+                Self::on_dtn_rejected(&unconfirmed.message_id);
+                return;
+            }
+            MessagingServiceType::DtnStored => {
+                // stored messages expire in the DTN service
+                return;
+            }
+            MessagingServiceType::Chat => {
+                if let Some(account_id) = user_account_id {
+                    if let Some(id) =
+                        ChatStorage::get_group_id(&account_id, &unconfirmed.message_id)
+                    {
+                        group_id = id;
+                    }
+                    ChatStorage::udate_status(
+                        &account_id,
+                        &unconfirmed.message_id,
+                        super::chat::rpc_proto::MessageStatus::Failed,
+                    );
+                }
+            }
+            MessagingServiceType::ChatFile => {
+                if let Some(account_id) = user_account_id {
+                    if let Some(id) = ChatFile::update_failed(account_id, &unconfirmed.message_id) {
+                        group_id = id;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // save message to failed table
+        let failed_message = FailedMessage {
+            user_id,
+            receiver_id: unconfirmed.receiver_id,
+            group_id,
+            message_type: unconfirmed.message_type,
+            message_id: unconfirmed.message_id,
+            container: unconfirmed.container,
+            is_dtn: unconfirmed.is_dtn,
+            created_at: unconfirmed.created_at,
+            last_try: unconfirmed.last_sent,
+            try_count: unconfirmed.retry,
+            message: reason.to_string(),
+        };
+
+        let messages = UNCONFIRMED.get().write().unwrap();
+        if let Err(e) = messages.failed.insert(signature.clone(), failed_message) {
+            log::error!("{}", e);
+        }
+        if let Err(e) = messages.failed.flush() {
+            log::error!("Error failed table flush: {}", e);
+        }
+    }
+
+    /// check if a message container was sent by a user account
+    fn is_sent_by(container: &Vec<u8>, user_id: &PeerId) -> bool {
+        if let Ok(container) = proto::Container::decode(&container[..]) {
+            if let Some(envelope) = container.envelope {
+                return envelope.sender_id == user_id.to_bytes();
+            }
+        }
+        false
+    }
+
+    /// get the unconfirmed messages of a user account
+    /// with one of the given message ids
+    pub fn get_unconfirmed_by_message_ids(
+        user_id: &PeerId,
+        message_ids: &Vec<Vec<u8>>,
+    ) -> Vec<UnConfirmedMessage> {
+        let mut res: Vec<UnConfirmedMessage> = Vec::new();
+        let unconfirmed = UNCONFIRMED.get().read().unwrap();
+
+        for entry in unconfirmed.unconfirmed.iter() {
+            if let Ok((_signature, unconfirmed_message)) = entry {
+                if let MessagingServiceType::DtnStored = unconfirmed_message.message_type {
+                    continue;
+                }
+                if message_ids.contains(&unconfirmed_message.message_id)
+                    && Self::is_sent_by(&unconfirmed_message.container, user_id)
+                {
+                    res.push(unconfirmed_message);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// get the failed messages of a user account
+    /// with one of the given message ids
+    pub fn get_failed_by_message_ids(
+        user_id: &PeerId,
+        message_ids: &Vec<Vec<u8>>,
+    ) -> Vec<FailedMessage> {
+        let mut res: Vec<FailedMessage> = Vec::new();
+        let unconfirmed = UNCONFIRMED.get().read().unwrap();
+
+        for entry in unconfirmed.failed.iter() {
+            if let Ok((_signature, failed_message)) = entry {
+                if message_ids.contains(&failed_message.message_id)
+                    && failed_message.user_id == user_id.to_bytes()
+                {
+                    res.push(failed_message);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// resend messages of a user account
+    ///
+    /// Unconfirmed messages are retransmitted immediately,
+    /// failed messages are moved back to the unconfirmed table.
+    /// Messages in the custody of a storage node are not touched.
+    ///
+    /// Returns the number of messages scheduled again.
+    pub fn resend_messages(user_id: &PeerId, message_ids: &Vec<Vec<u8>>) -> u32 {
+        let mut count: u32 = 0;
+        let timestamp = Timestamp::get_timestamp();
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        // reset unconfirmed messages
+        for entry in unconfirmed.unconfirmed.iter() {
+            if let Ok((signature, mut unconfirmed_message)) = entry {
+                if let MessagingServiceType::DtnStored = unconfirmed_message.message_type {
+                    continue;
+                }
+                if unconfirmed_message.custody || unconfirmed_message.scheduled_dtn {
+                    continue;
+                }
+                if !message_ids.contains(&unconfirmed_message.message_id)
+                    || !Self::is_sent_by(&unconfirmed_message.container, user_id)
+                {
+                    continue;
+                }
+
+                unconfirmed_message.retry = 1;
+                unconfirmed_message.last_sent = 0;
+                unconfirmed_message.scheduled = false;
+                if let Err(e) = unconfirmed
+                    .unconfirmed
+                    .insert(signature, unconfirmed_message)
+                {
+                    log::error!("{}", e);
+                } else {
+                    count = count + 1;
+                }
+            }
+        }
+
+        // move failed messages back to the unconfirmed table
+        for entry in unconfirmed.failed.iter() {
+            if let Ok((signature, failed_message)) = entry {
+                if !message_ids.contains(&failed_message.message_id)
+                    || failed_message.user_id != user_id.to_bytes()
+                {
+                    continue;
+                }
+
+                let unconfirmed_message = UnConfirmedMessage {
+                    receiver_id: failed_message.receiver_id,
+                    message_type: failed_message.message_type,
+                    message_id: failed_message.message_id,
+                    container: failed_message.container,
+                    last_sent: 0,
+                    retry: 1,
+                    scheduled: false,
+                    scheduled_dtn: false,
+                    is_dtn: failed_message.is_dtn,
+                    custody: false,
+                    created_at: timestamp,
+                    jitter: thread_rng().gen(),
+                    route_node: Vec::new(),
+                    route_module: ConnectionModule::None,
+                    route_hc: 0,
+                };
+                if let Err(e) = unconfirmed
+                    .unconfirmed
+                    .insert(signature.clone(), unconfirmed_message)
+                {
+                    log::error!("{}", e);
+                    continue;
+                }
+                if let Err(e) = unconfirmed.failed.remove(signature) {
+                    log::error!("{}", e);
+                }
+                count = count + 1;
+            }
+        }
+
+        // flush
+        if let Err(e) = unconfirmed.unconfirmed.flush() {
+            log::error!("Error unconfirmed table flush: {}", e);
+        }
+        if let Err(e) = unconfirmed.failed.flush() {
+            log::error!("Error failed table flush: {}", e);
+        }
+
+        count
+    }
+
+    /// cancel the sending of messages of a user account
+    ///
+    /// The messages are removed from the unconfirmed and
+    /// the failed table, together with the DTN messages
+    /// that were sent to storage nodes for them.
+    ///
+    /// Returns the number of removed messages.
+    pub fn cancel_messages(user_id: &PeerId, message_ids: &Vec<Vec<u8>>) -> u32 {
+        let mut count: u32 = 0;
+        let mut signatures: Vec<Vec<u8>> = Vec::new();
+        let unconfirmed = UNCONFIRMED.get().write().unwrap();
+
+        // find unconfirmed messages
+        for entry in unconfirmed.unconfirmed.iter() {
+            if let Ok((signature, unconfirmed_message)) = entry {
+                if let MessagingServiceType::DtnStored = unconfirmed_message.message_type {
+                    continue;
+                }
+                if message_ids.contains(&unconfirmed_message.message_id)
+                    && Self::is_sent_by(&unconfirmed_message.container, user_id)
+                {
+                    signatures.push(signature.to_vec());
+                }
+            }
+        }
+
+        // find the DTN messages of them
+        let mut dtn_signatures: Vec<Vec<u8>> = Vec::new();
+        for entry in unconfirmed.unconfirmed.iter() {
+            if let Ok((signature, unconfirmed_message)) = entry {
+                if let MessagingServiceType::DtnOrigin = unconfirmed_message.message_type {
+                    if signatures.contains(&unconfirmed_message.message_id) {
+                        dtn_signatures.push(signature.to_vec());
+                    }
+                }
+            }
+        }
+
+        // remove unconfirmed messages
+        for signature in signatures {
+            if let Err(e) = unconfirmed.unconfirmed.remove(signature) {
+                log::error!("{}", e);
+            } else {
+                count = count + 1;
+            }
+        }
+        for signature in dtn_signatures {
+            if let Err(e) = unconfirmed.unconfirmed.remove(signature) {
+                log::error!("{}", e);
+            }
+        }
+
+        // remove failed messages
+        let mut failed_signatures: Vec<Vec<u8>> = Vec::new();
+        for entry in unconfirmed.failed.iter() {
+            if let Ok((signature, failed_message)) = entry {
+                if message_ids.contains(&failed_message.message_id)
+                    && failed_message.user_id == user_id.to_bytes()
+                {
+                    failed_signatures.push(signature.to_vec());
+                }
+            }
+        }
+        for signature in failed_signatures {
+            if let Err(e) = unconfirmed.failed.remove(signature) {
+                log::error!("{}", e);
+            } else {
+                count = count + 1;
+            }
+        }
+
+        // flush
+        if let Err(e) = unconfirmed.unconfirmed.flush() {
+            log::error!("Error unconfirmed table flush: {}", e);
+        }
+        if let Err(e) = unconfirmed.failed.flush() {
+            log::error!("Error failed table flush: {}", e);
+        }
+
+        count
+    }
+
     /// process confirmation message and return (sender_id, message_id)
     pub fn on_confirmed_message(
         signature: &Vec<u8>,
@@ -196,6 +689,11 @@ impl Messaging {
 
                 match v {
                     Some(unconfirmed) => {
+                        // the message was delivered via the route
+                        if unconfirmed.scheduled && !unconfirmed.scheduled_dtn {
+                            Self::update_link_statistics(&unconfirmed, true);
+                        }
+
                         // check message and decide what to do
                         match unconfirmed.message_type {
                             MessagingServiceType::Unconfirmed => {
@@ -228,22 +726,13 @@ impl Messaging {
                             }
                             MessagingServiceType::ChatFile => {
                                 log::trace!("Confirmation: ChatFile");
-                                match unconfirmed.message_id.try_into() {
-                                    Ok(arr) => {
-                                        let file_id = u64::from_be_bytes(arr);
-
-                                        // confirm message reception in data base
-                                        ChatFile::update_confirmation(
-                                            user_account.id,
-                                            sender_id,
-                                            file_id,
-                                            confirmation.received_at,
-                                        );
-                                    }
-                                    Err(e) => {
-                                        log::error!("couldn't convert file_id to u64: {:?}", e);
-                                    }
-                                }
+                                // confirm message reception in data base
+                                ChatFile::update_confirmation(
+                                    user_account.id,
+                                    sender_id,
+                                    &unconfirmed.message_id,
+                                    confirmation.received_at,
+                                );
                             }
                             MessagingServiceType::Rtc => {
                                 log::trace!("Confirmation: Rtc");
@@ -260,18 +749,41 @@ impl Messaging {
         }
     }
 
-    fn on_scheduled_message(signature: &Vec<u8>) {
+    /// update the delivery statistics of the link
+    /// to the neighbour via which the message was last sent
+    ///
+    /// The statistics are used by the ETX link metric
+    /// of the router. Only messages to receivers on the neighbour
+    /// itself are counted, as the delivery of messages to receivers
+    /// further away depends on the links beyond the neighbour.
+    pub fn update_link_statistics(unconfirmed_message: &UnConfirmedMessage, success: bool) {
+        if unconfirmed_message.route_hc != 1 {
+            return;
+        }
+        if let Ok(node_id) = PeerId::from_bytes(&unconfirmed_message.route_node) {
+            Neighbours::update_statistics(unconfirmed_message.route_module, node_id, success);
+        }
+    }
+
+    /// set the scheduled flag and remember the route
+    /// via which the message is sent
+    fn on_scheduled_message(
+        signature: &Vec<u8>,
+        node_id: PeerId,
+        module: ConnectionModule,
+        hc: u8,
+    ) {
         let unconfirmed = UNCONFIRMED.get().write().unwrap();
         if !unconfirmed.unconfirmed.contains_key(signature).unwrap() {
             return;
         }
 
         let mut unconfirmed_message = unconfirmed.unconfirmed.get(signature).unwrap().unwrap();
-        if unconfirmed_message.scheduled {
-            return;
-        }
 
         unconfirmed_message.scheduled = true;
+        unconfirmed_message.route_node = node_id.to_bytes();
+        unconfirmed_message.route_module = module;
+        unconfirmed_message.route_hc = hc;
         if let Err(_e) = unconfirmed
             .unconfirmed
             .insert(signature.clone(), unconfirmed_message)
@@ -313,9 +825,34 @@ impl Messaging {
         user_account: &UserAccount,
         receiver: &PeerId,
         data: Vec<u8>,
-        _message_type: MessagingServiceType,
+        message_type: MessagingServiceType,
+        message_id: &Vec<u8>,
+        is_common_message: bool,
+    ) -> Result<Vec<u8>, String> {
+        Self::pack_and_send_message_on_route(
+            user_account,
+            receiver,
+            data,
+            message_type,
+            message_id,
+            is_common_message,
+            0,
+        )
+    }
+
+    /// pack, sign and schedule a message for sending
+    /// on an alternative route
+    ///
+    /// The route_index is the position of the route in the
+    /// ranked routes to the receiver. 0 is the best route.
+    pub fn pack_and_send_message_on_route(
+        user_account: &UserAccount,
+        receiver: &PeerId,
+        data: Vec<u8>,
+        message_type: MessagingServiceType,
         message_id: &Vec<u8>,
         is_common_message: bool,
+        route_index: usize,
     ) -> Result<Vec<u8>, String> {
         log::trace!("pack_and_send_message to {}", receiver.to_base58());
 
@@ -337,6 +874,7 @@ impl Messaging {
         );
 
         let envelop_payload = proto::EnvelopPayload {
+            dtn_ttl: 0,
             payload: Some(proto::envelop_payload::Payload::Encrypted(
                 encrypted_message,
             )),
@@ -366,7 +904,7 @@ impl Messaging {
             // in common message case, save into unconfirmed table
             if is_common_message {
                 Self::save_unconfirmed_message(
-                    MessagingServiceType::Chat,
+                    message_type,
                     message_id,
                     receiver,
                     &container,
@@ -375,13 +913,14 @@ impl Messaging {
             }
 
             // schedule message for sending
-            Self::schedule_message(
+            Self::schedule_message_on_route(
                 receiver.clone(),
                 container,
                 is_common_message,
                 false,
                 false,
                 false,
+                route_index,
             );
 
             // return signature
@@ -391,14 +930,21 @@ impl Messaging {
         }
     }
 
-    /// pack, sign and schedule a message for sending
+    /// pack, sign and schedule a DTN message for sending
+    ///
+    /// The original container is handed to a storage node.
+    /// The message_id references the message for which the
+    /// custody is transferred, once the storage node accepted it.
     pub fn send_dtn_message(
         user_account: &UserAccount,
         storage_node_id: &PeerId,
         org_container: &proto::Container,
+        message_id: &Vec<u8>,
+        dtn_ttl: u64,
     ) -> Result<Vec<u8>, String> {
         // create Dtn message
         let dtn_payload = proto::EnvelopPayload {
+            dtn_ttl,
             payload: Some(proto::envelop_payload::Payload::Dtn(
                 org_container.encode_to_vec(),
             )),
@@ -416,10 +962,10 @@ impl Messaging {
                 envelope: Some(envelope_dtn),
             };
 
-            // in common message case, save into unconfirmed table
+            // save into unconfirmed table
             Self::save_unconfirmed_message(
-                MessagingServiceType::Chat,
-                &Vec::new(),
+                MessagingServiceType::DtnOrigin,
+                message_id,
                 &storage_node_id,
                 &container_dtn,
                 true,
@@ -456,23 +1002,76 @@ impl Messaging {
         scheduled_dtn: bool,
         is_dtn: bool,
     ) {
-        #[cfg(emulate)]
-        if network_emul::NetworkEmulator::is_lost() {
-            log::error!(
-                "drop message, signature: {}",
-                bs58::encode(container.signature.clone()).into_string()
-            );
-            return;
-        }
+        Self::schedule_message_on_route(
+            receiver,
+            container,
+            is_common,
+            is_forward,
+            scheduled_dtn,
+            is_dtn,
+            0,
+        );
+    }
 
-        let msg = ScheduledMessage {
+    /// schedule a message on an alternative route
+    ///
+    /// The route_index is the position of the route in the
+    /// ranked routes to the receiver. 0 is the best route.
+    pub fn schedule_message_on_route(
+        receiver: PeerId,
+        container: proto::Container,
+        is_common: bool,
+        is_forward: bool,
+        scheduled_dtn: bool,
+        is_dtn: bool,
+        route_index: usize,
+    ) {
+        Self::schedule(ScheduledMessage {
             receiver,
             container,
             is_common,
             is_forward,
             scheduled_dtn,
             is_dtn,
-        };
+            route_index,
+            avoid_route: None,
+        });
+    }
+
+    /// schedule a retransmission of a message
+    ///
+    /// The message is sent on the best route, that doesn't lead
+    /// over the neighbour and connection module of the previous
+    /// send attempt, if there is such a route.
+    pub fn schedule_retransmission(
+        receiver: PeerId,
+        container: proto::Container,
+        scheduled_dtn: bool,
+        is_dtn: bool,
+        avoid_route: Option<(PeerId, ConnectionModule)>,
+    ) {
+        Self::schedule(ScheduledMessage {
+            receiver,
+            container,
+            is_common: true,
+            is_forward: false,
+            scheduled_dtn,
+            is_dtn,
+            route_index: 0,
+            avoid_route,
+        });
+    }
+
+    /// add a message to the sending queue
+    fn schedule(msg: ScheduledMessage) {
+        #[cfg(emulate)]
+        if network_emul::NetworkEmulator::is_lost() {
+            log::error!(
+                "drop message, signature: {}",
+                bs58::encode(msg.container.signature.clone()).into_string()
+            );
+            return;
+        }
 
         // add it to sending queue
         let mut messaging = MESSAGING.get().write().unwrap();
@@ -494,9 +1093,30 @@ impl Messaging {
 
         if let Some(message) = message_item {
             // check for route
-            if let Some(route) = RoutingTable::get_route_to_user(message.receiver) {
-                // update unconfirmed table set scheduled flag.
-                Self::on_scheduled_message(&message.container.signature);
+            let route_option = match message.avoid_route {
+                Some((node_id, module)) => {
+                    RoutingTable::get_route_to_user_avoiding(message.receiver, &node_id, &module)
+                }
+                None => RoutingTable::get_alternative_route_to_user(
+                    message.receiver,
+                    message.route_index,
+                ),
+            };
+            if let Some(route) = route_option {
+                // update unconfirmed table set scheduled flag
+                // and the route of this send attempt.
+                Self::on_scheduled_message(
+                    &message.container.signature,
+                    route.node,
+                    route.module,
+                    route.hc,
+                );
+
+                // remember own messages for the loop detection,
+                // forwarded messages are already remembered
+                if !message.is_forward {
+                    LoopDetector::add_sent(&message.container.signature);
+                }
 
                 // create binary message
                 let data = message.container.encode_to_vec();
@@ -510,26 +1130,40 @@ impl Messaging {
                     && !message.scheduled_dtn
                     && message.is_common
                 {
-                    // get storage node id
+                    // get storage node ids
                     if let Ok(my_user_id) =
                         PeerId::from_bytes(&message.container.envelope.as_ref().unwrap().sender_id)
                     {
-                        if let Some(storage_node_id) =
-                            super::dtn::Dtn::get_storage_user(&my_user_id)
-                        {
-                            if let Some(user_account) = UserAccounts::get_by_id(my_user_id) {
+                        if let Some(user_account) = UserAccounts::get_by_id(my_user_id) {
+                            // get requested time to live
+                            let mut dtn_ttl: u64 = 0;
+                            if let Some(user_profile) =
+                                Configuration::get_user(my_user_id.to_string())
+                            {
+                                dtn_ttl = user_profile.storage.ttl_request;
+                            }
+
+                            // hand the message to every selected storage node
+                            let mut scheduled = false;
+                            for storage_node_id in super::dtn::Dtn::get_storage_users(&my_user_id) {
                                 if let Err(_e) = Self::send_dtn_message(
                                     &user_account,
                                     &storage_node_id,
                                     &message.container,
+                                    &message.container.signature,
+                                    dtn_ttl,
                                 ) {
                                     log::error!("DTN scheduling error!");
                                 } else {
-                                    log::error!("DTN scheduled...");
-                                    // update unconfirmed table
-                                    Self::on_scheduled_as_dtn_message(&message.container.signature);
+                                    scheduled = true;
                                 }
                             }
+
+                            if scheduled {
+                                log::error!("DTN scheduled...");
+                                // update unconfirmed table
+                                Self::on_scheduled_as_dtn_message(&message.container.signature);
+                            }
                         }
                     }
                 }
@@ -598,14 +1232,29 @@ impl Messaging {
                                 ),
 
                                 // schedule it for further sending otherwise
-                                None => Self::schedule_message(
-                                    receiver_id,
-                                    container,
-                                    true,
-                                    true,
-                                    false,
-                                    false,
-                                ),
+                                None => {
+                                    // drop messages that were routed back through this node
+                                    if LoopDetector::is_loop(
+                                        &container.signature,
+                                        &received.received_from,
+                                    ) {
+                                        log::warn!(
+                                            "routing loop detected, message {} from {} dropped",
+                                            bs58::encode(container.signature.clone()).into_string(),
+                                            received.received_from.to_base58()
+                                        );
+                                        return;
+                                    }
+
+                                    Self::schedule_message(
+                                        receiver_id,
+                                        container,
+                                        true,
+                                        true,
+                                        false,
+                                        false,
+                                    )
+                                }
                             }
                         }
                         Err(e) => log::error!(
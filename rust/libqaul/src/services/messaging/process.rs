@@ -11,7 +11,7 @@ use prost::Message;
 
 use crate::node::user_accounts::UserAccount;
 use crate::router;
-use crate::services::chat::{self, rpc_proto, ChatFile, ChatStorage};
+use crate::services::chat::{self, rpc_proto, ChatFile, ChatReceipt, ChatStorage};
 use crate::services::crypto::Crypto;
 use crate::services::dtn;
 use crate::services::group::{self, Group, GroupId};
@@ -96,6 +96,14 @@ impl MessagingProcess {
                     log::error!("send confirmation failed {}", e);
                 }
             }
+            Some(super::proto::messaging::Message::ChatTyping(typing)) => {
+                // typing notifications are ephemeral and not confirmed
+                ChatReceipt::on_typing(&user_account.id, sender_id, &typing);
+            }
+            Some(super::proto::messaging::Message::ChatRead(chat_read)) => {
+                // read receipts are not confirmed
+                ChatReceipt::on_read_receipt(&user_account.id, sender_id, &chat_read);
+            }
             Some(super::proto::messaging::Message::CommonMessage(common)) => {
                 // create group id
                 let group_id;
@@ -136,6 +144,7 @@ impl MessagingProcess {
                             message: Some(rpc_proto::chat_content_message::Message::ChatContent(
                                 rpc_proto::ChatContent {
                                     text: chat_message.content.clone(),
+                                    reply_to: Vec::new(),
                                 },
                             )),
                         };
@@ -150,6 +159,53 @@ impl MessagingProcess {
                             chat::rpc_proto::MessageStatus::Received,
                         );
                     }
+                    Some(super::proto::common_message::Payload::ChatReply(ref chat_reply)) => {
+                        // create ChatContentMessage with the replied message
+                        let content_message = rpc_proto::ChatContentMessage {
+                            message: Some(rpc_proto::chat_content_message::Message::ChatContent(
+                                rpc_proto::ChatContent {
+                                    text: chat_reply.content.clone(),
+                                    reply_to: chat_reply.parent_id.clone(),
+                                },
+                            )),
+                        };
+
+                        ChatStorage::save_message(
+                            &user_account.id,
+                            &group_id,
+                            sender_id,
+                            &common.message_id,
+                            common.sent_at,
+                            content_message,
+                            chat::rpc_proto::MessageStatus::Received,
+                        );
+                    }
+                    Some(super::proto::common_message::Payload::ChatReaction(
+                        ref chat_reaction,
+                    )) => {
+                        // add or remove reaction
+                        ChatStorage::update_reaction(
+                            &user_account.id,
+                            &group_id.to_bytes(),
+                            &chat_reaction.parent_id,
+                            sender_id,
+                            &chat_reaction.reaction,
+                            chat_reaction.remove,
+                            common.sent_at,
+                        );
+                    }
+                    Some(super::proto::common_message::Payload::ChatEdit(ref chat_edit)) => {
+                        // edit message, the sender is checked by the storage
+                        ChatStorage::update_edit(
+                            &user_account.id,
+                            &group_id.to_bytes(),
+                            &chat_edit.parent_id,
+                            sender_id,
+                            &chat_edit.content,
+                            chat_edit.redact,
+                            common.sent_at,
+                        );
+                    }
                     Some(super::proto::common_message::Payload::FileMessage(ref file_message)) => {
                         ChatFile::process_net_chatfilecontainer(
                             sender_id.to_owned(),
@@ -289,7 +345,13 @@ impl MessagingProcess {
                         );
                     }
                     Some(super::proto::envelop_payload::Payload::Dtn(dtn)) => {
-                        dtn::Dtn::net(&receiver_id, &sender_id, &container.signature, &dtn);
+                        dtn::Dtn::net(
+                            &receiver_id,
+                            &sender_id,
+                            &container.signature,
+                            &dtn,
+                            payload.dtn_ttl,
+                        );
                     }
                     _ => {
                         log::error!("unknown envelop payload");
@@ -4,54 +4,112 @@
 //! # Retransmit Qaul Messages
 //!
 //! Messages that couldn't be sent to a user are scheduled for retransmission.
+//!
+//! Every retransmission is sent on the next best route to the user,
+//! to fail over to another neighbour or connection module.
+//!
+//! The waiting time between two attempts doubles with every attempt,
+//! up to a configured maximum, and is varied by a jitter.
+//! Messages that exceed the maximal number of attempts or the
+//! maximal age are given up and moved to the failed messages table.
 
 use libp2p::PeerId;
 use prost::Message;
+use rand::{thread_rng, Rng};
 
 use crate::router;
+use crate::storage::configuration::{Configuration, MessagingOptions};
 use crate::utilities::qaul_id::QaulId;
 use crate::utilities::timestamp::Timestamp;
 
+/// time to wait for the confirmation of a sent message (milliseconds)
+const CONFIRMATION_TIMEOUT: u64 = 20000;
+
 /// Qaul Messaging Structure
 pub struct MessagingRetransmit {}
 
 impl MessagingRetransmit {
     /// process retransmission
     pub fn process() {
-        // get unconfirmed table
-        let unconfirmed = super::UNCONFIRMED.get().write().unwrap();
-        if unconfirmed.unconfirmed.len() == 0 {
-            // there are no message to retrans
-            return;
+        // get messaging options
+        let options: MessagingOptions;
+        {
+            let config = Configuration::get();
+            options = config.messaging.clone();
         }
 
-        // get online users from route table
-        let online_users = router::table::RoutingTable::get_online_users();
+        // messages that were given up
+        let mut failed: Vec<(Vec<u8>, super::UnConfirmedMessage, &str)> = Vec::new();
 
-        let mut updated = false;
-        let cur_time = Timestamp::get_timestamp();
-        for entry in unconfirmed.unconfirmed.iter() {
-            if let Ok((signature, mut unconfirmed_message)) = entry {
-                // let's assume message transmit in 3 seconds
-                if cur_time < (unconfirmed_message.last_sent + 3000) {
-                    continue;
-                }
+        {
+            // get unconfirmed table
+            let unconfirmed = super::UNCONFIRMED.get().write().unwrap();
+            if unconfirmed.unconfirmed.len() == 0 {
+                // there are no message to retrans
+                return;
+            }
 
-                // message scheduled via DTN, ignore retrans
-                if unconfirmed_message.scheduled_dtn {
-                    continue;
-                }
+            // get online users from route table
+            let online_users = router::table::RoutingTable::get_online_users();
+
+            let mut updated = false;
+            let cur_time = Timestamp::get_timestamp();
+            for entry in unconfirmed.unconfirmed.iter() {
+                if let Ok((signature, mut unconfirmed_message)) = entry {
+                    // message scheduled via DTN, ignore retrans
+                    if unconfirmed_message.scheduled_dtn {
+                        continue;
+                    }
+
+                    // custody was transferred to a storage node
+                    if unconfirmed_message.custody {
+                        continue;
+                    }
+
+                    // stored messages are delivered by the DTN service
+                    if let super::MessagingServiceType::DtnStored = unconfirmed_message.message_type
+                    {
+                        continue;
+                    }
+
+                    // check maximal age
+                    if options.message_age_max > 0
+                        && cur_time
+                            > unconfirmed_message.created_at + options.message_age_max * 1000
+                    {
+                        failed.push((signature.to_vec(), unconfirmed_message, "maximal age"));
+                        continue;
+                    }
+
+                    // check if the waiting time expired
+                    let mut timeout = Self::retry_delay(
+                        &options,
+                        unconfirmed_message.retry,
+                        unconfirmed_message.jitter,
+                    );
+                    if unconfirmed_message.scheduled && timeout < CONFIRMATION_TIMEOUT {
+                        timeout = CONFIRMATION_TIMEOUT;
+                    }
+                    if cur_time < (unconfirmed_message.last_sent + timeout) {
+                        continue;
+                    }
 
-                let qaul_id = QaulId::bytes_to_q8id(unconfirmed_message.receiver_id.clone());
-                //1. check receiver is online
-                if let Some(_hc) = online_users.get(&qaul_id) {
-                    let mut timeout: u64 = 0;
-                    if unconfirmed_message.scheduled {
-                        timeout = 20 * 1000;
+                    // check maximal send attempts
+                    if options.retry_max > 0
+                        && unconfirmed_message.scheduled
+                        && unconfirmed_message.retry >= options.retry_max
+                    {
+                        failed.push((
+                            signature.to_vec(),
+                            unconfirmed_message,
+                            "maximal send attempts",
+                        ));
+                        continue;
                     }
 
-                    //check if expired timeout
-                    if cur_time > (timeout + unconfirmed_message.last_sent) {
+                    let qaul_id = QaulId::bytes_to_q8id(unconfirmed_message.receiver_id.clone());
+                    //1. check receiver is online
+                    if let Some(_hc) = online_users.get(&qaul_id) {
                         // queue into messaging queue
                         if let Ok(container) =
                             super::proto::Container::decode(&unconfirmed_message.container[..])
@@ -63,23 +121,37 @@ impl MessagingRetransmit {
                                 "retrans message, signature: {}",
                                 bs58::encode(container.signature.clone()).into_string()
                             );
-                            super::Messaging::schedule_message(
+                            // avoid the route of the last attempt
+                            let mut avoid_route = None;
+                            if unconfirmed_message.scheduled {
+                                if let Ok(node_id) =
+                                    PeerId::from_bytes(&unconfirmed_message.route_node)
+                                {
+                                    avoid_route = Some((node_id, unconfirmed_message.route_module));
+                                }
+                            }
+                            super::Messaging::schedule_retransmission(
                                 receiver.clone(),
                                 container.clone(),
-                                true,
-                                false,
                                 unconfirmed_message.scheduled_dtn,
                                 unconfirmed_message.is_dtn,
+                                avoid_route,
                             );
 
-                            // update entry
-                            let mut new_retry = unconfirmed_message.retry;
-                            if new_retry > 10 {
-                                new_retry = 1;
+                            // the last attempt was not confirmed in time
+                            if unconfirmed_message.scheduled {
+                                super::Messaging::update_link_statistics(
+                                    &unconfirmed_message,
+                                    false,
+                                );
                             }
 
-                            unconfirmed_message.retry = new_retry;
+                            // update entry
+                            if unconfirmed_message.scheduled {
+                                unconfirmed_message.retry = unconfirmed_message.retry + 1;
+                            }
                             unconfirmed_message.last_sent = cur_time;
+                            unconfirmed_message.jitter = thread_rng().gen();
                             if let Err(_e) = unconfirmed
                                 .unconfirmed
                                 .insert(signature, unconfirmed_message.clone())
@@ -92,12 +164,48 @@ impl MessagingRetransmit {
                     }
                 }
             }
-        }
 
-        if updated {
-            if let Err(_e) = unconfirmed.unconfirmed.flush() {
-                log::error!("updating unconfirmed table error!");
+            // remove failed messages
+            for (signature, _, _) in &failed {
+                if let Err(_e) = unconfirmed.unconfirmed.remove(signature) {
+                    log::error!("updating unconfirmed table error!");
+                } else {
+                    updated = true;
+                }
+            }
+
+            if updated {
+                if let Err(_e) = unconfirmed.unconfirmed.flush() {
+                    log::error!("updating unconfirmed table error!");
+                }
             }
         }
+
+        // report failed messages
+        for (signature, unconfirmed_message, reason) in failed {
+            super::Messaging::on_failed_message(&signature, unconfirmed_message, reason);
+        }
+    }
+
+    /// waiting time before the next send attempt (milliseconds)
+    ///
+    /// The waiting time doubles with every attempt until it reaches
+    /// the configured maximum.
+    /// The random jitter is drawn for every send attempt and stored
+    /// with the message, so that it stays the same for every check
+    /// of an attempt.
+    fn retry_delay(options: &MessagingOptions, retry: u32, jitter: u8) -> u64 {
+        let exponent = retry.saturating_sub(1).min(20);
+        let mut delay = (options.retry_interval * 1000).saturating_mul(1 << exponent);
+        if delay > options.retry_interval_max * 1000 {
+            delay = options.retry_interval_max * 1000;
+        }
+
+        // add jitter
+        if options.retry_jitter > 0 {
+            delay = delay + delay * options.retry_jitter as u64 / 100 * jitter as u64 / 255;
+        }
+
+        delay
     }
 }
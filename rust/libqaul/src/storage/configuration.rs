@@ -18,6 +18,8 @@ use std::{
     sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
+use crate::router::link_metric::LinkMetricType;
+
 /// make configuration globally accessible mutable state
 static CONFIG: Storage<RwLock<Configuration>> = Storage::new();
 
@@ -110,6 +112,8 @@ pub struct UserAccount {
     pub id: String,
     pub keys: String,
     pub storage: StorageOptions,
+    #[serde(default)]
+    pub chat: ChatOptions,
 }
 
 impl Default for UserAccount {
@@ -119,6 +123,26 @@ impl Default for UserAccount {
             id: String::from(""),
             keys: String::from(""),
             storage: StorageOptions::default(),
+            chat: ChatOptions::default(),
+        }
+    }
+}
+
+/// Chat Configuration Options
+///
+/// The following options can be configured per user account:
+/// * read receipts
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct ChatOptions {
+    //Send read receipts to the group members.
+    pub read_receipts: bool,
+}
+
+impl Default for ChatOptions {
+    fn default() -> Self {
+        ChatOptions {
+            read_receipts: false, //opt-in
         }
     }
 }
@@ -146,6 +170,7 @@ impl Default for DebugOption {
 /// because rtt is measured as micro seconds
 /// * routing options
 #[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
 pub struct RoutingOptions {
     //Sending the table every 10 seconds to direct neighbours.
     pub sending_table_period: u64,
@@ -153,17 +178,127 @@ pub struct RoutingOptions {
     pub ping_neighbour_period: u64,
     //Hop count penalty.
     pub hop_count_penalty: u64,
+    //Maximal hop count of a route, routes with more hops are dropped.
+    pub hop_count_max: u64,
     //How long a route is stored until it is removed.
     pub maintain_period_limit: u64,
+    //Metric to calculate the link quality of a route.
+    pub link_metric: LinkMetricType,
+    //Sending a full routing information snapshot to neighbours,
+    //between the snapshots only the changes are sent.
+    pub snapshot_period: u64,
+    //Time window in which a message arriving again from another neighbour is a loop, 0 = disabled.
+    pub loop_window: u64,
 }
 
 impl Default for RoutingOptions {
     fn default() -> Self {
         RoutingOptions {
-            sending_table_period: 10,   //10 seconds, unit seconds
-            ping_neighbour_period: 5,   //5  seconds, unit: seconds
-            hop_count_penalty: 10,      //10 seconds, unit: second
-            maintain_period_limit: 300, //5min, unit: second
+            sending_table_period: 10,             //10 seconds, unit seconds
+            ping_neighbour_period: 5,             //5  seconds, unit: seconds
+            hop_count_penalty: 10,                //10 seconds, unit: second
+            hop_count_max: 32,                    //32 hops
+            maintain_period_limit: 300,           //5min, unit: second
+            link_metric: LinkMetricType::Latency, //round trip time & hop count penalty
+            snapshot_period: 60,                  //1min, unit: second
+            loop_window: 10,                      //10 seconds, unit: second
+        }
+    }
+}
+
+/// Messaging Configuration Options
+///
+/// The following options can be configured:
+/// All time units are seconds
+/// * retransmission of unconfirmed messages
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct MessagingOptions {
+    //Waiting time before the first retransmission, doubled with every attempt.
+    pub retry_interval: u64,
+    //Maximal waiting time between two retransmissions.
+    pub retry_interval_max: u64,
+    //Random variation of the waiting time in percent.
+    pub retry_jitter: u32,
+    //Maximal number of send attempts, 0 = unlimited.
+    pub retry_max: u32,
+    //Maximal age of an unconfirmed message, 0 = unlimited.
+    pub message_age_max: u64,
+}
+
+impl Default for MessagingOptions {
+    fn default() -> Self {
+        MessagingOptions {
+            retry_interval: 3,        //3 seconds, unit: seconds
+            retry_interval_max: 600,  //10min, unit: seconds
+            retry_jitter: 20,         //20 percent
+            retry_max: 12,            //12 attempts
+            message_age_max: 1209600, //14 days, unit: seconds
+        }
+    }
+}
+
+/// File Sharing Configuration Options
+///
+/// The following options can be configured:
+/// All size units are bytes
+/// * maximal file chunk size per connection module
+/// * splitting of large files across several routes
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct FileOptions {
+    //Chunk size for receivers reachable via LAN or the local node.
+    pub chunk_size_lan: u32,
+    //Chunk size for receivers reachable via internet.
+    pub chunk_size_internet: u32,
+    //Chunk size for receivers reachable via BLE.
+    pub chunk_size_ble: u32,
+    //Split the chunks of large files across all disjoint routes.
+    pub multipath: bool,
+    //Minimal file size to split a file across several routes.
+    pub multipath_size_min: u64,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        FileOptions {
+            chunk_size_lan: 60000,      //60 KB
+            chunk_size_internet: 60000, //60 KB
+            chunk_size_ble: 4000,       //4 KB
+            multipath: false,
+            multipath_size_min: 1048576, //1 MB
+        }
+    }
+}
+
+/// Feed Configuration Options
+///
+/// The following options can be configured:
+/// All time units are seconds, size units are MB
+/// * retention of public feed messages
+#[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct FeedOptions {
+    //Maximal age of a feed message, 0 = unlimited.
+    pub message_age_max: u64,
+    //Maximal storage size of all feed messages, 0 = unlimited.
+    pub size_max: u32,
+    //Maximal number of messages per sender stored and forwarded within the flood rate period, 0 = unlimited.
+    pub flood_rate_max: u32,
+    //Period of the flood rate limit.
+    pub flood_rate_period: u64,
+    //Time a tombstone of a pruned message is kept, 0 = unlimited.
+    pub tombstone_age_max: u64,
+}
+
+impl Default for FeedOptions {
+    fn default() -> Self {
+        FeedOptions {
+            message_age_max: 7776000,    //90 days, unit: seconds
+            size_max: 64,                //64 MB
+            flood_rate_max: 10,          //10 messages
+            flood_rate_period: 60,       //1min, unit: seconds
+            tombstone_age_max: 15552000, //180 days, unit: seconds
         }
     }
 }
@@ -171,21 +306,47 @@ impl Default for RoutingOptions {
 /// Storage Configuration Options
 ///
 /// The following options can be configured:
-/// size_total units are MB
+/// size_total and user_size_max units are MB
+/// ttl and delivery units are seconds
 /// * storage options
 #[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
 pub struct StorageOptions {
     //storage node users
     pub users: Vec<String>,
     //Sending the table every 10 seconds to direct neighbours.
     pub size_total: u32,
+    //Time to live we request for our own DTN messages, 0 = storage node default.
+    pub ttl_request: u64,
+    //Maximal time to live of DTN messages stored on this node, 0 = unlimited.
+    pub ttl_max: u64,
+    //Maximal storage size per origin user, 0 = unlimited.
+    pub user_size_max: u32,
+    //Maximal number of stored messages per origin user, 0 = unlimited.
+    pub user_message_max: u32,
+    //Number of storage nodes a DTN message is handed to.
+    pub replication: u32,
+    //Interval in which the deliveries of stored messages to a receiver are counted.
+    pub delivery_interval: u64,
+    //Maximal number of stored messages delivered to a receiver per interval.
+    pub delivery_max_messages: u32,
+    //Time to wait for a confirmation before a stored message is delivered again.
+    pub delivery_retry: u64,
 }
 
 impl Default for StorageOptions {
     fn default() -> Self {
         StorageOptions {
             users: vec![],
-            size_total: 1024, //1024 MB
+            size_total: 1024,          //1024 MB
+            ttl_request: 0,            //storage node default
+            ttl_max: 2592000,          //30 days, unit: seconds
+            user_size_max: 0,          //unlimited, unit: MB
+            user_message_max: 0,       //unlimited
+            replication: 2,            //2 storage nodes
+            delivery_interval: 10,     //10 seconds
+            delivery_max_messages: 20, //20 messages per interval
+            delivery_retry: 20,        //20 seconds
         }
     }
 }
@@ -201,6 +362,12 @@ pub struct Configuration {
     pub user_accounts: Vec<UserAccount>,
     pub debug: DebugOption,
     pub routing: RoutingOptions,
+    #[serde(default)]
+    pub messaging: MessagingOptions,
+    #[serde(default)]
+    pub files: FileOptions,
+    #[serde(default)]
+    pub feed: FeedOptions,
 }
 
 impl Default for Configuration {
@@ -212,6 +379,9 @@ impl Default for Configuration {
             user_accounts: Vec::new(),
             debug: DebugOption::default(),
             routing: RoutingOptions::default(),
+            messaging: MessagingOptions::default(),
+            files: FileOptions::default(),
+            feed: FeedOptions::default(),
         }
     }
 }
@@ -293,6 +463,33 @@ impl Configuration {
         }
     }
 
+    // CHANGE: remove this function and save configuration directly via UserAccount
+    pub fn update_user_quota(user_id: String, size: u32, message_count: u32) {
+        let mut config = CONFIG.get().write().unwrap();
+        for i in 0..config.user_accounts.len() {
+            if let Some(user) = config.user_accounts.get_mut(i) {
+                if user.id == user_id {
+                    user.storage.user_size_max = size;
+                    user.storage.user_message_max = message_count;
+                    break;
+                }
+            }
+        }
+    }
+
+    // CHANGE: remove this function and save configuration directly via UserAccount
+    pub fn update_user_read_receipts(user_id: String, enabled: bool) {
+        let mut config = CONFIG.get().write().unwrap();
+        for i in 0..config.user_accounts.len() {
+            if let Some(user) = config.user_accounts.get_mut(i) {
+                if user.id == user_id {
+                    user.chat.read_receipts = enabled;
+                    break;
+                }
+            }
+        }
+    }
+
     /// lend configuration for writing
     pub fn get_mut<'a>() -> RwLockWriteGuard<'a, Configuration> {
         let config_mutable = CONFIG.get().write().unwrap();
@@ -9,6 +9,7 @@
 //! The following upgrades to new versions are included:
 //!
 //! * 2.0.0-beta.9
+//! * 2.0.0-beta.13
 
 use semver::Version;
 use std::fs;
@@ -17,6 +18,7 @@ use std::path::Path;
 use crate::utilities::upgrade::backup::Backup;
 
 pub mod backup;
+mod v2_0_0_beta_13;
 mod v2_0_0_beta_9;
 
 /// upgrade module
@@ -92,6 +94,21 @@ impl Upgrade {
             }
         }
 
+        // upgrade to version 2.0.0-beta.13
+        if version < Version::parse("2.0.0-beta.13").unwrap() {
+            match v2_0_0_beta_13::VersionUpgrade::upgrade(storage_path, &backup_path) {
+                Ok((new_version, new_path)) => {
+                    // update values
+                    version = Version::parse(&new_version).unwrap();
+                    backup_path = new_path;
+                }
+                Err(e) => {
+                    println!("Upgrade to 2.0.0-beta.13 failed: {}", e);
+                    return false;
+                }
+            }
+        }
+
         // restore the upgraded last version
         log::trace!("restore upgraded version {}", version);
         if backup::Backup::restore(&storage_path, &backup_path) == true {
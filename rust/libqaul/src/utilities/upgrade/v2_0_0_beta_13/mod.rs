@@ -0,0 +1,308 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Upgrade to new version 2.0.0-beta.13
+//!
+//! Breaking changes that need to be upgraded:
+//!
+//! * node data base:
+//!   * DTN message entries: origin user, reception time and time to live added
+//!   * unconfirmed messages: custody flag, creation time, retry jitter
+//!     and the route of the last send attempt added
+//! * user data bases:
+//!   * groups and group invites: retention settings added
+//!   * chat messages: reactions and edits added
+//!   * file histories: 64 bit file size and file hash added
+
+use rand::{thread_rng, Rng};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+mod old_db;
+
+use super::backup;
+use crate::connections::ConnectionModule;
+use crate::services::chat::{file::FileHistory, rpc_proto::ChatMessage};
+use crate::services::dtn::DtnMessageEntry;
+use crate::services::group::{Group, GroupInvited, GroupRetention};
+use crate::services::messaging::UnConfirmedMessage;
+use crate::storage::configuration::StorageOptions;
+use crate::utilities::timestamp::Timestamp;
+
+/// # Version Upgrade Logic
+pub struct VersionUpgrade {}
+impl VersionUpgrade {
+    /// Upgrade to new Version
+    ///
+    /// Returns a result, containing a tuple with ( new_version, new_path )
+    pub fn upgrade(storage_path: &Path, old_path: &Path) -> Result<(String, PathBuf), String> {
+        let version = "2.0.0-beta.13";
+        println!("upgrade to version {}", version);
+        let new_path = storage_path.join("backup").join(version);
+
+        // cleanup dest
+        backup::Backup::remove_folder(&new_path);
+
+        // create dest
+        if let Err(_) = std::fs::create_dir(&new_path) {
+            return Err("failed to create destinaton folder".to_string());
+        }
+
+        // move contents
+        println!("move content");
+        if Self::move_content(Path::new(old_path), &new_path) == false {
+            return Err("Error moving content".to_string());
+        }
+
+        // create new version file
+        println!("create version file");
+        let path = Path::new(new_path.to_str().unwrap()).join("version");
+        if let Err(_) = std::fs::write(path, version) {
+            println!("failed to create version file!");
+        }
+
+        // upgrade node data base
+        println!("upgrade node data base");
+        if !Self::upgrade_node_db(&new_path) {
+            return Err("node data base upgrade failed".to_string());
+        }
+
+        // upgrade user data bases
+        println!("upgrade user data bases");
+        if !Self::upgrade_user_dbs(&new_path) {
+            return Err("user data base upgrade failed".to_string());
+        }
+
+        // remove old backup
+        backup::Backup::remove_folder(old_path);
+
+        Ok((version.to_string(), new_path))
+    }
+
+    /// upgrade the DTN message entries and the
+    /// unconfirmed messages of the node data base
+    fn upgrade_node_db(path: &Path) -> bool {
+        let db_path = path.join("node.db");
+        if !db_path.exists() {
+            return true;
+        }
+
+        let db;
+        match sled_extensions::Config::default().path(db_path).open() {
+            Ok(v) => db = v,
+            Err(e) => {
+                println!("Error: open node data base: {}", e);
+                return false;
+            }
+        }
+
+        // stored DTN messages get the current time as reception
+        // time and the default maximal time to live
+        let now = Timestamp::get_timestamp();
+        let ttl = StorageOptions::default().ttl_max;
+        let dtn = Self::upgrade_tree(&db, "dtn-messages", |old: old_db::DtnMessageEntry| {
+            DtnMessageEntry {
+                org_sig: old.org_sig,
+                origin: Vec::new(),
+                size: old.size,
+                received_at: now,
+                ttl,
+            }
+        });
+
+        let unconfirmed =
+            Self::upgrade_tree(&db, "unconfirmed", |old: old_db::UnConfirmedMessage| {
+                UnConfirmedMessage {
+                    receiver_id: old.receiver_id,
+                    message_type: old.message_type,
+                    message_id: old.message_id,
+                    container: old.container,
+                    last_sent: old.last_sent,
+                    retry: old.retry,
+                    scheduled: old.scheduled,
+                    scheduled_dtn: old.scheduled_dtn,
+                    is_dtn: old.is_dtn,
+                    custody: false,
+                    created_at: old.last_sent,
+                    jitter: thread_rng().gen(),
+                    route_node: Vec::new(),
+                    route_module: ConnectionModule::None,
+                    route_hc: 0,
+                }
+            });
+
+        dtn && unconfirmed
+    }
+
+    /// upgrade the groups, the chat messages and the
+    /// file histories of all user data bases
+    fn upgrade_user_dbs(path: &Path) -> bool {
+        for entry_res in std::fs::read_dir(path).unwrap() {
+            let entry = entry_res.unwrap();
+            let file_name_buf = entry.file_name();
+            let file_name = file_name_buf.to_str().unwrap();
+
+            if !entry.file_type().unwrap().is_dir() || !file_name.starts_with("12D3KooW") {
+                continue;
+            }
+
+            let db_path = entry.path().join("user.db");
+            if !db_path.exists() {
+                continue;
+            }
+
+            let db;
+            match sled_extensions::Config::default().path(db_path).open() {
+                Ok(v) => db = v,
+                Err(e) => {
+                    println!("Error: open user data base {}: {}", file_name, e);
+                    return false;
+                }
+            }
+
+            let groups = Self::upgrade_tree(&db, "groups", Self::upgrade_group);
+            let invited =
+                Self::upgrade_tree(&db, "invited", |old: old_db::GroupInvited| GroupInvited {
+                    sender_id: old.sender_id,
+                    received_at: old.received_at,
+                    group: Self::upgrade_group(old.group),
+                });
+            let messages = Self::upgrade_tree(&db, "chat_messages", |old: old_db::ChatMessage| {
+                ChatMessage {
+                    index: old.index,
+                    sender_id: old.sender_id,
+                    message_id: old.message_id,
+                    status: old.status,
+                    message_reception_confirmed: old.message_reception_confirmed,
+                    group_id: old.group_id,
+                    sent_at: old.sent_at,
+                    received_at: old.received_at,
+                    content: old.content,
+                    reactions: Vec::new(),
+                    edited_at: 0,
+                    redacted: false,
+                }
+            });
+            let files =
+                Self::upgrade_tree(&db, "chat_file", |old: old_db::FileHistory| FileHistory {
+                    group_id: old.group_id,
+                    sender_id: old.sender_id,
+                    file_id: old.file_id,
+                    message_id: old.message_id,
+                    start_index: old.start_index,
+                    message_count: old.message_count,
+                    chunk_size: old.chunk_size,
+                    file_state: old.file_state,
+                    reception_tracking: old.reception_tracking,
+                    file_name: old.file_name,
+                    file_description: old.file_description,
+                    file_extension: old.file_extension,
+                    file_size: old.file_size as u64,
+                    sent_at: old.sent_at,
+                    received_at: old.received_at,
+                    file_hash: Vec::new(),
+                });
+
+            if !(groups && invited && messages && files) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// convert a group to the new structure
+    fn upgrade_group(old: old_db::Group) -> Group {
+        Group {
+            id: old.id,
+            name: old.name,
+            is_direct_chat: old.is_direct_chat,
+            created_at: old.created_at,
+            status: old.status,
+            revision: old.revision,
+            members: old.members,
+            unread_messages: old.unread_messages,
+            last_message_at: old.last_message_at,
+            last_message_data: old.last_message_data,
+            last_message_sender_id: old.last_message_sender_id,
+            retention: GroupRetention::default(),
+        }
+    }
+
+    /// upgrade all entries of a data base tree
+    ///
+    /// The entries are loaded with the old structure,
+    /// converted and saved with the new structure.
+    /// Entries that can't be loaded are left unchanged.
+    fn upgrade_tree<O, N, F>(db: &sled_extensions::Db, name: &str, convert: F) -> bool
+    where
+        O: DeserializeOwned,
+        N: Serialize,
+        F: Fn(O) -> N,
+    {
+        let tree;
+        match db.open_tree(name) {
+            Ok(v) => tree = v,
+            Err(e) => {
+                println!("Error: open tree {}: {}", name, e);
+                return false;
+            }
+        }
+
+        for entry in tree.iter() {
+            if let Ok((key, value)) = entry {
+                match bincode::deserialize::<O>(&value) {
+                    Ok(old) => {
+                        if let Ok(data) = bincode::serialize(&convert(old)) {
+                            if let Err(e) = tree.insert(key, data) {
+                                println!("Error: upgrade entry of {}: {}", name, e);
+                                return false;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        println!("Error: unknown entry layout in {}: {}", name, e);
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = tree.flush() {
+            println!("Error: flush {}: {}", name, e);
+            return false;
+        }
+
+        true
+    }
+
+    /// move the content of the old version
+    fn move_content(old_path: &Path, new_path: &Path) -> bool {
+        let mut files: Vec<String> = vec![];
+        let mut folders: Vec<String> = vec![];
+
+        for entry_res in std::fs::read_dir(old_path).unwrap() {
+            let entry = entry_res.unwrap();
+            let file_name_buf = entry.file_name();
+            let file_name = file_name_buf.to_str().unwrap();
+
+            if entry.file_type().unwrap().is_dir() {
+                if file_name.starts_with(".") {
+                    continue;
+                }
+                let path = String::from(file_name);
+                folders.push(path);
+            } else {
+                if file_name == "version" {
+                    continue;
+                }
+                let path = String::from(file_name);
+                files.push(path);
+            }
+        }
+
+        if super::backup::Backup::move_files(&files, old_path, new_path) == false {
+            return false;
+        }
+        super::backup::Backup::move_folders(&folders, old_path, new_path)
+    }
+}
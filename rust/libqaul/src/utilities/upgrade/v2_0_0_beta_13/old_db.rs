@@ -0,0 +1,108 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Data Base Entries from Version 2.0.0-beta.12
+//!
+//! These structures are needed to load the existing data base
+//! entries, which were stored in the layout of version 2.0.0-beta.12.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::services::chat::{
+    file::{FileState, ReceptionTracking},
+    rpc_proto::MessageReceptionConfirmed,
+};
+use crate::services::group::GroupMember;
+use crate::services::messaging::MessagingServiceType;
+
+/// DTN message entry
+///
+/// node data base, tree `dtn-messages`
+#[derive(Deserialize)]
+pub struct DtnMessageEntry {
+    pub org_sig: Vec<u8>,
+    pub size: u32,
+}
+
+/// unconfirmed message
+///
+/// node data base, tree `unconfirmed`
+#[derive(Deserialize)]
+pub struct UnConfirmedMessage {
+    pub receiver_id: Vec<u8>,
+    pub message_type: MessagingServiceType,
+    pub message_id: Vec<u8>,
+    pub container: Vec<u8>,
+    pub last_sent: u64,
+    pub retry: u32,
+    pub scheduled: bool,
+    pub scheduled_dtn: bool,
+    pub is_dtn: bool,
+}
+
+/// group
+///
+/// user data base, tree `groups`
+#[derive(Deserialize)]
+pub struct Group {
+    pub id: Vec<u8>,
+    pub name: String,
+    pub is_direct_chat: bool,
+    pub created_at: u64,
+    pub status: i32,
+    pub revision: u32,
+    pub members: BTreeMap<Vec<u8>, GroupMember>,
+    pub unread_messages: u32,
+    pub last_message_at: u64,
+    pub last_message_data: Vec<u8>,
+    pub last_message_sender_id: Vec<u8>,
+}
+
+/// group invite
+///
+/// user data base, tree `invited`
+#[derive(Deserialize)]
+pub struct GroupInvited {
+    pub sender_id: Vec<u8>,
+    pub received_at: u64,
+    pub group: Group,
+}
+
+/// chat message
+///
+/// user data base, tree `chat_messages`
+#[derive(Deserialize)]
+pub struct ChatMessage {
+    pub index: u64,
+    pub sender_id: Vec<u8>,
+    pub message_id: Vec<u8>,
+    pub status: i32,
+    pub message_reception_confirmed: Vec<MessageReceptionConfirmed>,
+    pub group_id: Vec<u8>,
+    pub sent_at: u64,
+    pub received_at: u64,
+    pub content: Vec<u8>,
+}
+
+/// file history
+///
+/// user data base, tree `chat_file`
+#[derive(Deserialize)]
+pub struct FileHistory {
+    pub group_id: Vec<u8>,
+    pub sender_id: Vec<u8>,
+    pub file_id: u64,
+    pub message_id: Vec<u8>,
+    pub start_index: u32,
+    pub message_count: u32,
+    pub chunk_size: u32,
+    pub file_state: FileState,
+    pub reception_tracking: BTreeMap<Vec<u8>, ReceptionTracking>,
+    pub file_name: String,
+    pub file_description: String,
+    pub file_extension: String,
+    pub file_size: u32,
+    pub sent_at: u64,
+    pub received_at: u64,
+}
@@ -98,7 +98,9 @@ impl VersionUpgrade {
                     storage: crate::storage::configuration::StorageOptions {
                         users: user.storage.users.clone(),
                         size_total: user.storage.size_total,
+                        ..Default::default()
                     },
+                    chat: crate::storage::configuration::ChatOptions::default(),
                 });
             }
 
@@ -109,7 +111,11 @@ impl VersionUpgrade {
                 sending_table_period: old_cfg.routing.sending_table_period,
                 ping_neighbour_period: old_cfg.routing.ping_neighbour_period,
                 hop_count_penalty: old_cfg.routing.hop_count_penalty,
+                hop_count_max: 32,
                 maintain_period_limit: old_cfg.routing.maintain_period_limit,
+                link_metric: Default::default(),
+                snapshot_period: 60,
+                loop_window: 10,
             };
 
             let new_config = crate::storage::configuration::Configuration {
@@ -119,6 +125,9 @@ impl VersionUpgrade {
                 user_accounts,
                 debug,
                 routing,
+                messaging: crate::storage::configuration::MessagingOptions::default(),
+                files: crate::storage::configuration::FileOptions::default(),
+                feed: crate::storage::configuration::FeedOptions::default(),
             };
 
             if let Ok(yaml) = serde_yaml::to_string(&new_config) {
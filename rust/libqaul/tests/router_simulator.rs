@@ -0,0 +1,151 @@
+// Copyright (c) 2021 Open Community Project Association https://ocpa.ch
+// This software is published under the AGPLv3 license.
+
+//! # Router Simulation Tests
+//!
+//! Runs simulated qaul networks over virtual links
+//! and checks the routing tables of the nodes.
+
+use libqaul::simulator::{ConnectionModule, RoutingOptions, Simulator, VirtualLink};
+
+/// run the simulation until all routing tables converged
+///
+/// Returns false if the routing tables did not converge
+/// within the timeout in milli seconds.
+fn run_until_converged(simulator: &mut Simulator, timeout: u64) -> bool {
+    let mut elapsed = 0;
+    while elapsed < timeout {
+        simulator.run(1000);
+        elapsed += 1000;
+        if simulator.is_converged() {
+            return true;
+        }
+    }
+    false
+}
+
+/// create a simulation with the nodes connected in a line
+fn line(seed: u64, count: usize, link: VirtualLink) -> Simulator {
+    let mut simulator = Simulator::new(seed, RoutingOptions::default());
+    for _ in 0..count {
+        simulator.add_node();
+    }
+    for node in 1..count {
+        simulator.connect(node - 1, node, link);
+    }
+    simulator
+}
+
+#[test]
+fn line_converges() {
+    let link = VirtualLink::new(ConnectionModule::Lan, 5, 0);
+    let mut simulator = line(1, 6, link);
+
+    assert!(run_until_converged(&mut simulator, 180_000));
+    assert_eq!(simulator.hop_count(0, 5), Some(5));
+    assert_eq!(simulator.next_hop(0, 5), Some(1));
+    assert_eq!(simulator.next_hop(5, 0), Some(4));
+}
+
+#[test]
+fn lossy_mesh_converges() {
+    let mut simulator = Simulator::new(2, RoutingOptions::default());
+    for _ in 0..9 {
+        simulator.add_node();
+    }
+
+    // 3 x 3 grid
+    let link = VirtualLink::new(ConnectionModule::Internet, 20, 20);
+    for row in 0..3 {
+        for column in 0..3 {
+            let node = row * 3 + column;
+            if column < 2 {
+                simulator.connect(node, node + 1, link);
+            }
+            if row < 2 {
+                simulator.connect(node, node + 3, link);
+            }
+        }
+    }
+
+    assert!(run_until_converged(&mut simulator, 300_000));
+    assert_eq!(simulator.hop_count(0, 8), Some(4));
+    assert!(simulator.statistics().messages_lost > 0);
+}
+
+#[test]
+fn partition_heals() {
+    let link = VirtualLink::new(ConnectionModule::Lan, 5, 0);
+    let mut simulator = line(3, 6, link);
+    assert!(run_until_converged(&mut simulator, 180_000));
+
+    // split the network in two partitions
+    simulator.set_link_up(2, 3, false);
+    assert!(run_until_converged(&mut simulator, 400_000));
+    assert!(!simulator.is_reachable(0, 5));
+    assert!(!simulator.is_reachable(5, 0));
+    assert!(simulator.is_reachable(0, 2));
+    assert!(simulator.is_reachable(3, 5));
+
+    // heal the partition
+    simulator.set_link_up(2, 3, true);
+    assert!(run_until_converged(&mut simulator, 180_000));
+    assert_eq!(simulator.hop_count(0, 5), Some(5));
+    assert_eq!(simulator.hop_count(5, 0), Some(5));
+}
+
+#[test]
+fn route_flapping_settles() {
+    let mut simulator = Simulator::new(4, RoutingOptions::default());
+    for _ in 0..4 {
+        simulator.add_node();
+    }
+
+    // diamond with a fast path via node 1
+    // and a slow path via node 2
+    let fast = VirtualLink::new(ConnectionModule::Lan, 5, 0);
+    simulator.connect(0, 1, fast);
+    simulator.connect(1, 3, fast);
+    simulator.connect(0, 2, VirtualLink::new(ConnectionModule::Lan, 50, 0));
+    simulator.connect(2, 3, VirtualLink::new(ConnectionModule::Lan, 60, 0));
+
+    assert!(run_until_converged(&mut simulator, 180_000));
+    assert_eq!(simulator.next_hop(0, 3), Some(1));
+
+    // flap the fast path
+    for _ in 0..10 {
+        simulator.set_link_up(1, 3, false);
+        simulator.run(20_000);
+        simulator.set_link_up(1, 3, true);
+        simulator.run(20_000);
+    }
+
+    // the routes return to the fast path
+    assert!(run_until_converged(&mut simulator, 300_000));
+    assert_eq!(simulator.next_hop(0, 3), Some(1));
+    assert_eq!(simulator.next_hop(3, 0), Some(1));
+
+    // and stay there
+    let route_changes = simulator.statistics().route_changes;
+    simulator.run(120_000);
+    assert_eq!(simulator.statistics().route_changes, route_changes);
+    assert!(simulator.is_converged());
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let link = VirtualLink::new(ConnectionModule::Lan, 10, 100);
+    let mut first = line(5, 4, link);
+    let mut second = line(5, 4, link);
+
+    first.run(120_000);
+    second.run(120_000);
+
+    assert_eq!(first.statistics(), second.statistics());
+    for node in 0..first.node_count() {
+        assert_eq!(first.node(node).node_id, second.node(node).node_id);
+        for user in 0..first.node_count() {
+            assert_eq!(first.next_hop(node, user), second.next_hop(node, user));
+        }
+    }
+}